@@ -0,0 +1,76 @@
+//! Audit trail: every mutating command records who did it and the affected
+//! row before and after the change, as JSON.
+
+use crate::auth::session::Session;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, Params};
+use serde_json::{Map, Value};
+
+// Columns that must never be copied into the log
+const GIZLI_SAHELER: [&str; 2] = ["sifre", "qifil_sifresi"];
+
+/// Runs `sql` and returns every row as a JSON object keyed by column name.
+pub fn setirler_json<P: Params>(conn: &Connection, sql: &str, params: P) -> Result<Vec<Value>, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let adlar: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut rows = stmt.query(params).map_err(|e| e.to_string())?;
+
+    let mut setirler = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let mut obyekt = Map::new();
+        for (i, ad) in adlar.iter().enumerate() {
+            if GIZLI_SAHELER.contains(&ad.as_str()) {
+                continue;
+            }
+            let deyer = match row.get_ref(i).map_err(|e| e.to_string())? {
+                ValueRef::Null => Value::Null,
+                ValueRef::Integer(n) => Value::from(n),
+                ValueRef::Real(f) => Value::from(f),
+                ValueRef::Text(t) => Value::from(String::from_utf8_lossy(t).into_owned()),
+                ValueRef::Blob(b) => Value::from(format!("<{} bayt>", b.len())),
+            };
+            obyekt.insert(ad.clone(), deyer);
+        }
+        setirler.push(Value::Object(obyekt));
+    }
+
+    Ok(setirler)
+}
+
+/// Same as [`setirler_json`] but only the first row.
+pub fn setir_json<P: Params>(conn: &Connection, sql: &str, params: P) -> Result<Option<Value>, String> {
+    Ok(setirler_json(conn, sql, params)?.into_iter().next())
+}
+
+/// Snapshot of a row in `cedvel` by its id.
+pub fn setir(conn: &Connection, cedvel: &str, id: i64) -> Result<Option<Value>, String> {
+    setir_json(conn, &format!("SELECT * FROM {} WHERE id = ?1", cedvel), [id])
+}
+
+/// Writes one audit entry. Call it on the same connection or transaction as the change.
+pub fn qeyde_al(
+    conn: &Connection,
+    session: &Session,
+    emr: &str,
+    obyekt: &str,
+    obyekt_id: Option<i64>,
+    evvel: Option<Value>,
+    sonra: Option<Value>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO audit_log (istifadeci_id, istifadeci_adi, emr, obyekt, obyekt_id, evvel, sonra)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            session.istifadeci_id,
+            session.istifadeci_adi,
+            emr,
+            obyekt,
+            obyekt_id,
+            evvel.map(|v| v.to_string()),
+            sonra.map(|v| v.to_string()),
+        ],
+    )
+    .map_err(|e| format!("Audit qeydi yazıla bilmədi: {}", e))?;
+
+    Ok(())
+}
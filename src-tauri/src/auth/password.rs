@@ -0,0 +1,21 @@
+use bcrypt::{hash, verify, DEFAULT_COST};
+
+/// Hashes a password with bcrypt for storage in `users.sifre`.
+pub fn sifre_hash(sifre: &str) -> Result<String, String> {
+    hash(sifre, DEFAULT_COST).map_err(|e| format!("Şifrə hash edilə bilmədi: {}", e))
+}
+
+/// Whether a stored password is already a bcrypt hash (legacy rows are plaintext).
+pub fn hash_olunub(saxlanilan: &str) -> bool {
+    saxlanilan.len() == 60 && saxlanilan.starts_with("$2")
+}
+
+/// Checks an entered password against the stored value.
+/// Legacy plaintext rows are still accepted so they can be rehashed on login.
+pub fn sifre_yoxla(daxil_edilen: &str, saxlanilan: &str) -> bool {
+    if hash_olunub(saxlanilan) {
+        verify(daxil_edilen, saxlanilan).unwrap_or(false)
+    } else {
+        daxil_edilen == saxlanilan
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+// Sessions expire after this much inactivity
+const SESSIYA_MUDDETI: Duration = Duration::from_secs(12 * 60 * 60);
+
+pub const SESSIYA_XETASI: &str = "Sessiya etibarsızdır, yenidən daxil olun";
+pub const ICAZE_XETASI: &str = "Bu əməliyyat üçün admin icazəsi lazımdır";
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub istifadeci_id: i64,
+    pub istifadeci_adi: String,
+    pub rol: String,
+    son_aktivlik: Instant,
+}
+
+impl Session {
+    pub fn admin_dir(&self) -> bool {
+        self.rol == "admin"
+    }
+}
+
+/// In-memory store of logged-in users, keyed by the token issued at login.
+#[derive(Default)]
+pub struct SessionStore {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionStore {
+    pub fn yarat(&self, istifadeci_id: i64, istifadeci_adi: &str, rol: &str) -> Result<String, String> {
+        let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+
+        // Drop expired sessions while we hold the lock
+        sessions.retain(|_, s| s.son_aktivlik.elapsed() < SESSIYA_MUDDETI);
+
+        let token = Uuid::new_v4().to_string();
+        sessions.insert(
+            token.clone(),
+            Session {
+                istifadeci_id,
+                istifadeci_adi: istifadeci_adi.to_string(),
+                rol: rol.to_string(),
+                son_aktivlik: Instant::now(),
+            },
+        );
+
+        Ok(token)
+    }
+
+    /// Returns the caller's session, refreshing its activity time.
+    pub fn yoxla(&self, token: &str) -> Result<Session, String> {
+        let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+
+        let session = match sessions.get_mut(token) {
+            Some(s) if s.son_aktivlik.elapsed() < SESSIYA_MUDDETI => s,
+            Some(_) => {
+                sessions.remove(token);
+                return Err(SESSIYA_XETASI.to_string());
+            }
+            None => return Err(SESSIYA_XETASI.to_string()),
+        };
+
+        session.son_aktivlik = Instant::now();
+        Ok(session.clone())
+    }
+
+    /// Like `yoxla`, but also requires the "admin" role.
+    pub fn admin_yoxla(&self, token: &str) -> Result<Session, String> {
+        let session = self.yoxla(token)?;
+        if !session.admin_dir() {
+            return Err(ICAZE_XETASI.to_string());
+        }
+        Ok(session)
+    }
+
+    pub fn sil(&self, token: &str) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        sessions.remove(token);
+        Ok(())
+    }
+
+    /// Ends every session of a user (after deactivation, deletion or a role change).
+    pub fn istifadecini_sil(&self, istifadeci_id: i64) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        sessions.retain(|_, s| s.istifadeci_id != istifadeci_id);
+        Ok(())
+    }
+
+    /// Ends every session, e.g. after the database was restored from a backup.
+    pub fn hamisini_sil(&self) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        sessions.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! Barcodes: check digits for EAN-8, EAN-13 and UPC-A, internal EAN-13 codes
+//! for goods that come without a manufacturer barcode, and label images.
+
+mod naxis;
+pub mod sekil;
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// GS1 keeps 20–29 for in-store numbering, so internal codes never clash
+/// with a real product's barcode.
+pub const STANDART_PREFIKS: &str = "200";
+
+const MAKS_UZUNLUQ: usize = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Simvolika {
+    #[serde(rename = "EAN-13")]
+    Ean13,
+    #[serde(rename = "EAN-8")]
+    Ean8,
+    #[serde(rename = "UPC-A")]
+    UpcA,
+    #[serde(rename = "Code128")]
+    Code128,
+}
+
+/// GTIN check digit for the digits before it.
+fn yoxlama_reqemi(reqemler: &[u8]) -> u8 {
+    // Weights run 3, 1, 3, ... from the right-hand end
+    let cem: u32 = reqemler
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, r)| *r as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    ((10 - cem % 10) % 10) as u8
+}
+
+fn reqemlere_ayir(barkod: &str) -> Option<Vec<u8>> {
+    barkod
+        .chars()
+        .map(|c| c.to_digit(10).map(|r| r as u8))
+        .collect()
+}
+
+/// Works out how a barcode will be printed and rejects it if it cannot be.
+///
+/// Numeric codes of 8, 12 or 13 digits are EAN-8, UPC-A and EAN-13 and must
+/// carry a correct check digit; anything else is printed as Code128.
+pub fn yoxla(barkod: &str) -> Result<Simvolika, String> {
+    if barkod.is_empty() {
+        return Err("Barkod boş ola bilməz".to_string());
+    }
+    if barkod.len() > MAKS_UZUNLUQ {
+        return Err(format!("Barkod {} simvoldan uzun ola bilməz", MAKS_UZUNLUQ));
+    }
+
+    if let Some(reqemler) = reqemlere_ayir(barkod) {
+        let simvolika = match reqemler.len() {
+            8 => Some(Simvolika::Ean8),
+            12 => Some(Simvolika::UpcA),
+            13 => Some(Simvolika::Ean13),
+            _ => None,
+        };
+        if let Some(simvolika) = simvolika {
+            let (melumat, yoxlama) = reqemler.split_at(reqemler.len() - 1);
+            let gozlenilen = yoxlama_reqemi(melumat);
+            if yoxlama[0] != gozlenilen {
+                return Err(format!(
+                    "Barkodun yoxlama rəqəmi yanlışdır: {} (sonuncu rəqəm {} olmalıdır)",
+                    barkod, gozlenilen
+                ));
+            }
+            return Ok(simvolika);
+        }
+    }
+
+    if !barkod.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+        return Err("Barkodda yalnız latın hərfləri, rəqəmlər və işarələr ola bilər".to_string());
+    }
+
+    Ok(Simvolika::Code128)
+}
+
+/// Checks the store prefix for internal codes before it is saved to settings.
+pub fn prefiks_yoxla(prefiks: &str) -> Result<(), String> {
+    let duzgun = (2..=6).contains(&prefiks.len())
+        && prefiks.starts_with('2')
+        && prefiks.chars().all(|c| c.is_ascii_digit());
+    if !duzgun {
+        return Err("Barkod prefiksi 2 ilə başlayan 2–6 rəqəm olmalıdır (məsələn, 200)".to_string());
+    }
+    Ok(())
+}
+
+/// Issues the next unused internal EAN-13 from the prefix in settings.
+pub fn daxili_yarat(conn: &Connection) -> Result<String, String> {
+    let prefiks: String = conn
+        .query_row("SELECT barkod_prefiksi FROM settings WHERE id = 1", [], |row| {
+            row.get::<_, Option<String>>(0)
+        })
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .unwrap_or_else(|| STANDART_PREFIKS.to_string());
+    prefiks_yoxla(&prefiks)?;
+
+    let uzunluq = 12 - prefiks.len();
+    let tutum = 10_i64.pow(uzunluq as u32);
+
+    // Every prefix keeps its own counter; codes typed in by hand are skipped
+    loop {
+        let nomre: i64 = conn
+            .query_row(
+                "INSERT INTO sequences (ad, il, son_nomre) VALUES (?1, 0, 1)
+                 ON CONFLICT(ad) DO UPDATE SET
+                    son_nomre = son_nomre + 1,
+                    updated_at = CURRENT_TIMESTAMP
+                 RETURNING son_nomre",
+                [format!("barkod-{}", prefiks)],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Barkod nömrəsi ayrıla bilmədi: {}", e))?;
+
+        if nomre >= tutum {
+            return Err(format!(
+                "{} prefiksi ilə bütün barkodlar istifadə olunub, parametrlərdə prefiksi dəyişin",
+                prefiks
+            ));
+        }
+
+        let melumat = format!("{}{:0width$}", prefiks, nomre, width = uzunluq);
+        let reqemler = reqemlere_ayir(&melumat).unwrap_or_default();
+        let barkod = format!("{}{}", melumat, yoxlama_reqemi(&reqemler));
+
+        let istifade_olunub: bool = conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM products WHERE barkod = ?1)
+                     OR EXISTS (SELECT 1 FROM variants WHERE barkod = ?1)",
+                [&barkod],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        if !istifade_olunub {
+            return Ok(barkod);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Bar patterns: one `bool` per module, `true` for a dark bar.
+
+use super::Simvolika;
+
+// EAN/UPC digit codes, seven modules each, '1' is a bar
+const L_KODLAR: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011",
+    "0110001", "0101111", "0111011", "0110111", "0001011",
+];
+const G_KODLAR: [&str; 10] = [
+    "0100111", "0110011", "0011011", "0100001", "0011101",
+    "0111001", "0000101", "0010001", "0001001", "0010111",
+];
+const R_KODLAR: [&str; 10] = [
+    "1110010", "1100110", "1101100", "1000010", "1011100",
+    "1001110", "1010000", "1000100", "1001000", "1110100",
+];
+
+// Which of the six left-hand EAN-13 digits use the G set, by the first digit
+const EAN13_PARITET: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG",
+    "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL", "LGGLGL",
+];
+
+// Code128 symbol widths (bar, space, bar, ...), indexed by symbol value
+const CODE128: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212", "221213",
+    "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221", "223211", "221132",
+    "221231", "213212", "223112", "312131", "311222", "321122", "321221", "312212", "322112", "322211",
+    "212123", "212321", "232121", "111323", "131123", "131321", "112313", "132113", "132311", "211313",
+    "231113", "231311", "112133", "112331", "132131", "113123", "113321", "133121", "313121", "211331",
+    "231131", "213113", "213311", "213131", "311123", "311321", "331121", "312113", "312311", "332111",
+    "314111", "221411", "431111", "111224", "111422", "121124", "121421", "141122", "141221", "112214",
+    "112412", "122114", "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111",
+    "111242", "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311", "113141",
+    "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+const CODE128_BASLA_B: usize = 104;
+const CODE128_BASLA_C: usize = 105;
+const CODE128_DAYAN: usize = 106;
+
+fn bitler(naxis: &mut Vec<bool>, kod: &str) {
+    naxis.extend(kod.chars().map(|c| c == '1'));
+}
+
+fn reqem(c: char) -> usize {
+    c.to_digit(10).unwrap_or(0) as usize
+}
+
+fn ean13(barkod: &str) -> Vec<bool> {
+    let reqemler: Vec<usize> = barkod.chars().map(reqem).collect();
+    let paritet = EAN13_PARITET[reqemler[0]];
+
+    let mut naxis = Vec::with_capacity(95);
+    bitler(&mut naxis, "101");
+    for (r, p) in reqemler[1..7].iter().zip(paritet.chars()) {
+        bitler(&mut naxis, if p == 'G' { G_KODLAR[*r] } else { L_KODLAR[*r] });
+    }
+    bitler(&mut naxis, "01010");
+    for r in &reqemler[7..13] {
+        bitler(&mut naxis, R_KODLAR[*r]);
+    }
+    bitler(&mut naxis, "101");
+    naxis
+}
+
+fn ean8(barkod: &str) -> Vec<bool> {
+    let reqemler: Vec<usize> = barkod.chars().map(reqem).collect();
+
+    let mut naxis = Vec::with_capacity(67);
+    bitler(&mut naxis, "101");
+    for r in &reqemler[..4] {
+        bitler(&mut naxis, L_KODLAR[*r]);
+    }
+    bitler(&mut naxis, "01010");
+    for r in &reqemler[4..] {
+        bitler(&mut naxis, R_KODLAR[*r]);
+    }
+    bitler(&mut naxis, "101");
+    naxis
+}
+
+fn code128_simvol(naxis: &mut Vec<bool>, deyer: usize) {
+    for (i, en) in CODE128[deyer].chars().enumerate() {
+        let bar = i % 2 == 0;
+        naxis.extend(std::iter::repeat_n(bar, reqem(en)));
+    }
+}
+
+/// Code set C packs digit pairs, so long numbers come out half as wide;
+/// anything else goes through set B.
+fn code128(barkod: &str) -> Vec<bool> {
+    let mut deyerler = Vec::new();
+    let simvollar: Vec<char> = barkod.chars().collect();
+    let cut_reqemler = simvollar.len() % 2 == 0
+        && simvollar.len() >= 4
+        && simvollar.iter().all(char::is_ascii_digit);
+
+    if cut_reqemler {
+        deyerler.push(CODE128_BASLA_C);
+        for cut in simvollar.chunks(2) {
+            deyerler.push(reqem(cut[0]) * 10 + reqem(cut[1]));
+        }
+    } else {
+        deyerler.push(CODE128_BASLA_B);
+        deyerler.extend(simvollar.iter().map(|c| *c as usize - 32));
+    }
+
+    let yoxlama = deyerler
+        .iter()
+        .enumerate()
+        .map(|(i, d)| d * i.max(1))
+        .sum::<usize>()
+        % 103;
+    deyerler.push(yoxlama);
+    deyerler.push(CODE128_DAYAN);
+
+    let mut naxis = Vec::new();
+    for deyer in deyerler {
+        code128_simvol(&mut naxis, deyer);
+    }
+    naxis
+}
+
+/// Bars for a barcode already accepted by `yoxla`.
+pub fn modullar(barkod: &str, simvolika: Simvolika) -> Vec<bool> {
+    match simvolika {
+        Simvolika::Ean13 => ean13(barkod),
+        Simvolika::UpcA => ean13(&format!("0{}", barkod)),
+        Simvolika::Ean8 => ean8(barkod),
+        Simvolika::Code128 => code128(barkod),
+    }
+}
@@ -0,0 +1,111 @@
+//! Barcode images for labels: SVG with the human-readable text underneath, or
+//! a PNG as a data URL for the webview to print.
+
+use super::{naxis, yoxla};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+// Quiet zone either side of the bars, in modules
+const SESSIZ_ZONA: usize = 10;
+const BAR_HUNDURLUYU: usize = 60;
+const METN_HUNDURLUYU: usize = 14;
+// PNG pixels per module; thermal label printers are at least 203 dpi
+const PIKSEL: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SekilFormati {
+    Svg,
+    Png,
+}
+
+fn xml_qacir(metn: &str) -> String {
+    metn.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn svg(barkod: &str, modullar: &[bool]) -> String {
+    let en = modullar.len() + 2 * SESSIZ_ZONA;
+    let hundurluk = BAR_HUNDURLUYU + METN_HUNDURLUYU;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {en} {hundurluk}\" \
+         width=\"{en}\" height=\"{hundurluk}\" shape-rendering=\"crispEdges\">\
+         <rect width=\"100%\" height=\"100%\" fill=\"#fff\"/>"
+    );
+
+    // Neighbouring dark modules are drawn as one wide bar
+    let mut i = 0;
+    while i < modullar.len() {
+        if !modullar[i] {
+            i += 1;
+            continue;
+        }
+        let baslangic = i;
+        while i < modullar.len() && modullar[i] {
+            i += 1;
+        }
+        svg.push_str(&format!(
+            "<rect x=\"{}\" width=\"{}\" height=\"{}\"/>",
+            baslangic + SESSIZ_ZONA,
+            i - baslangic,
+            BAR_HUNDURLUYU
+        ));
+    }
+
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"11\" text-anchor=\"middle\">{}</text></svg>",
+        en / 2,
+        hundurluk - 2,
+        xml_qacir(barkod)
+    ));
+    svg
+}
+
+fn png(modullar: &[bool]) -> Result<Vec<u8>, String> {
+    let en = (modullar.len() + 2 * SESSIZ_ZONA) * PIKSEL;
+    let hundurluk = BAR_HUNDURLUYU * PIKSEL;
+
+    // Every pixel row is the same, so one is built and repeated
+    let mut setir = vec![255u8; en];
+    for (i, bar) in modullar.iter().enumerate() {
+        if *bar {
+            let x = (i + SESSIZ_ZONA) * PIKSEL;
+            setir[x..x + PIKSEL].fill(0);
+        }
+    }
+    let pikseller = setir.repeat(hundurluk);
+
+    let mut bayt = Vec::new();
+    let mut kodlayici = png::Encoder::new(&mut bayt, en as u32, hundurluk as u32);
+    kodlayici.set_color(png::ColorType::Grayscale);
+    kodlayici.set_depth(png::BitDepth::Eight);
+    let mut yazici = kodlayici
+        .write_header()
+        .map_err(|e| format!("Barkod şəkli yaradıla bilmədi: {}", e))?;
+    yazici
+        .write_image_data(&pikseller)
+        .map_err(|e| format!("Barkod şəkli yaradıla bilmədi: {}", e))?;
+    drop(yazici);
+
+    Ok(bayt)
+}
+
+/// Renders a barcode; SVG comes back as markup, PNG as a data URL.
+pub fn ciz(barkod: &str, format: SekilFormati) -> Result<String, String> {
+    let simvolika = yoxla(barkod)?;
+    let modullar = naxis::modullar(barkod, simvolika);
+
+    match format {
+        SekilFormati::Svg => Ok(svg(barkod, &modullar)),
+        SekilFormati::Png => {
+            let bayt = png(&modullar)?;
+            Ok(format!(
+                "data:image/png;base64,{}",
+                base64::engine::general_purpose::STANDARD.encode(bayt)
+            ))
+        }
+    }
+}
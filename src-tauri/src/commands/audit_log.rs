@@ -0,0 +1,98 @@
+use crate::commands::report::secimli_tarix_araligi;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::State;
+
+const STANDART_LIMIT: i64 = 500;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub istifadeci_id: i64,
+    pub istifadeci_adi: String,
+    pub emr: String,
+    pub obyekt: String,
+    pub obyekt_id: Option<i64>,
+    pub evvel: Option<Value>,
+    pub sonra: Option<Value>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AuditFilter {
+    pub istifadeci_id: Option<i64>,
+    pub emr: Option<String>,
+    pub obyekt: Option<String>,
+    pub obyekt_id: Option<i64>,
+    pub baslangic_tarix: Option<String>,
+    pub bitis_tarix: Option<String>,
+    pub limit: Option<i64>,
+}
+
+fn json_oxu(metn: Option<String>) -> Option<Value> {
+    metn.and_then(|m| serde_json::from_str(&m).ok())
+}
+
+/// Newest-first audit entries; every filter is optional.
+#[tauri::command]
+pub async fn audit_jurnali(
+    state: State<'_, AppState>,
+    token: String,
+    filter: Option<AuditFilter>,
+) -> Result<Vec<AuditEntry>, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let filter = filter.unwrap_or_default();
+    let (baslangic, bitis) = secimli_tarix_araligi(filter.baslangic_tarix, filter.bitis_tarix)?;
+    let limit = filter.limit.filter(|l| *l > 0).unwrap_or(STANDART_LIMIT);
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = db
+        .conn
+        .prepare(
+            "SELECT id, istifadeci_id, istifadeci_adi, emr, obyekt, obyekt_id, evvel, sonra, created_at
+             FROM audit_log
+             WHERE (?1 IS NULL OR istifadeci_id = ?1)
+               AND (?2 IS NULL OR emr = ?2)
+               AND (?3 IS NULL OR obyekt = ?3)
+               AND (?4 IS NULL OR obyekt_id = ?4)
+               AND (?5 IS NULL OR date(created_at) >= ?5)
+               AND (?6 IS NULL OR date(created_at) <= ?6)
+             ORDER BY id DESC
+             LIMIT ?7",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(
+            rusqlite::params![
+                filter.istifadeci_id,
+                filter.emr,
+                filter.obyekt,
+                filter.obyekt_id,
+                baslangic,
+                bitis,
+                limit,
+            ],
+            |row| {
+                Ok(AuditEntry {
+                    id: row.get(0)?,
+                    istifadeci_id: row.get(1)?,
+                    istifadeci_adi: row.get(2)?,
+                    emr: row.get(3)?,
+                    obyekt: row.get(4)?,
+                    obyekt_id: row.get(5)?,
+                    evvel: json_oxu(row.get(6)?),
+                    sonra: json_oxu(row.get(7)?),
+                    created_at: row.get(8)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(entries)
+}
@@ -0,0 +1,69 @@
+use crate::audit;
+use crate::db::backup::{self, AutoBackupStatus, BackupFile};
+use crate::AppState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn ehtiyat_yarat(state: State<'_, AppState>, token: String) -> Result<BackupFile, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let yol = backup::snapshot_yarat(&db.conn, backup::EL_ILE)?;
+    let fayl = backup::fayl_melumati(&yol)?;
+
+    audit::qeyde_al(
+        &db.conn,
+        &session,
+        "ehtiyat_yarat",
+        "database",
+        None,
+        None,
+        Some(serde_json::json!({ "fayl": fayl.ad })),
+    )?;
+
+    Ok(fayl)
+}
+
+#[tauri::command]
+pub async fn ehtiyat_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<BackupFile>, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    backup::siyahi()
+}
+
+/// How the last automatic backup went; `son_yoxlama` is empty until it first runs.
+#[tauri::command]
+pub async fn ehtiyat_veziyyeti(state: State<'_, AppState>, token: String) -> Result<AutoBackupStatus, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let veziyyet = state.ehtiyat_veziyyeti.lock().map_err(|e| e.to_string())?;
+    Ok(veziyyet.clone())
+}
+
+/// Restores a backup from `ehtiyat_siyahisi`. Everyone is logged out afterwards,
+/// since the users table may have changed.
+#[tauri::command]
+pub async fn ehtiyat_berpa_et(state: State<'_, AppState>, token: String, ad: String) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let yol = backup::fayl_yolu(&ad)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+
+    db.berpa_et(&yol)?;
+
+    audit::qeyde_al(
+        &db.conn,
+        &session,
+        "ehtiyat_berpa_et",
+        "database",
+        None,
+        None,
+        Some(serde_json::json!({ "fayl": ad })),
+    )?;
+
+    state.sessions.hamisini_sil()?;
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::barcode::{self, sekil::SekilFormati, Simvolika};
+use crate::AppState;
+use tauri::State;
+
+/// Issues the next internal barcode for a product that has none.
+#[tauri::command]
+pub async fn barkod_yarat(state: State<'_, AppState>, token: String) -> Result<String, String> {
+    state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    barcode::daxili_yarat(&db.conn)
+}
+
+#[tauri::command]
+pub async fn barkod_yoxla(
+    state: State<'_, AppState>,
+    token: String,
+    barkod: String,
+) -> Result<Simvolika, String> {
+    state.sessions.yoxla(&token)?;
+
+    barcode::yoxla(barkod.trim())
+}
+
+/// Label image: SVG markup, or a PNG data URL.
+#[tauri::command]
+pub async fn barkod_sekli(
+    state: State<'_, AppState>,
+    token: String,
+    barkod: String,
+    format: SekilFormati,
+) -> Result<String, String> {
+    state.sessions.yoxla(&token)?;
+
+    barcode::sekil::ciz(barkod.trim(), format)
+}
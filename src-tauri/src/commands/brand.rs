@@ -0,0 +1,224 @@
+use crate::audit;
+use crate::auth::session::Session;
+use crate::models::{Brand, CreateBrand};
+use crate::promotions;
+use crate::AppState;
+use rusqlite::{Connection, OptionalExtension};
+use tauri::State;
+
+fn marka_oxu(conn: &Connection, id: i64) -> Result<Brand, String> {
+    conn.query_row(
+        "SELECT id, ad, created_at FROM brands WHERE id = ?1",
+        [id],
+        |row| {
+            Ok(Brand {
+                id: row.get(0)?,
+                ad: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Marka tapılmadı: {}", e))
+}
+
+// "Zara", "ZARA" and "zara " are one brand. Compared in Rust because
+// SQLite's NOCASE only folds ASCII letters.
+fn eyni_adli_marka(conn: &Connection, ad: &str, istisna: Option<i64>) -> Result<Option<(i64, String)>, String> {
+    let acar = ad.trim().to_lowercase();
+    let mut stmt = conn
+        .prepare("SELECT id, ad FROM brands WHERE ?1 IS NULL OR id <> ?1")
+        .map_err(|e| e.to_string())?;
+    let markalar = stmt
+        .query_map([istisna], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(markalar.into_iter().find(|(_, ad)| ad.trim().to_lowercase() == acar))
+}
+
+/// Works out a product's brand from an id or a typed name, creating the
+/// brand when the name is new; the new brand is audited under `emr`.
+/// Returns the id and the name as stored.
+pub(crate) fn marka_hell_et(
+    conn: &Connection,
+    session: &Session,
+    emr: &str,
+    marka_id: Option<i64>,
+    marka: Option<&str>,
+) -> Result<(Option<i64>, Option<String>), String> {
+    if let Some(id) = marka_id {
+        let marka = marka_oxu(conn, id)?;
+        return Ok((Some(marka.id), Some(marka.ad)));
+    }
+
+    let ad = match marka.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(ad) => ad,
+        None => return Ok((None, None)),
+    };
+
+    if let Some((id, ad)) = eyni_adli_marka(conn, ad, None)? {
+        return Ok((Some(id), Some(ad)));
+    }
+
+    conn.execute("INSERT INTO brands (ad) VALUES (?1)", [ad])
+        .map_err(|e| format!("Marka əlavə edilə bilmədi: {}", e))?;
+    let id = conn.last_insert_rowid();
+
+    audit::qeyde_al(conn, session, emr, "brands", Some(id), None, audit::setir(conn, "brands", id)?)?;
+
+    Ok((Some(id), Some(ad.to_string())))
+}
+
+#[tauri::command]
+pub async fn marka_elave_et(
+    state: State<'_, AppState>,
+    token: String,
+    marka: CreateBrand,
+) -> Result<Brand, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let ad = marka.ad.trim();
+    if ad.is_empty() {
+        return Err("Marka adı boş ola bilməz".to_string());
+    }
+    if let Some((_, movcud)) = eyni_adli_marka(&tx, ad, None)? {
+        return Err(format!("Bu marka artıq mövcuddur: {}", movcud));
+    }
+
+    tx
+        .execute("INSERT INTO brands (ad) VALUES (?1)", [ad])
+        .map_err(|e| format!("Marka əlavə edilə bilmədi: {}", e))?;
+
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "marka_elave_et",
+        "brands",
+        Some(id),
+        None,
+        audit::setir(&tx, "brands", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    marka_oxu(&db.conn, id)
+}
+
+#[tauri::command]
+pub async fn marka_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Brand>, String> {
+    state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = db.conn
+        .prepare("SELECT id, ad, created_at FROM brands ORDER BY ad")
+        .map_err(|e| e.to_string())?;
+
+    let brands = stmt
+        .query_map([], |row| {
+            Ok(Brand {
+                id: row.get(0)?,
+                ad: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(brands)
+}
+
+/// Renaming a brand renames it on every product that carries it.
+#[tauri::command]
+pub async fn marka_yenile(
+    state: State<'_, AppState>,
+    token: String,
+    id: i64,
+    ad: String,
+) -> Result<Brand, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let ad = ad.trim();
+    if ad.is_empty() {
+        return Err("Marka adı boş ola bilməz".to_string());
+    }
+    if let Some((_, movcud)) = eyni_adli_marka(&tx, ad, Some(id))? {
+        return Err(format!("Bu marka artıq mövcuddur: {}", movcud));
+    }
+
+    let evvel = audit::setir(&tx, "brands", id)?;
+
+    tx.execute(
+        "UPDATE brands SET ad = ?1 WHERE id = ?2",
+        rusqlite::params![ad, id],
+    )
+    .map_err(|e| format!("Marka yenilənə bilmədi: {}", e))?;
+    tx.execute(
+        "UPDATE products SET marka = ?1, updated_at = CURRENT_TIMESTAMP WHERE marka_id = ?2",
+        rusqlite::params![ad, id],
+    )
+    .map_err(|e| format!("Marka yenilənə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "marka_yenile",
+        "brands",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "brands", id)?,
+    )?;
+
+    let brand = marka_oxu(&tx, id)?;
+
+    tx.commit().map_err(|e| format!("Marka yadda saxlanıla bilmədi: {}", e))?;
+
+    Ok(brand)
+}
+
+#[tauri::command]
+pub async fn marka_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "brands", id)?;
+
+    let istifade_olunur: Option<i64> = tx
+        .query_row("SELECT id FROM products WHERE marka_id = ?1 LIMIT 1", [id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if istifade_olunur.is_some() {
+        return Err("Bu markanın məhsulları var, əvvəlcə onları başqa markaya keçirin".to_string());
+    }
+    promotions::aid_kampaniya_yoxla(&tx, "marka_id", id)?;
+
+    tx
+        .execute("DELETE FROM brands WHERE id = ?1", [id])
+        .map_err(|e| format!("Marka silinə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "marka_sil",
+        "brands",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
@@ -1,32 +1,80 @@
+use crate::audit;
+use crate::commands::stock;
 use crate::models::{Category, CreateCategory};
+use crate::promotions;
 use crate::AppState;
+use rusqlite::Connection;
 use tauri::State;
 
+/// Recursive CTE `alt_kateqoriyalar(id)`: the category bound to `param` and
+/// every category below it. A NULL parameter gives an empty tree.
+pub(crate) fn alt_kateqoriyalar_cte(param: &str) -> String {
+    format!(
+        "alt_kateqoriyalar(id) AS (
+            SELECT {}
+            UNION
+            SELECT c.id FROM categories c JOIN alt_kateqoriyalar a ON c.parent_id = a.id
+         )",
+        param
+    )
+}
+
+// True when `diger` is `id` itself or somewhere below it
+fn alt_agacdadir(conn: &Connection, id: i64, diger: i64) -> Result<bool, String> {
+    conn.query_row(
+        &format!(
+            "WITH RECURSIVE {} SELECT EXISTS(SELECT 1 FROM alt_kateqoriyalar WHERE id = ?2)",
+            alt_kateqoriyalar_cte("?1")
+        ),
+        [id, diger],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn kateqoriya_elave_et(
     state: State<'_, AppState>,
+    token: String,
     kateqoriya: CreateCategory,
 ) -> Result<Category, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
     
-    db.conn
+    tx
         .execute(
-            "INSERT INTO categories (ad) VALUES (?1)",
-            [&kateqoriya.ad],
+            "INSERT INTO categories (ad, parent_id, olcu_qrupu_id) VALUES (?1, ?2, ?3)",
+            rusqlite::params![&kateqoriya.ad, kateqoriya.parent_id, kateqoriya.olcu_qrupu_id],
         )
         .map_err(|e| format!("Kateqoriya əlavə edilə bilmədi: {}", e))?;
     
-    let id = db.conn.last_insert_rowid();
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kateqoriya_elave_et",
+        "categories",
+        Some(id),
+        None,
+        audit::setir(&tx, "categories", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
     
     let category = db.conn
         .query_row(
-            "SELECT id, ad, created_at FROM categories WHERE id = ?1",
+            "SELECT id, ad, parent_id, olcu_qrupu_id, created_at FROM categories WHERE id = ?1",
             [id],
             |row| {
                 Ok(Category {
                     id: row.get(0)?,
                     ad: row.get(1)?,
-                    created_at: row.get(2)?,
+                    parent_id: row.get(2)?,
+                    olcu_qrupu_id: row.get(3)?,
+                    created_at: row.get(4)?,
                 })
             },
         )
@@ -36,11 +84,13 @@ pub async fn kateqoriya_elave_et(
 }
 
 #[tauri::command]
-pub async fn kateqoriya_siyahisi(state: State<'_, AppState>) -> Result<Vec<Category>, String> {
+pub async fn kateqoriya_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Category>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
     let mut stmt = db.conn
-        .prepare("SELECT id, ad, created_at FROM categories ORDER BY ad")
+        .prepare("SELECT id, ad, parent_id, olcu_qrupu_id, created_at FROM categories ORDER BY ad")
         .map_err(|e| e.to_string())?;
     
     let categories = stmt
@@ -48,7 +98,9 @@ pub async fn kateqoriya_siyahisi(state: State<'_, AppState>) -> Result<Vec<Categ
             Ok(Category {
                 id: row.get(0)?,
                 ad: row.get(1)?,
-                created_at: row.get(2)?,
+                parent_id: row.get(2)?,
+                olcu_qrupu_id: row.get(3)?,
+                created_at: row.get(4)?,
             })
         })
         .map_err(|e| e.to_string())?
@@ -61,27 +113,47 @@ pub async fn kateqoriya_siyahisi(state: State<'_, AppState>) -> Result<Vec<Categ
 #[tauri::command]
 pub async fn kateqoriya_yenile(
     state: State<'_, AppState>,
+    token: String,
     id: i64,
     ad: String,
 ) -> Result<Category, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "categories", id)?;
 
-    db.conn
+    tx
         .execute(
             "UPDATE categories SET ad = ?1 WHERE id = ?2",
             rusqlite::params![&ad, id],
         )
         .map_err(|e| format!("Kateqoriya yenilənə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kateqoriya_yenile",
+        "categories",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "categories", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     let category = db.conn
         .query_row(
-            "SELECT id, ad, created_at FROM categories WHERE id = ?1",
+            "SELECT id, ad, parent_id, olcu_qrupu_id, created_at FROM categories WHERE id = ?1",
             [id],
             |row| {
                 Ok(Category {
                     id: row.get(0)?,
                     ad: row.get(1)?,
-                    created_at: row.get(2)?,
+                    parent_id: row.get(2)?,
+                    olcu_qrupu_id: row.get(3)?,
+                    created_at: row.get(4)?,
                 })
             },
         )
@@ -90,13 +162,248 @@ pub async fn kateqoriya_yenile(
     Ok(category)
 }
 
+/// Which size scale the category's goods use; `None` offers every size.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kateqoriya_olcu_qrupu_teyin_et(
+    state: State<'_, AppState>,
+    token: String,
+    id: i64,
+    olcu_qrupu_id: Option<i64>,
+) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "categories", id)?;
+    let kenar_stok = stock::qrupdan_kenar_stok(&tx, id)?;
+
+    tx
+        .execute(
+            "UPDATE categories SET olcu_qrupu_id = ?1 WHERE id = ?2",
+            rusqlite::params![olcu_qrupu_id, id],
+        )
+        .map_err(|e| format!("Kateqoriya yenilənə bilmədi: {}", e))?;
+    stock::qrupdan_kenar_stok_yoxla(&tx, id, &kenar_stok)?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kateqoriya_olcu_qrupu_teyin_et",
+        "categories",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "categories", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Moves a category, with everything under it, below another one; `None`
+/// makes it a top-level category.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kateqoriya_kocur(
+    state: State<'_, AppState>,
+    token: String,
+    id: i64,
+    parent_id: Option<i64>,
+) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    if let Some(parent_id) = parent_id {
+        if alt_agacdadir(&tx, id, parent_id)? {
+            return Err("Kateqoriya öz altına köçürülə bilməz".to_string());
+        }
+    }
+
+    let evvel = audit::setir(&tx, "categories", id)?;
+    // Under a new parent the category may inherit a different size group
+    let kenar_stok = stock::qrupdan_kenar_stok(&tx, id)?;
+
+    tx
+        .execute(
+            "UPDATE categories SET parent_id = ?1 WHERE id = ?2",
+            rusqlite::params![parent_id, id],
+        )
+        .map_err(|e| format!("Kateqoriya köçürülə bilmədi: {}", e))?;
+    stock::qrupdan_kenar_stok_yoxla(&tx, id, &kenar_stok)?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kateqoriya_kocur",
+        "categories",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "categories", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Folds one category into another: its products and sub-categories move to
+/// the target and the source is deleted.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kateqoriya_birlesdir(
+    state: State<'_, AppState>,
+    token: String,
+    menbe_id: i64,
+    hedef_id: i64,
+) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    if alt_agacdadir(&tx, menbe_id, hedef_id)? {
+        return Err("Kateqoriya özünə və ya öz alt kateqoriyasına birləşdirilə bilməz".to_string());
+    }
+
+    let evvel = audit::setir(&tx, "categories", menbe_id)?;
+    if evvel.is_none() {
+        return Err("Kateqoriya tapılmadı".to_string());
+    }
+
+    let idler = |sql: &str| -> Result<Vec<i64>, String> {
+        tx.prepare(sql)
+            .map_err(|e| e.to_string())?
+            .query_map([menbe_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())
+    };
+    let mehsul_idler = idler("SELECT id FROM products WHERE kateqoriya_id = ?1")?;
+    let alt_idler = idler("SELECT id FROM categories WHERE parent_id = ?1")?;
+    let kampaniya_idler = idler("SELECT id FROM promotions WHERE kateqoriya_id = ?1")?;
+    // What moves over takes the target's size group
+    let mut kenar_stok = stock::qrupdan_kenar_stok(&tx, menbe_id)?;
+    kenar_stok.extend(stock::qrupdan_kenar_stok(&tx, hedef_id)?);
+
+    tx.execute(
+        "UPDATE products SET kateqoriya_id = ?1, updated_at = CURRENT_TIMESTAMP WHERE kateqoriya_id = ?2",
+        [hedef_id, menbe_id],
+    )
+    .map_err(|e| format!("Kateqoriyalar birləşdirilə bilmədi: {}", e))?;
+    tx.execute(
+        "UPDATE categories SET parent_id = ?1 WHERE parent_id = ?2",
+        [hedef_id, menbe_id],
+    )
+    .map_err(|e| format!("Kateqoriyalar birləşdirilə bilmədi: {}", e))?;
+    tx.execute(
+        "UPDATE promotions SET kateqoriya_id = ?1 WHERE kateqoriya_id = ?2",
+        [hedef_id, menbe_id],
+    )
+    .map_err(|e| format!("Kateqoriyalar birləşdirilə bilmədi: {}", e))?;
+    tx.execute("DELETE FROM categories WHERE id = ?1", [menbe_id])
+        .map_err(|e| format!("Kateqoriyalar birləşdirilə bilmədi: {}", e))?;
+    stock::qrupdan_kenar_stok_yoxla(&tx, hedef_id, &kenar_stok)?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kateqoriya_birlesdir",
+        "categories",
+        Some(menbe_id),
+        evvel,
+        Some(serde_json::json!({
+            "hedef_id": hedef_id,
+            "mehsul_idler": mehsul_idler,
+            "alt_kateqoriya_idler": alt_idler,
+            "kampaniya_idler": kampaniya_idler,
+        })),
+    )?;
+
+    tx.commit().map_err(|e| format!("Kateqoriyalar birləşdirilə bilmədi: {}", e))?;
+
+    Ok(())
+}
+
 #[tauri::command]
-pub async fn kateqoriya_sil(state: State<'_, AppState>, id: i64) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub async fn kateqoriya_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "categories", id)?;
 
-    db.conn
+    promotions::aid_kampaniya_yoxla(&tx, "kateqoriya_id", id)?;
+
+    tx
         .execute("DELETE FROM categories WHERE id = ?1", [id])
         .map_err(|e| format!("Kateqoriya silinə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kateqoriya_sil",
+        "categories",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::test_bazasi;
+
+    // Geyim > Kişi > Köynək, and a separate Ayaqqabı
+    fn agac(conn: &Connection) {
+        conn.execute_batch(
+            "INSERT INTO categories (id, ad, parent_id) VALUES
+                (100, 'Geyim', NULL), (101, 'Kişi', 100), (102, 'Köynək', 101), (103, 'Ayaqqabı', NULL);",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn alt_agac_ozunu_ve_asagini_tapir() {
+        let conn = test_bazasi();
+        agac(&conn);
+
+        assert!(alt_agacdadir(&conn, 100, 100).unwrap());
+        assert!(alt_agacdadir(&conn, 100, 101).unwrap());
+        assert!(alt_agacdadir(&conn, 100, 102).unwrap());
+        assert!(!alt_agacdadir(&conn, 102, 100).unwrap());
+        assert!(!alt_agacdadir(&conn, 100, 103).unwrap());
+    }
+
+    #[test]
+    fn qrup_deyisende_kenarda_qalan_stok_tapilir() {
+        let conn = test_bazasi();
+        agac(&conn);
+        conn.execute_batch(
+            "INSERT INTO size_groups (id, ad) VALUES (100, 'Hərf'), (101, 'Ayaqqabı');
+             INSERT INTO sizes (id, olcu, qrup_id) VALUES (100, 'M', 100), (101, '42', 101);
+             INSERT INTO products (id, barkod, ad, kateqoriya_id, alis_qiymeti, satis_qiymeti)
+                VALUES (100, 'KG-1', 'Köynək', 102, 10, 20);
+             INSERT INTO stock (id, mehsul_id, olcu_id, miqdar) VALUES (100, 100, 100, 3), (101, 100, 101, 0);",
+        )
+        .unwrap();
+
+        // Only the non-empty row counts, and only once a group applies
+        let evvel = stock::qrupdan_kenar_stok(&conn, 100).unwrap();
+        assert!(evvel.is_empty());
+
+        conn.execute("UPDATE categories SET olcu_qrupu_id = 101 WHERE id = 100", []).unwrap();
+        let xeta = stock::qrupdan_kenar_stok_yoxla(&conn, 100, &evvel).unwrap_err();
+        assert!(xeta.contains("Köynək (M): 3"), "{}", xeta);
+
+        // A closer group on the sub-category wins
+        conn.execute("UPDATE categories SET olcu_qrupu_id = 100 WHERE id = 101", []).unwrap();
+        stock::qrupdan_kenar_stok_yoxla(&conn, 100, &evvel).unwrap();
+    }
+}
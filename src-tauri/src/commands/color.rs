@@ -1,3 +1,4 @@
+use crate::audit;
 use crate::models::{Color, CreateColor};
 use crate::AppState;
 use tauri::State;
@@ -5,18 +6,34 @@ use tauri::State;
 #[tauri::command]
 pub async fn reng_elave_et(
     state: State<'_, AppState>,
+    token: String,
     reng: CreateColor,
 ) -> Result<Color, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
 
-    db.conn
+    tx
         .execute(
             "INSERT INTO colors (ad, kod) VALUES (?1, ?2)",
             rusqlite::params![&reng.ad, &reng.kod],
         )
         .map_err(|e| format!("Rəng əlavə edilə bilmədi: {}", e))?;
 
-    let id = db.conn.last_insert_rowid();
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "reng_elave_et",
+        "colors",
+        Some(id),
+        None,
+        audit::setir(&tx, "colors", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
 
     let color = db.conn
         .query_row(
@@ -37,7 +54,9 @@ pub async fn reng_elave_et(
 }
 
 #[tauri::command]
-pub async fn reng_siyahisi(state: State<'_, AppState>) -> Result<Vec<Color>, String> {
+pub async fn reng_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Color>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = db.conn
@@ -63,19 +82,37 @@ pub async fn reng_siyahisi(state: State<'_, AppState>) -> Result<Vec<Color>, Str
 #[tauri::command]
 pub async fn reng_yenile(
     state: State<'_, AppState>,
+    token: String,
     id: i64,
     ad: String,
     kod: Option<String>,
 ) -> Result<Color, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
 
-    db.conn
+    let evvel = audit::setir(&tx, "colors", id)?;
+
+    tx
         .execute(
             "UPDATE colors SET ad = ?1, kod = ?2 WHERE id = ?3",
             rusqlite::params![&ad, &kod, id],
         )
         .map_err(|e| format!("Rəng yenilənə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "reng_yenile",
+        "colors",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "colors", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     let color = db.conn
         .query_row(
             "SELECT id, ad, kod, created_at FROM colors WHERE id = ?1",
@@ -95,12 +132,29 @@ pub async fn reng_yenile(
 }
 
 #[tauri::command]
-pub async fn reng_sil(state: State<'_, AppState>, id: i64) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub async fn reng_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
 
-    db.conn
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "colors", id)?;
+
+    tx
         .execute("DELETE FROM colors WHERE id = ?1", [id])
         .map_err(|e| format!("Rəng silinə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "reng_sil",
+        "colors",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     Ok(())
 }
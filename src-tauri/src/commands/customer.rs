@@ -1,3 +1,4 @@
+use crate::audit;
 use crate::models::{CreateCustomer, Customer, UpdateCustomer};
 use crate::AppState;
 use tauri::State;
@@ -5,11 +6,15 @@ use tauri::State;
 #[tauri::command]
 pub async fn musteri_elave_et(
     state: State<'_, AppState>,
+    token: String,
     musteri: CreateCustomer,
 ) -> Result<Customer, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
 
-    db.conn
+    tx
         .execute(
             "INSERT INTO customers (ad, soyad, telefon, qeyd, baslangic_borcu) VALUES (?1, ?2, ?3, ?4, ?5)",
             rusqlite::params![
@@ -28,7 +33,19 @@ pub async fn musteri_elave_et(
             }
         })?;
 
-    let id = db.conn.last_insert_rowid();
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "musteri_elave_et",
+        "customers",
+        Some(id),
+        None,
+        audit::setir(&tx, "customers", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
 
     let customer = db
         .conn
@@ -50,7 +67,9 @@ pub async fn musteri_elave_et(
 }
 
 #[tauri::command]
-pub async fn musteri_siyahisi(state: State<'_, AppState>) -> Result<Vec<Customer>, String> {
+pub async fn musteri_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Customer>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = db
@@ -81,8 +100,11 @@ pub async fn musteri_siyahisi(state: State<'_, AppState>) -> Result<Vec<Customer
 #[tauri::command(rename_all = "camelCase")]
 pub async fn musteri_axtar(
     state: State<'_, AppState>,
+    token: String,
     axtaris: String,
 ) -> Result<Vec<Customer>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let search_term = format!("%{}%", axtaris);
@@ -120,10 +142,16 @@ pub async fn musteri_axtar(
 #[tauri::command(rename_all = "camelCase")]
 pub async fn musteri_yenile(
     state: State<'_, AppState>,
+    token: String,
     id: i64,
     musteri: UpdateCustomer,
 ) -> Result<Customer, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "customers", id)?;
 
     let mut query = String::from("UPDATE customers SET updated_at = CURRENT_TIMESTAMP");
 
@@ -142,10 +170,22 @@ pub async fn musteri_yenile(
 
     query.push_str(&format!(" WHERE id = {}", id));
 
-    db.conn
+    tx
         .execute(&query, [])
         .map_err(|e| format!("Müştəri yenilənə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "musteri_yenile",
+        "customers",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "customers", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     let customer = db
         .conn
         .query_row("SELECT * FROM customers WHERE id = ?1", [id], |row| {
@@ -166,21 +206,41 @@ pub async fn musteri_yenile(
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub async fn musteri_sil(state: State<'_, AppState>, id: i64) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub async fn musteri_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "customers", id)?;
 
-    db.conn
+    tx
         .execute("DELETE FROM customers WHERE id = ?1", [id])
         .map_err(|e| format!("Müştəri silinə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "musteri_sil",
+        "customers",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     Ok(())
 }
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn musteri_nisye_borclari(
     state: State<'_, AppState>,
+    token: String,
     musteri_id: i64,
 ) -> Result<f64, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     // Başlangıç borcu
@@ -193,11 +253,13 @@ pub async fn musteri_nisye_borclari(
         )
         .unwrap_or(0.0);
 
-    // Toplam nisyə satışları
+    // Toplam nisyə satışları (yalnız satışın nisyə hissəsi)
     let toplam_borc: f64 = db
         .conn
         .query_row(
-            "SELECT COALESCE(SUM(son_mebleg), 0) FROM sales WHERE musteri_id = ?1 AND odenis_usulu = 'Nisyə'",
+            "SELECT COALESCE(SUM(sp.mebleg), 0) FROM sale_payments sp
+             JOIN sales s ON sp.satis_id = s.id
+             WHERE s.musteri_id = ?1 AND sp.odenis_usulu = 'Nisyə'",
             [musteri_id],
             |row| row.get(0),
         )
@@ -227,23 +289,29 @@ pub struct CustomerSaleItem {
     pub endirim: f64,
     pub son_mebleg: f64,
     pub odenis_usulu: String,
+    pub nisye_mebleg: f64,
     pub created_at: String,
 }
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn musteri_satis_kecmisi(
     state: State<'_, AppState>,
+    token: String,
     musteri_id: i64,
 ) -> Result<Vec<CustomerSaleItem>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = db
         .conn
         .prepare(
-            "SELECT id, satis_nomresi, toplam_mebleg, endirim, son_mebleg, odenis_usulu, created_at
-             FROM sales
-             WHERE musteri_id = ?1
-             ORDER BY created_at DESC",
+            "SELECT s.id, s.satis_nomresi, s.toplam_mebleg, s.endirim, s.son_mebleg, s.odenis_usulu, s.created_at,
+                    COALESCE((SELECT SUM(mebleg) FROM sale_payments
+                              WHERE satis_id = s.id AND odenis_usulu = 'Nisyə'), 0)
+             FROM sales s
+             WHERE s.musteri_id = ?1
+             ORDER BY s.created_at DESC",
         )
         .map_err(|e| e.to_string())?;
 
@@ -257,6 +325,7 @@ pub async fn musteri_satis_kecmisi(
                 son_mebleg: row.get(4)?,
                 odenis_usulu: row.get(5)?,
                 created_at: row.get(6)?,
+                nisye_mebleg: row.get(7)?,
             })
         })
         .map_err(|e| e.to_string())?
@@ -1,57 +1,157 @@
+use crate::audit;
+use crate::auth::password::sifre_yoxla;
+use crate::db::backup::{self, BackupFile};
+use crate::db::{defaults, migrations};
 use crate::AppState;
+use serde::{Deserialize, Serialize};
 use tauri::State;
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub versiya: i64,
+    pub ad: String,
+    pub tetbiq_tarixi: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchemaVersion {
+    pub cari_versiya: i64,
+    pub son_versiya: i64,
+    pub miqrasiyalar: Vec<AppliedMigration>,
+}
+
+/// Parts of the data a reset can clear. Users, sizes, colours, settings and
+/// the audit log are always kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetScope {
+    /// Sales, returns, payments, shifts, purchase orders and stock history
+    Emeliyyatlar,
+    /// Products, stock, categories and suppliers
+    Kataloq,
+    Musteriler,
+}
+
+impl ResetScope {
+    // Children before parents, so foreign keys hold at every step
+    fn cedveller(self) -> &'static [&'static str] {
+        match self {
+            ResetScope::Emeliyyatlar => &[
+                "return_items",
+                "returns",
+                "sale_payments",
+                "sale_items",
+                "sales",
+                "debt_payments",
+                "supplier_payments",
+                "stock_movements",
+                "purchase_order_items",
+                "purchase_orders",
+                "shifts",
+                "sequences",
+            ],
+            ResetScope::Kataloq => &["promotions", "cost_layers", "variants", "stock", "products", "brands", "categories", "suppliers"],
+            ResetScope::Musteriler => &["customers"],
+        }
+    }
+}
+
 #[tauri::command]
-pub async fn databazi_sifirla(state: State<'_, AppState>) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    
-    // Delete all data except users and sizes
-    db.conn.execute_batch(r#"
-        -- Delete returns first (foreign key)
-        DELETE FROM return_items;
-        DELETE FROM returns;
-        
-        -- Delete sales and related
-        DELETE FROM sale_items;
-        DELETE FROM sales;
-        
-        -- Delete debt payments
-        DELETE FROM debt_payments;
-        
-        -- Delete customers
-        DELETE FROM customers;
-        
-        -- Delete stock and movements
-        DELETE FROM stock_movements;
-        DELETE FROM stock;
-        
-        -- Delete products
-        DELETE FROM products;
-        
-        -- Delete categories
-        DELETE FROM categories;
-        
-        -- Delete settings
-        DELETE FROM settings;
-        
-        -- Reset autoincrement counters
-        DELETE FROM sqlite_sequence WHERE name IN (
-            'returns', 'return_items', 'sale_items', 'sales', 
-            'debt_payments', 'customers', 'stock_movements', 
-            'stock', 'products', 'categories', 'settings'
-        );
-        
-        -- Keep users and sizes
-    "#).map_err(|e| format!("Database sıfırlama xətası: {}", e))?;
-    
-    // Re-seed default categories
-    let categories = ["Köynək", "Şalvar", "Pencək", "Jilet", "Kurtka", "Palto", "Kostyum", "Aksesuar"];
-    for category in categories {
-        db.conn.execute(
-            "INSERT INTO categories (ad) VALUES (?1)",
-            [category],
-        ).map_err(|e| e.to_string())?;
+pub async fn databazi_sifirla(
+    state: State<'_, AppState>,
+    token: String,
+    sifre: String,
+    hisseler: Vec<ResetScope>,
+) -> Result<BackupFile, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    if hisseler.is_empty() {
+        return Err("Sıfırlanacaq bölmə seçilməyib".to_string());
     }
-    
-    Ok(())
+
+    // Sales point at products and customers, so those go only together with them
+    let emeliyyatlar = hisseler.contains(&ResetScope::Emeliyyatlar);
+    if !emeliyyatlar && (hisseler.contains(&ResetScope::Kataloq) || hisseler.contains(&ResetScope::Musteriler)) {
+        return Err("Kataloq və müştərilər yalnız əməliyyatlarla birlikdə sıfırlana bilər".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+
+    // The admin confirms with their own password
+    let saxlanilan: String = db
+        .conn
+        .query_row("SELECT sifre FROM users WHERE id = ?1", [session.istifadeci_id], |row| row.get(0))
+        .map_err(|_| "İstifadəçi tapılmadı".to_string())?;
+
+    if !sifre_yoxla(&sifre, &saxlanilan) {
+        return Err("Şifrə yanlışdır".to_string());
+    }
+
+    let yol = backup::snapshot_yarat(&db.conn, backup::SIFIRLAMA_ONCESI)?;
+    let ehtiyat = backup::fayl_melumati(&yol)?;
+
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    // Fixed order regardless of how the caller listed them
+    for hisse in [ResetScope::Emeliyyatlar, ResetScope::Kataloq, ResetScope::Musteriler] {
+        if !hisseler.contains(&hisse) {
+            continue;
+        }
+        for cedvel in hisse.cedveller() {
+            tx.execute(&format!("DELETE FROM {}", cedvel), [])
+                .map_err(|e| format!("Database sıfırlama xətası ({}): {}", cedvel, e))?;
+            tx.execute("DELETE FROM sqlite_sequence WHERE name = ?1", [cedvel])
+                .map_err(|e| format!("Database sıfırlama xətası ({}): {}", cedvel, e))?;
+        }
+    }
+
+    if hisseler.contains(&ResetScope::Kataloq) {
+        defaults::kateqoriyalar(&tx).map_err(|e| e.to_string())?;
+    }
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "databazi_sifirla",
+        "database",
+        None,
+        None,
+        Some(serde_json::json!({ "hisseler": hisseler, "ehtiyat": ehtiyat.ad })),
+    )?;
+
+    tx.commit().map_err(|e| format!("Database sıfırlama xətası: {}", e))?;
+
+    Ok(ehtiyat)
+}
+
+#[tauri::command]
+pub async fn sxem_versiyasi(state: State<'_, AppState>, token: String) -> Result<SchemaVersion, String> {
+    state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let cari_versiya = db.schema_versiyasi().map_err(|e| e.to_string())?;
+
+    let mut stmt = db
+        .conn
+        .prepare("SELECT versiya, ad, tetbiq_tarixi FROM schema_migrations ORDER BY versiya")
+        .map_err(|e| e.to_string())?;
+
+    let miqrasiyalar = stmt
+        .query_map([], |row| {
+            Ok(AppliedMigration {
+                versiya: row.get(0)?,
+                ad: row.get(1)?,
+                tetbiq_tarixi: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(SchemaVersion {
+        cari_versiya,
+        son_versiya: migrations::son_versiya(),
+        miqrasiyalar,
+    })
 }
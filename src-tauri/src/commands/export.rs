@@ -0,0 +1,127 @@
+use crate::commands::{report, shift};
+use crate::export::{ExportFormat, Senet};
+use crate::AppState;
+use serde::Deserialize;
+use std::path::Path;
+use tauri::State;
+
+/// Which report to export, with the same filters its own command takes.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "hesabat", rename_all = "snake_case")]
+pub enum ExportRequest {
+    GunlukSatis {
+        tarix: Option<String>,
+    },
+    AyliqSatis {
+        ay: Option<String>,
+    },
+    AzQalanStok,
+    SatisSiyahisi {
+        baslangic_tarix: Option<String>,
+        bitis_tarix: Option<String>,
+    },
+    Qazanc {
+        baslangic_tarix: Option<String>,
+        bitis_tarix: Option<String>,
+        kateqoriya_id: Option<i64>,
+    },
+    StokDeyeri {
+        kateqoriya_id: Option<i64>,
+    },
+    MehsulStatistikasi {
+        baslangic_tarix: Option<String>,
+        bitis_tarix: Option<String>,
+        kateqoriya_id: Option<i64>,
+    },
+    Marka {
+        baslangic_tarix: Option<String>,
+        bitis_tarix: Option<String>,
+    },
+    Kampaniya {
+        baslangic_tarix: Option<String>,
+        bitis_tarix: Option<String>,
+    },
+    MehsulHereketleri {
+        mehsul_id: i64,
+        baslangic_tarix: Option<String>,
+        bitis_tarix: Option<String>,
+    },
+    ZHesabati {
+        novbe_id: i64,
+    },
+}
+
+/// Runs the report through its own command, so the same permission checks
+/// apply, and writes it to `fayl_yolu`. Returns the path actually written.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn hesabat_ixrac_et(
+    state: State<'_, AppState>,
+    token: String,
+    sorgu: ExportRequest,
+    format: ExportFormat,
+    fayl_yolu: String,
+) -> Result<String, String> {
+    let (basliq, melumat) = match sorgu {
+        ExportRequest::GunlukSatis { tarix } => (
+            "Günlük satış hesabatı",
+            serde_json::to_value(report::gunluk_satis_hesabati(state.clone(), token, tarix).await?),
+        ),
+        ExportRequest::AyliqSatis { ay } => (
+            "Aylıq satış hesabatı",
+            serde_json::to_value(report::ayliq_satis_hesabati(state.clone(), token, ay).await?),
+        ),
+        ExportRequest::AzQalanStok => (
+            "Az qalan stok",
+            serde_json::to_value(report::stok_hesabati(state.clone(), token).await?),
+        ),
+        ExportRequest::SatisSiyahisi { baslangic_tarix, bitis_tarix } => (
+            "Satış siyahısı",
+            serde_json::to_value(
+                report::satis_siyahisi_tarixe_gore(state.clone(), token, baslangic_tarix, bitis_tarix).await?,
+            ),
+        ),
+        ExportRequest::Qazanc { baslangic_tarix, bitis_tarix, kateqoriya_id } => (
+            "Qazanc hesabatı",
+            serde_json::to_value(
+                report::qazanc_hesabati(state.clone(), token, baslangic_tarix, bitis_tarix, kateqoriya_id).await?,
+            ),
+        ),
+        ExportRequest::StokDeyeri { kateqoriya_id } => (
+            "Stok dəyəri hesabatı",
+            serde_json::to_value(report::stok_deyeri_hesabati(state.clone(), token, kateqoriya_id).await?),
+        ),
+        ExportRequest::MehsulStatistikasi { baslangic_tarix, bitis_tarix, kateqoriya_id } => (
+            "Məhsul statistikası",
+            serde_json::to_value(
+                report::mehsul_statistikasi(state.clone(), token, baslangic_tarix, bitis_tarix, kateqoriya_id).await?,
+            ),
+        ),
+        ExportRequest::Marka { baslangic_tarix, bitis_tarix } => (
+            "Marka hesabatı",
+            serde_json::to_value(report::marka_hesabati(state.clone(), token, baslangic_tarix, bitis_tarix).await?),
+        ),
+        ExportRequest::Kampaniya { baslangic_tarix, bitis_tarix } => (
+            "Kampaniya hesabatı",
+            serde_json::to_value(report::kampaniya_hesabati(state.clone(), token, baslangic_tarix, bitis_tarix).await?),
+        ),
+        ExportRequest::MehsulHereketleri { mehsul_id, baslangic_tarix, bitis_tarix } => (
+            "Məhsul hərəkətləri",
+            serde_json::to_value(
+                report::mehsul_hereketleri(state.clone(), token, mehsul_id, baslangic_tarix, bitis_tarix).await?,
+            ),
+        ),
+        ExportRequest::ZHesabati { novbe_id } => (
+            "Z-hesabatı",
+            serde_json::to_value(shift::novbe_z_hesabati(state.clone(), token, novbe_id).await?),
+        ),
+    };
+    let melumat = melumat.map_err(|e| e.to_string())?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let senet = Senet::hazirla(&db.conn, basliq, &melumat)?;
+    drop(db);
+
+    let yol = senet.yaz(format, Path::new(&fayl_yolu))?;
+
+    Ok(yol.to_string_lossy().into_owned())
+}
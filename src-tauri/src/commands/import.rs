@@ -0,0 +1,49 @@
+use crate::import::{self, products::ImportReport};
+use crate::AppState;
+use std::path::Path;
+use tauri::State;
+
+/// Dry run: validates the file and shows what importing it would change.
+#[tauri::command]
+pub async fn mehsul_idxal_yoxla(
+    state: State<'_, AppState>,
+    token: String,
+    fayl_yolu: String,
+) -> Result<ImportReport, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let cedvel = import::oxu(Path::new(&fayl_yolu))?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let plan = import::products::planla(&db.conn, &cedvel)?;
+
+    Ok(plan.hesabat(false))
+}
+
+/// Imports the file in one transaction. Nothing is written if any row has an
+/// error; the report lists them instead.
+#[tauri::command]
+pub async fn mehsul_idxal_et(
+    state: State<'_, AppState>,
+    token: String,
+    fayl_yolu: String,
+) -> Result<ImportReport, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let cedvel = import::oxu(Path::new(&fayl_yolu))?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    // Planned again inside the transaction so the preview cannot go stale
+    let plan = import::products::planla(&tx, &cedvel)?;
+    if !plan.xetasizdir() {
+        return Ok(plan.hesabat(false));
+    }
+
+    plan.tetbiq_et(&tx, &session)?;
+
+    tx.commit().map_err(|e| format!("İdxal yadda saxlanıla bilmədi: {}", e))?;
+
+    Ok(plan.hesabat(true))
+}
@@ -1,7 +1,10 @@
 pub mod product;
 pub mod category;
 pub mod size;
+pub mod size_group;
 pub mod color;
+pub mod brand;
+pub mod promotion;
 pub mod stock;
 pub mod sale;
 pub mod report;
@@ -11,3 +14,13 @@ pub mod returns;
 pub mod settings;
 pub mod user;
 pub mod database;
+pub mod supplier;
+pub mod purchase_order;
+pub mod supplier_payment;
+pub mod shift;
+pub mod audit_log;
+pub mod backup;
+pub mod import;
+pub mod export;
+pub mod barcode;
+pub mod variant;
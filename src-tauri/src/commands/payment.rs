@@ -1,3 +1,6 @@
+use crate::audit;
+use crate::commands::sale::odenis_usulu_yoxla;
+use crate::commands::shift::aktiv_novbe_id;
 use crate::AppState;
 use serde::{Deserialize, Serialize};
 use tauri::State;
@@ -34,23 +37,59 @@ pub struct CustomerDebtSummary {
 #[tauri::command]
 pub async fn borc_odeme_yarat(
     state: State<'_, AppState>,
+    token: String,
     odeme: CreateDebtPayment,
 ) -> Result<DebtPayment, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.yoxla(&token)?;
+
+    if odeme.mebleg <= 0.0 {
+        return Err("Ödəniş məbləği müsbət olmalıdır".to_string());
+    }
+    odenis_usulu_yoxla(&odeme.odenis_usulu)?;
+    // Paying a debt on credit would only move it around
+    if odeme.odenis_usulu == "Nisyə" {
+        return Err("Borc nisyə ilə ödənilə bilməz".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
 
-    db.conn
+    let novbe_id = aktiv_novbe_id(&tx, session.istifadeci_id)?;
+
+    tx
         .execute(
-            "INSERT INTO debt_payments (musteri_id, mebleg, odenis_usulu, qeyd) VALUES (?1, ?2, ?3, ?4)",
-            rusqlite::params![odeme.musteri_id, odeme.mebleg, odeme.odenis_usulu, odeme.qeyd],
+            "INSERT INTO debt_payments (musteri_id, mebleg, odenis_usulu, qeyd, novbe_id, istifadeci_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                odeme.musteri_id,
+                odeme.mebleg,
+                odeme.odenis_usulu,
+                odeme.qeyd,
+                novbe_id,
+                session.istifadeci_id,
+            ],
         )
         .map_err(|e| format!("Ödəmə yaradıla bilmədi: {}", e))?;
 
-    let id = db.conn.last_insert_rowid();
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "borc_odeme_yarat",
+        "debt_payments",
+        Some(id),
+        None,
+        audit::setir(&tx, "debt_payments", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
 
     let payment = db
         .conn
         .query_row(
-            "SELECT dp.*, c.ad || ' ' || c.soyad as musteri_adi
+            "SELECT dp.id, dp.musteri_id, dp.mebleg, dp.odenis_usulu, dp.qeyd, dp.created_at,
+                    c.ad || ' ' || c.soyad as musteri_adi
              FROM debt_payments dp
              JOIN customers c ON dp.musteri_id = c.id
              WHERE dp.id = ?1",
@@ -73,13 +112,16 @@ pub async fn borc_odeme_yarat(
 }
 
 #[tauri::command]
-pub async fn borc_odeme_siyahisi(state: State<'_, AppState>) -> Result<Vec<DebtPayment>, String> {
+pub async fn borc_odeme_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<DebtPayment>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = db
         .conn
         .prepare(
-            "SELECT dp.*, c.ad || ' ' || c.soyad as musteri_adi
+            "SELECT dp.id, dp.musteri_id, dp.mebleg, dp.odenis_usulu, dp.qeyd, dp.created_at,
+                    c.ad || ' ' || c.soyad as musteri_adi
              FROM debt_payments dp
              JOIN customers c ON dp.musteri_id = c.id
              ORDER BY dp.created_at DESC",
@@ -108,7 +150,10 @@ pub async fn borc_odeme_siyahisi(state: State<'_, AppState>) -> Result<Vec<DebtP
 #[tauri::command]
 pub async fn musteri_borc_xulasesi(
     state: State<'_, AppState>,
+    token: String,
 ) -> Result<Vec<CustomerDebtSummary>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = db
@@ -118,13 +163,13 @@ pub async fn musteri_borc_xulasesi(
                 c.id as musteri_id,
                 c.ad || ' ' || c.soyad as musteri_adi,
                 c.telefon,
-                COALESCE(c.baslangic_borcu, 0) + COALESCE((SELECT SUM(son_mebleg) FROM sales WHERE musteri_id = c.id AND odenis_usulu = 'Nisyə'), 0) as toplam_borc,
+                COALESCE(c.baslangic_borcu, 0) + COALESCE((SELECT SUM(sp.mebleg) FROM sale_payments sp JOIN sales s ON sp.satis_id = s.id WHERE s.musteri_id = c.id AND sp.odenis_usulu = 'Nisyə'), 0) as toplam_borc,
                 COALESCE((SELECT SUM(mebleg) FROM debt_payments WHERE musteri_id = c.id), 0) as toplam_odenis
              FROM customers c
              WHERE (COALESCE(c.baslangic_borcu, 0) > 0)
-                OR EXISTS (SELECT 1 FROM sales WHERE musteri_id = c.id AND odenis_usulu = 'Nisyə')
+                OR EXISTS (SELECT 1 FROM sale_payments sp JOIN sales s ON sp.satis_id = s.id WHERE s.musteri_id = c.id AND sp.odenis_usulu = 'Nisyə')
                 OR EXISTS (SELECT 1 FROM debt_payments WHERE musteri_id = c.id)
-             ORDER BY (COALESCE(c.baslangic_borcu, 0) + COALESCE((SELECT SUM(son_mebleg) FROM sales WHERE musteri_id = c.id AND odenis_usulu = 'Nisyə'), 0) 
+             ORDER BY (COALESCE(c.baslangic_borcu, 0) + COALESCE((SELECT SUM(sp.mebleg) FROM sale_payments sp JOIN sales s ON sp.satis_id = s.id WHERE s.musteri_id = c.id AND sp.odenis_usulu = 'Nisyə'), 0) 
                      - COALESCE((SELECT SUM(mebleg) FROM debt_payments WHERE musteri_id = c.id), 0)) DESC",
         )
         .map_err(|e| e.to_string())?;
@@ -152,14 +197,18 @@ pub async fn musteri_borc_xulasesi(
 #[tauri::command(rename_all = "camelCase")]
 pub async fn musteri_odeme_kecmisi(
     state: State<'_, AppState>,
+    token: String,
     musteri_id: i64,
 ) -> Result<Vec<DebtPayment>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = db
         .conn
         .prepare(
-            "SELECT dp.*, c.ad || ' ' || c.soyad as musteri_adi
+            "SELECT dp.id, dp.musteri_id, dp.mebleg, dp.odenis_usulu, dp.qeyd, dp.created_at,
+                    c.ad || ' ' || c.soyad as musteri_adi
              FROM debt_payments dp
              JOIN customers c ON dp.musteri_id = c.id
              WHERE dp.musteri_id = ?1
@@ -1,25 +1,46 @@
-use crate::models::{CreateProduct, Product, UpdateProduct};
+use crate::audit;
+use crate::auth::session::{Session, ICAZE_XETASI};
+use crate::barcode;
+use crate::commands::{brand, stock, variant};
+use crate::models::{
+    CreateProduct, CreateProductMatrix, CreateStock, Product, ProductWithStock, ScannedProduct, StockInfo,
+    UpdateProduct,
+};
+use crate::promotions;
 use crate::AppState;
+use rusqlite::Connection;
 use tauri::State;
 
-#[tauri::command]
-pub async fn mehsul_elave_et(
-    state: State<'_, AppState>,
-    mehsul: CreateProduct,
-) -> Result<Product, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+/// Inserts a product, giving it an internal barcode if it has none.
+fn mehsul_yarat(
+    conn: &Connection,
+    session: &Session,
+    emr: &str,
+    mehsul: &CreateProduct,
+) -> Result<i64, String> {
+    // Goods without a manufacturer barcode get an internal one
+    let barkod = mehsul.barkod.trim();
+    let barkod = if barkod.is_empty() {
+        barcode::daxili_yarat(conn)?
+    } else {
+        barcode::yoxla(barkod)?;
+        barkod.to_string()
+    };
+
+    let (marka_id, marka) = brand::marka_hell_et(conn, session, emr, mehsul.marka_id, mehsul.marka.as_deref())?;
     
     // Insert product
-    db.conn
+    conn
         .execute(
-            "INSERT INTO products (barkod, ad, kateqoriya_id, reng, marka, alis_qiymeti, satis_qiymeti, tesvir, sekil_yolu)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            "INSERT INTO products (barkod, ad, kateqoriya_id, reng, marka, marka_id, alis_qiymeti, satis_qiymeti, tesvir, sekil_yolu)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             rusqlite::params![
-                mehsul.barkod,
+                barkod,
                 mehsul.ad,
                 mehsul.kateqoriya_id,
                 mehsul.reng,
-                mehsul.marka,
+                marka,
+                marka_id,
                 mehsul.alis_qiymeti,
                 mehsul.satis_qiymeti,
                 mehsul.tesvir,
@@ -28,70 +49,175 @@ pub async fn mehsul_elave_et(
         )
         .map_err(|e| format!("Məhsul əlavə edilə bilmədi: {}", e))?;
     
-    let id = db.conn.last_insert_rowid();
-    
-    // Get the inserted product with category name
-    let product = db.conn
+    let id = conn.last_insert_rowid();
+
+    audit::qeyde_al(
+        conn,
+        session,
+        emr,
+        "products",
+        Some(id),
+        None,
+        audit::setir(conn, "products", id)?,
+    )?;
+
+    Ok(id)
+}
+
+const MEHSUL_SELECT: &str = "SELECT p.id, p.barkod, p.ad, p.kateqoriya_id, p.reng, p.marka, p.alis_qiymeti,
+            p.satis_qiymeti, p.tesvir, p.sekil_yolu, p.created_at, p.updated_at, c.ad, p.marka_id
+     FROM products p
+     LEFT JOIN categories c ON p.kateqoriya_id = c.id";
+
+fn mehsul_setri(row: &rusqlite::Row) -> rusqlite::Result<Product> {
+    Ok(Product {
+        id: row.get(0)?,
+        barkod: row.get(1)?,
+        ad: row.get(2)?,
+        kateqoriya_id: row.get(3)?,
+        reng: row.get(4)?,
+        marka: row.get(5)?,
+        alis_qiymeti: row.get(6)?,
+        satis_qiymeti: row.get(7)?,
+        tesvir: row.get(8)?,
+        sekil_yolu: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+        kateqoriya_adi: row.get(12)?,
+        marka_id: row.get(13)?,
+    })
+}
+
+fn mehsul_oxu(conn: &Connection, id: i64) -> Result<Product, String> {
+    conn
         .query_row(
-            "SELECT p.*, c.ad as kateqoriya_adi 
-             FROM products p 
-             LEFT JOIN categories c ON p.kateqoriya_id = c.id 
-             WHERE p.id = ?1",
+            &format!("{} WHERE p.id = ?1", MEHSUL_SELECT),
             [id],
-            |row| {
-                Ok(Product {
-                    id: row.get(0)?,
-                    barkod: row.get(1)?,
-                    ad: row.get(2)?,
-                    kateqoriya_id: row.get(3)?,
-                    reng: row.get(4)?,
-                    marka: row.get(5)?,
-                    alis_qiymeti: row.get(6)?,
-                    satis_qiymeti: row.get(7)?,
-                    tesvir: row.get(8)?,
-                    sekil_yolu: row.get(9)?,
-                    created_at: row.get(10)?,
-                    updated_at: row.get(11)?,
-                    kateqoriya_adi: row.get(12)?,
-                })
-            },
+            mehsul_setri,
         )
-        .map_err(|e| format!("Məhsul tapılmadı: {}", e))?;
-    
-    Ok(product)
+        .map_err(|e| format!("Məhsul tapılmadı: {}", e))
 }
 
 #[tauri::command]
-pub async fn mehsul_siyahisi(state: State<'_, AppState>) -> Result<Vec<Product>, String> {
+pub async fn mehsul_elave_et(
+    state: State<'_, AppState>,
+    token: String,
+    mehsul: CreateProduct,
+) -> Result<Product, String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let id = mehsul_yarat(&tx, &session, "mehsul_elave_et", &mehsul)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    mehsul_oxu(&db.conn, id)
+}
+
+/// Creates a style together with a stock row for every size and colour, all
+/// in one transaction.
+#[tauri::command]
+pub async fn mehsul_matris_elave_et(
+    state: State<'_, AppState>,
+    token: String,
+    matris: CreateProductMatrix,
+) -> Result<ProductWithStock, String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    let mut olculer: Vec<i64> = Vec::new();
+    for olcu_id in &matris.olcu_idler {
+        if !olculer.contains(olcu_id) {
+            olculer.push(*olcu_id);
+        }
+    }
+    if olculer.is_empty() {
+        return Err("Ən azı bir ölçü seçilməlidir".to_string());
+    }
+
+    // No colours chosen means the style is stocked without one
+    let mut rengler: Vec<Option<i64>> = Vec::new();
+    for reng_id in &matris.reng_idler {
+        if !rengler.contains(&Some(*reng_id)) {
+            rengler.push(Some(*reng_id));
+        }
+    }
+    if rengler.is_empty() {
+        rengler.push(None);
+    }
+
+    for hucre in &matris.hucreler {
+        if !olculer.contains(&hucre.olcu_id) || !rengler.contains(&hucre.reng_id) {
+            return Err("Miqdar seçilməmiş ölçü və ya rəng üçün verilib".to_string());
+        }
+    }
+    if matris.miqdar < 0 || matris.hucreler.iter().any(|h| h.miqdar < 0) {
+        return Err("Miqdar mənfi ola bilməz".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let mehsul_id = mehsul_yarat(&tx, &session, "mehsul_matris_elave_et", &matris.mehsul)?;
+
+    let mut stok = Vec::new();
+    for olcu_id in &olculer {
+        for reng_id in &rengler {
+            let hucre = matris
+                .hucreler
+                .iter()
+                .find(|h| h.olcu_id == *olcu_id && h.reng_id == *reng_id);
+            let (miqdar, minimum_miqdar) = match hucre {
+                Some(h) => (h.miqdar, h.minimum_miqdar.or(matris.minimum_miqdar)),
+                None => (matris.miqdar, matris.minimum_miqdar),
+            };
+
+            let setir = stock::stok_daxil_et(
+                &tx,
+                &session,
+                "mehsul_matris_elave_et",
+                &CreateStock {
+                    mehsul_id,
+                    olcu_id: *olcu_id,
+                    reng_id: *reng_id,
+                    miqdar,
+                    minimum_miqdar,
+                },
+            )?;
+
+            stok.push(StockInfo {
+                olcu_id: setir.olcu_id,
+                olcu: setir.olcu.unwrap_or_default(),
+                reng_id: setir.reng_id,
+                reng: setir.reng,
+                miqdar: setir.miqdar,
+                minimum_miqdar: setir.minimum_miqdar,
+            });
+        }
+    }
+
+    let product = mehsul_oxu(&tx, mehsul_id)?;
+
+    tx.commit().map_err(|e| format!("Məhsul yadda saxlanıla bilmədi: {}", e))?;
+
+    Ok(ProductWithStock { product, stok })
+}
+
+#[tauri::command]
+pub async fn mehsul_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Product>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
     let mut stmt = db.conn
         .prepare(
-            "SELECT p.*, c.ad as kateqoriya_adi 
-             FROM products p 
-             LEFT JOIN categories c ON p.kateqoriya_id = c.id 
-             ORDER BY p.created_at DESC",
+            &format!("{} ORDER BY p.created_at DESC", MEHSUL_SELECT),
         )
         .map_err(|e| e.to_string())?;
     
     let products = stmt
-        .query_map([], |row| {
-            Ok(Product {
-                id: row.get(0)?,
-                barkod: row.get(1)?,
-                ad: row.get(2)?,
-                kateqoriya_id: row.get(3)?,
-                reng: row.get(4)?,
-                marka: row.get(5)?,
-                alis_qiymeti: row.get(6)?,
-                satis_qiymeti: row.get(7)?,
-                tesvir: row.get(8)?,
-                sekil_yolu: row.get(9)?,
-                created_at: row.get(10)?,
-                updated_at: row.get(11)?,
-                kateqoriya_adi: row.get(12)?,
-            })
-        })
+        .query_map([], mehsul_setri)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
@@ -102,40 +228,24 @@ pub async fn mehsul_siyahisi(state: State<'_, AppState>) -> Result<Vec<Product>,
 #[tauri::command]
 pub async fn mehsul_axtar(
     state: State<'_, AppState>,
+    token: String,
     axtaris: String,
 ) -> Result<Vec<Product>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
     let search_term = format!("%{}%", axtaris);
     
     let mut stmt = db.conn
         .prepare(
-            "SELECT p.*, c.ad as kateqoriya_adi 
-             FROM products p 
-             LEFT JOIN categories c ON p.kateqoriya_id = c.id 
-             WHERE p.barkod LIKE ?1 OR p.ad LIKE ?1 OR p.marka LIKE ?1
-             ORDER BY p.ad",
+            &format!("{} WHERE p.barkod LIKE ?1 OR p.ad LIKE ?1 OR p.marka LIKE ?1
+             ORDER BY p.ad", MEHSUL_SELECT),
         )
         .map_err(|e| e.to_string())?;
     
     let products = stmt
-        .query_map([&search_term], |row| {
-            Ok(Product {
-                id: row.get(0)?,
-                barkod: row.get(1)?,
-                ad: row.get(2)?,
-                kateqoriya_id: row.get(3)?,
-                reng: row.get(4)?,
-                marka: row.get(5)?,
-                alis_qiymeti: row.get(6)?,
-                satis_qiymeti: row.get(7)?,
-                tesvir: row.get(8)?,
-                sekil_yolu: row.get(9)?,
-                created_at: row.get(10)?,
-                updated_at: row.get(11)?,
-                kateqoriya_adi: row.get(12)?,
-            })
-        })
+        .query_map([&search_term], mehsul_setri)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
@@ -146,38 +256,26 @@ pub async fn mehsul_axtar(
 #[tauri::command]
 pub async fn mehsul_barkod_ile_axtar(
     state: State<'_, AppState>,
+    token: String,
     barkod: String,
-) -> Result<Option<Product>, String> {
+) -> Result<Option<ScannedProduct>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    // A variant's code leads to its product, with the size already known
+    let variant = variant::barkodla_tap(&db.conn, &barkod)?;
+    let mehsul_id = variant.as_ref().map(|v| v.mehsul_id);
     
     let result = db.conn
         .query_row(
-            "SELECT p.*, c.ad as kateqoriya_adi 
-             FROM products p 
-             LEFT JOIN categories c ON p.kateqoriya_id = c.id 
-             WHERE p.barkod = ?1",
-            [&barkod],
-            |row| {
-                Ok(Product {
-                    id: row.get(0)?,
-                    barkod: row.get(1)?,
-                    ad: row.get(2)?,
-                    kateqoriya_id: row.get(3)?,
-                    reng: row.get(4)?,
-                    marka: row.get(5)?,
-                    alis_qiymeti: row.get(6)?,
-                    satis_qiymeti: row.get(7)?,
-                    tesvir: row.get(8)?,
-                    sekil_yolu: row.get(9)?,
-                    created_at: row.get(10)?,
-                    updated_at: row.get(11)?,
-                    kateqoriya_adi: row.get(12)?,
-                })
-            },
+            &format!("{} WHERE p.id = ?1 OR (?1 IS NULL AND p.barkod = ?2)", MEHSUL_SELECT),
+            rusqlite::params![mehsul_id, barkod],
+            mehsul_setri,
         );
     
     match result {
-        Ok(product) => Ok(Some(product)),
+        Ok(product) => Ok(Some(ScannedProduct { product, variant })),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
         Err(e) => Err(format!("Axtarış xətası: {}", e)),
     }
@@ -186,18 +284,38 @@ pub async fn mehsul_barkod_ile_axtar(
 #[tauri::command]
 pub async fn mehsul_yenile(
     state: State<'_, AppState>,
+    token: String,
     id: i64,
     mehsul: UpdateProduct,
 ) -> Result<Product, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.yoxla(&token)?;
+
+    // Cashiers may edit product details, but not prices
+    if (mehsul.alis_qiymeti.is_some() || mehsul.satis_qiymeti.is_some()) && !session.admin_dir() {
+        return Err(ICAZE_XETASI.to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "products", id)?;
     
     // Build dynamic update query
     let mut updates = Vec::new();
     let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
     
     if let Some(ref barkod) = mehsul.barkod {
+        let barkod = barkod.trim();
+        // Older products may carry codes that predate validation; they are
+        // only checked once someone actually changes them
+        let hazirki: Option<String> = tx
+            .query_row("SELECT barkod FROM products WHERE id = ?1", [id], |row| row.get(0))
+            .ok();
+        if hazirki.as_deref() != Some(barkod) {
+            barcode::yoxla(barkod)?;
+        }
         updates.push("barkod = ?");
-        params.push(Box::new(barkod.clone()));
+        params.push(Box::new(barkod.to_string()));
     }
     if let Some(ref ad) = mehsul.ad {
         updates.push("ad = ?");
@@ -211,9 +329,13 @@ pub async fn mehsul_yenile(
         updates.push("reng = ?");
         params.push(Box::new(reng.clone()));
     }
-    if let Some(ref marka) = mehsul.marka {
+    // The brand is always stored as the brands table spells it
+    if mehsul.marka_id.is_some() || mehsul.marka.is_some() {
+        let (marka_id, marka) = brand::marka_hell_et(&tx, &session, "mehsul_yenile", mehsul.marka_id, mehsul.marka.as_deref())?;
         updates.push("marka = ?");
-        params.push(Box::new(marka.clone()));
+        params.push(Box::new(marka));
+        updates.push("marka_id = ?");
+        params.push(Box::new(marka_id));
     }
     if let Some(alis_qiymeti) = mehsul.alis_qiymeti {
         updates.push("alis_qiymeti = ?");
@@ -243,35 +365,28 @@ pub async fn mehsul_yenile(
     
     let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
     
-    db.conn
+    tx
         .execute(&query, params_refs.as_slice())
         .map_err(|e| format!("Məhsul yenilənə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "mehsul_yenile",
+        "products",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "products", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
     
     // Get updated product
     let product = db.conn
         .query_row(
-            "SELECT p.*, c.ad as kateqoriya_adi 
-             FROM products p 
-             LEFT JOIN categories c ON p.kateqoriya_id = c.id 
-             WHERE p.id = ?1",
+            &format!("{} WHERE p.id = ?1", MEHSUL_SELECT),
             [id],
-            |row| {
-                Ok(Product {
-                    id: row.get(0)?,
-                    barkod: row.get(1)?,
-                    ad: row.get(2)?,
-                    kateqoriya_id: row.get(3)?,
-                    reng: row.get(4)?,
-                    marka: row.get(5)?,
-                    alis_qiymeti: row.get(6)?,
-                    satis_qiymeti: row.get(7)?,
-                    tesvir: row.get(8)?,
-                    sekil_yolu: row.get(9)?,
-                    created_at: row.get(10)?,
-                    updated_at: row.get(11)?,
-                    kateqoriya_adi: row.get(12)?,
-                })
-            },
+            mehsul_setri,
         )
         .map_err(|e| format!("Məhsul tapılmadı: {}", e))?;
     
@@ -279,12 +394,31 @@ pub async fn mehsul_yenile(
 }
 
 #[tauri::command]
-pub async fn mehsul_sil(state: State<'_, AppState>, id: i64) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub async fn mehsul_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "products", id)?;
+
+    promotions::aid_kampaniya_yoxla(&tx, "mehsul_id", id)?;
     
-    db.conn
+    tx
         .execute("DELETE FROM products WHERE id = ?1", [id])
         .map_err(|e| format!("Məhsul silinə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "mehsul_sil",
+        "products",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
     
     Ok(())
 }
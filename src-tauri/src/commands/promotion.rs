@@ -0,0 +1,280 @@
+use crate::audit;
+use crate::commands::report::tarix_yoxla;
+use crate::commands::sale::variantlari_hell_et;
+use crate::models::{CreatePromotion, CreateSaleItem, Promotion, PromotionType, SalePromotions};
+use crate::promotions::{self, kampaniya_setri, KAMPANIYA_SELECT};
+use crate::AppState;
+use rusqlite::{Connection, OptionalExtension};
+use tauri::State;
+
+fn kampaniya_oxu(conn: &Connection, id: i64) -> Result<Promotion, String> {
+    conn.query_row(&format!("{} WHERE id = ?1", KAMPANIYA_SELECT), [id], kampaniya_setri)
+        .map_err(|e| format!("Kampaniya tapılmadı: {}", e))
+}
+
+// Empty dates from the date pickers mean "no limit"
+fn tarix(tarix: &Option<String>) -> Option<String> {
+    tarix.as_deref().map(str::trim).filter(|t| !t.is_empty()).map(str::to_string)
+}
+
+fn kampaniya_hazirla(kampaniya: &mut CreatePromotion) -> Result<(), String> {
+    kampaniya.baslangic_tarixi = tarix(&kampaniya.baslangic_tarixi);
+    kampaniya.bitis_tarixi = tarix(&kampaniya.bitis_tarixi);
+
+    if kampaniya.ad.trim().is_empty() {
+        return Err("Kampaniya adı boş ola bilməz".to_string());
+    }
+
+    match kampaniya.novu {
+        PromotionType::Faiz if kampaniya.deyer <= 0.0 || kampaniya.deyer > 100.0 => {
+            return Err("Endirim faizi 0-dan böyük, 100-dən kiçik və ya bərabər olmalıdır".to_string());
+        }
+        PromotionType::Sabit if kampaniya.deyer <= 0.0 => {
+            return Err("Endirim məbləği müsbət olmalıdır".to_string());
+        }
+        PromotionType::Deste => {
+            if kampaniya.deste_miqdari.unwrap_or(0) < 2 {
+                return Err("Dəstədə ən azı 2 məhsul olmalıdır".to_string());
+            }
+            if kampaniya.deyer < 0.0 {
+                return Err("Dəstə qiyməti mənfi ola bilməz".to_string());
+            }
+        }
+        _ => {}
+    }
+
+    let baslangic = kampaniya.baslangic_tarixi.as_deref().map(tarix_yoxla).transpose()?;
+    let bitis = kampaniya.bitis_tarixi.as_deref().map(tarix_yoxla).transpose()?;
+    if let (Some(baslangic), Some(bitis)) = (baslangic, bitis) {
+        if baslangic > bitis {
+            return Err("Kampaniyanın başlanğıc tarixi bitiş tarixindən sonra ola bilməz".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+// Only the bundle type has a bundle size
+fn deste_miqdari(kampaniya: &CreatePromotion) -> Option<i32> {
+    match kampaniya.novu {
+        PromotionType::Deste => kampaniya.deste_miqdari,
+        _ => None,
+    }
+}
+
+#[tauri::command]
+pub async fn kampaniya_elave_et(
+    state: State<'_, AppState>,
+    token: String,
+    mut kampaniya: CreatePromotion,
+) -> Result<Promotion, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    kampaniya_hazirla(&mut kampaniya)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    tx
+        .execute(
+            "INSERT INTO promotions (ad, novu, deyer, deste_miqdari, mehsul_id, kateqoriya_id, marka_id, olcu_id,
+                                     baslangic_tarixi, bitis_tarixi)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                kampaniya.ad.trim(),
+                kampaniya.novu.as_str(),
+                kampaniya.deyer,
+                deste_miqdari(&kampaniya),
+                kampaniya.mehsul_id,
+                kampaniya.kateqoriya_id,
+                kampaniya.marka_id,
+                kampaniya.olcu_id,
+                kampaniya.baslangic_tarixi,
+                kampaniya.bitis_tarixi,
+            ],
+        )
+        .map_err(|e| format!("Kampaniya əlavə edilə bilmədi: {}", e))?;
+
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kampaniya_elave_et",
+        "promotions",
+        Some(id),
+        None,
+        audit::setir(&tx, "promotions", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    kampaniya_oxu(&db.conn, id)
+}
+
+#[tauri::command]
+pub async fn kampaniya_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Promotion>, String> {
+    state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = db.conn
+        .prepare(&format!("{} ORDER BY aktiv DESC, created_at DESC", KAMPANIYA_SELECT))
+        .map_err(|e| e.to_string())?;
+
+    let kampaniyalar = stmt
+        .query_map([], kampaniya_setri)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(kampaniyalar)
+}
+
+/// Replaces the promotion's rule. Sales already made keep the discount they got.
+#[tauri::command]
+pub async fn kampaniya_yenile(
+    state: State<'_, AppState>,
+    token: String,
+    id: i64,
+    mut kampaniya: CreatePromotion,
+) -> Result<Promotion, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    kampaniya_hazirla(&mut kampaniya)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "promotions", id)?;
+    if evvel.is_none() {
+        return Err("Kampaniya tapılmadı".to_string());
+    }
+
+    tx
+        .execute(
+            "UPDATE promotions
+             SET ad = ?1, novu = ?2, deyer = ?3, deste_miqdari = ?4, mehsul_id = ?5, kateqoriya_id = ?6,
+                 marka_id = ?7, olcu_id = ?8, baslangic_tarixi = ?9, bitis_tarixi = ?10
+             WHERE id = ?11",
+            rusqlite::params![
+                kampaniya.ad.trim(),
+                kampaniya.novu.as_str(),
+                kampaniya.deyer,
+                deste_miqdari(&kampaniya),
+                kampaniya.mehsul_id,
+                kampaniya.kateqoriya_id,
+                kampaniya.marka_id,
+                kampaniya.olcu_id,
+                kampaniya.baslangic_tarixi,
+                kampaniya.bitis_tarixi,
+                id,
+            ],
+        )
+        .map_err(|e| format!("Kampaniya yenilənə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kampaniya_yenile",
+        "promotions",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "promotions", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    kampaniya_oxu(&db.conn, id)
+}
+
+/// Switches a promotion on or off without touching its rule.
+#[tauri::command]
+pub async fn kampaniya_aktiv_et(
+    state: State<'_, AppState>,
+    token: String,
+    id: i64,
+    aktiv: bool,
+) -> Result<Promotion, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "promotions", id)?;
+
+    tx
+        .execute(
+            "UPDATE promotions SET aktiv = ?1 WHERE id = ?2",
+            rusqlite::params![aktiv, id],
+        )
+        .map_err(|e| format!("Kampaniya yenilənə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kampaniya_aktiv_et",
+        "promotions",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "promotions", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    kampaniya_oxu(&db.conn, id)
+}
+
+#[tauri::command]
+pub async fn kampaniya_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "promotions", id)?;
+
+    // Sales point at the promotion they got, so its report stays whole
+    let istifade_olunub: Option<i64> = tx
+        .query_row("SELECT id FROM sale_items WHERE kampaniya_id = ?1 LIMIT 1", [id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if istifade_olunub.is_some() {
+        return Err("Bu kampaniya satışlarda tətbiq olunub, onu silmək əvəzinə dayandırın".to_string());
+    }
+
+    tx
+        .execute("DELETE FROM promotions WHERE id = ?1", [id])
+        .map_err(|e| format!("Kampaniya silinə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "kampaniya_sil",
+        "promotions",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// What the running promotions would take off a cart, line by line, so the
+/// till can show the total before the sale is made.
+#[tauri::command]
+pub async fn kampaniyalari_hesabla(
+    state: State<'_, AppState>,
+    token: String,
+    mut items: Vec<CreateSaleItem>,
+) -> Result<SalePromotions, String> {
+    state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    variantlari_hell_et(&db.conn, &mut items)?;
+
+    promotions::hesabla(&db.conn, &items)
+}
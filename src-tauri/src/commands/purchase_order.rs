@@ -0,0 +1,516 @@
+use crate::audit;
+use crate::commands::sale::odenis_usulu_yoxla;
+use crate::commands::stock;
+use crate::inventory::cost;
+use crate::numbering::{self, Sened};
+use crate::AppState;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+pub const STATUS_ACIQ: &str = "Açıq";
+pub const STATUS_QISMEN: &str = "Qismən qəbul edilib";
+pub const STATUS_QEBUL: &str = "Qəbul edilib";
+pub const STATUS_BAGLI: &str = "Bağlanıb";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PurchaseOrder {
+    pub id: i64,
+    pub sifaris_nomresi: String,
+    pub tedarukcu_id: i64,
+    pub tedarukcu_adi: Option<String>,
+    pub status: String,
+    pub odenis_usulu: String,
+    pub gozlenilen_tarix: Option<String>,
+    pub qeyd: Option<String>,
+    pub toplam_mebleg: f64,
+    pub qebul_mebleg: f64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PurchaseOrderItem {
+    pub id: i64,
+    pub sifaris_id: i64,
+    pub mehsul_id: i64,
+    pub mehsul_adi: Option<String>,
+    pub mehsul_barkod: Option<String>,
+    pub olcu_id: i64,
+    pub olcu: Option<String>,
+    pub reng_id: Option<i64>,
+    pub reng: Option<String>,
+    pub sifaris_miqdari: i32,
+    pub qebul_miqdari: i32,
+    pub vahid_alis_qiymeti: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrderWithItems {
+    #[serde(flatten)]
+    pub sifaris: PurchaseOrder,
+    pub items: Vec<PurchaseOrderItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePurchaseOrder {
+    pub tedarukcu_id: i64,
+    /// 'Nisyə' (default) puts received goods on the supplier's account
+    pub odenis_usulu: Option<String>,
+    pub gozlenilen_tarix: Option<String>,
+    pub qeyd: Option<String>,
+    pub items: Vec<CreatePurchaseOrderItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePurchaseOrderItem {
+    pub mehsul_id: i64,
+    pub olcu_id: i64,
+    pub reng_id: Option<i64>,
+    pub miqdar: i32,
+    pub vahid_alis_qiymeti: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceivePurchaseOrder {
+    pub items: Vec<ReceivePurchaseOrderItem>,
+    pub qeyd: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceivePurchaseOrderItem {
+    /// purchase_order_items.id
+    pub setir_id: i64,
+    pub miqdar: i32,
+    /// Actual invoice price, when it differs from the ordered one
+    pub vahid_alis_qiymeti: Option<f64>,
+}
+
+pub fn sifaris_oxu(conn: &Connection, sifaris_id: i64) -> Result<PurchaseOrderWithItems, String> {
+    let sifaris = conn
+        .query_row(
+            "SELECT po.id, po.sifaris_nomresi, po.tedarukcu_id, t.ad, po.status, COALESCE(po.odenis_usulu, 'Nisyə'), po.gozlenilen_tarix, po.qeyd,
+                    COALESCE((SELECT SUM(sifaris_miqdari * vahid_alis_qiymeti) FROM purchase_order_items
+                              WHERE sifaris_id = po.id), 0),
+                    COALESCE((SELECT SUM(toplam_deyeri) FROM stock_movements
+                              WHERE sifaris_id = po.id), 0),
+                    po.created_at, po.updated_at
+             FROM purchase_orders po
+             LEFT JOIN suppliers t ON po.tedarukcu_id = t.id
+             WHERE po.id = ?1",
+            [sifaris_id],
+            row_to_purchase_order,
+        )
+        .map_err(|e| format!("Sifariş tapılmadı: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT poi.id, poi.sifaris_id, poi.mehsul_id, p.ad, p.barkod, poi.olcu_id, sz.olcu,
+                    poi.sifaris_miqdari, poi.qebul_miqdari, poi.vahid_alis_qiymeti, poi.reng_id, cl.ad
+             FROM purchase_order_items poi
+             JOIN products p ON poi.mehsul_id = p.id
+             JOIN sizes sz ON poi.olcu_id = sz.id
+             LEFT JOIN colors cl ON poi.reng_id = cl.id
+             WHERE poi.sifaris_id = ?1
+             ORDER BY poi.id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let items = stmt
+        .query_map([sifaris_id], |row| {
+            Ok(PurchaseOrderItem {
+                id: row.get(0)?,
+                sifaris_id: row.get(1)?,
+                mehsul_id: row.get(2)?,
+                mehsul_adi: row.get(3)?,
+                mehsul_barkod: row.get(4)?,
+                olcu_id: row.get(5)?,
+                olcu: row.get(6)?,
+                sifaris_miqdari: row.get(7)?,
+                qebul_miqdari: row.get(8)?,
+                vahid_alis_qiymeti: row.get(9)?,
+                reng_id: row.get(10)?,
+                reng: row.get(11)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(PurchaseOrderWithItems { sifaris, items })
+}
+
+fn row_to_purchase_order(row: &rusqlite::Row) -> rusqlite::Result<PurchaseOrder> {
+    Ok(PurchaseOrder {
+        id: row.get(0)?,
+        sifaris_nomresi: row.get(1)?,
+        tedarukcu_id: row.get(2)?,
+        tedarukcu_adi: row.get(3)?,
+        status: row.get(4)?,
+        odenis_usulu: row.get(5)?,
+        gozlenilen_tarix: row.get(6)?,
+        qeyd: row.get(7)?,
+        toplam_mebleg: row.get(8)?,
+        qebul_mebleg: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+    })
+}
+
+#[tauri::command]
+pub async fn sifaris_yarat(
+    state: State<'_, AppState>,
+    token: String,
+    sifaris: CreatePurchaseOrder,
+) -> Result<PurchaseOrderWithItems, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    if sifaris.items.is_empty() {
+        return Err("Sifarişdə heç bir məhsul yoxdur".to_string());
+    }
+    if sifaris.items.iter().any(|item| item.miqdar <= 0) {
+        return Err("Məhsul miqdarı müsbət olmalıdır".to_string());
+    }
+    if sifaris.items.iter().any(|item| item.vahid_alis_qiymeti < 0.0) {
+        return Err("Alış qiyməti mənfi ola bilməz".to_string());
+    }
+    // Only 'Nisyə' orders count towards the supplier's debt, so a misspelt
+    // tender would quietly drop the order out of it
+    let odenis_usulu = sifaris.odenis_usulu.as_deref().map(str::trim).filter(|u| !u.is_empty()).unwrap_or("Nisyə");
+    odenis_usulu_yoxla(odenis_usulu)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let aktiv: Option<bool> = tx
+        .query_row(
+            "SELECT COALESCE(aktiv, 1) FROM suppliers WHERE id = ?1",
+            [sifaris.tedarukcu_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match aktiv {
+        None => return Err("Tədarükçü tapılmadı".to_string()),
+        Some(false) => return Err("Deaktiv tədarükçüyə sifariş verilə bilməz".to_string()),
+        Some(true) => {}
+    }
+
+    let sifaris_nomresi = numbering::novbeti_nomre(&tx, Sened::Sifaris)?;
+
+    tx.execute(
+        "INSERT INTO purchase_orders (sifaris_nomresi, tedarukcu_id, status, odenis_usulu, gozlenilen_tarix, qeyd)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            sifaris_nomresi,
+            sifaris.tedarukcu_id,
+            STATUS_ACIQ,
+            odenis_usulu,
+            sifaris.gozlenilen_tarix,
+            sifaris.qeyd,
+        ],
+    )
+    .map_err(|e| format!("Sifariş yaradıla bilmədi: {}", e))?;
+
+    let sifaris_id = tx.last_insert_rowid();
+
+    for item in &sifaris.items {
+        let (mehsul_var, olcu_var): (bool, bool) = tx
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM products WHERE id = ?1), EXISTS(SELECT 1 FROM sizes WHERE id = ?2)",
+                [item.mehsul_id, item.olcu_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        if !mehsul_var {
+            return Err(format!("Məhsul tapılmadı (id {})", item.mehsul_id));
+        }
+        if !olcu_var {
+            return Err(format!("Ölçü tapılmadı (id {})", item.olcu_id));
+        }
+
+        // Received goods land in stock, so they follow the same size rule
+        stock::olcu_yoxla(&tx, item.mehsul_id, item.olcu_id)?;
+
+        tx.execute(
+            "INSERT INTO purchase_order_items (sifaris_id, mehsul_id, olcu_id, reng_id, sifaris_miqdari, vahid_alis_qiymeti)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                sifaris_id,
+                item.mehsul_id,
+                item.olcu_id,
+                item.reng_id,
+                item.miqdar,
+                item.vahid_alis_qiymeti,
+            ],
+        )
+        .map_err(|e| format!("Sifariş elementi əlavə edilə bilmədi: {}", e))?;
+    }
+
+    let result = sifaris_oxu(&tx, sifaris_id)?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "sifaris_yarat",
+        "purchase_orders",
+        Some(sifaris_id),
+        None,
+        serde_json::to_value(&result).ok(),
+    )?;
+
+    tx.commit().map_err(|e| format!("Sifariş yadda saxlanıla bilmədi: {}", e))?;
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn sifaris_siyahisi(
+    state: State<'_, AppState>,
+    token: String,
+    status: Option<String>,
+) -> Result<Vec<PurchaseOrder>, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = db
+        .conn
+        .prepare(
+            "SELECT po.id, po.sifaris_nomresi, po.tedarukcu_id, t.ad, po.status, COALESCE(po.odenis_usulu, 'Nisyə'), po.gozlenilen_tarix, po.qeyd,
+                    COALESCE((SELECT SUM(sifaris_miqdari * vahid_alis_qiymeti) FROM purchase_order_items
+                              WHERE sifaris_id = po.id), 0),
+                    COALESCE((SELECT SUM(toplam_deyeri) FROM stock_movements
+                              WHERE sifaris_id = po.id), 0),
+                    po.created_at, po.updated_at
+             FROM purchase_orders po
+             LEFT JOIN suppliers t ON po.tedarukcu_id = t.id
+             WHERE (?1 IS NULL OR po.status = ?1)
+             ORDER BY po.created_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let orders = stmt
+        .query_map([status.filter(|s| !s.is_empty())], row_to_purchase_order)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(orders)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn sifaris_detallari(
+    state: State<'_, AppState>,
+    token: String,
+    sifaris_id: i64,
+) -> Result<PurchaseOrderWithItems, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    sifaris_oxu(&db.conn, sifaris_id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn sifaris_qebul_et(
+    state: State<'_, AppState>,
+    token: String,
+    sifaris_id: i64,
+    qebul: ReceivePurchaseOrder,
+) -> Result<PurchaseOrderWithItems, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    if qebul.items.is_empty() {
+        return Err("Qəbul ediləcək məhsul seçilməyib".to_string());
+    }
+    if qebul.items.iter().any(|item| item.miqdar <= 0) {
+        return Err("Qəbul miqdarı müsbət olmalıdır".to_string());
+    }
+    if qebul.items.iter().any(|item| item.vahid_alis_qiymeti.is_some_and(|q| q < 0.0)) {
+        return Err("Alış qiyməti mənfi ola bilməz".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = serde_json::to_value(sifaris_oxu(&tx, sifaris_id)?).ok();
+
+    let (sifaris_nomresi, status): (String, String) = tx
+        .query_row(
+            "SELECT sifaris_nomresi, status FROM purchase_orders WHERE id = ?1",
+            [sifaris_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Sifariş tapılmadı: {}", e))?;
+
+    if status == STATUS_QEBUL || status == STATUS_BAGLI {
+        return Err(format!("{} sifarişi artıq bağlıdır ({})", sifaris_nomresi, status));
+    }
+
+    let qeyd = match &qebul.qeyd {
+        Some(q) if !q.trim().is_empty() => format!("Sifariş: {} - {}", sifaris_nomresi, q.trim()),
+        _ => format!("Sifariş: {}", sifaris_nomresi),
+    };
+
+    for item in &qebul.items {
+        let setir: Option<(i64, i64, Option<i64>, i32, i32, f64)> = tx
+            .query_row(
+                "SELECT mehsul_id, olcu_id, reng_id, sifaris_miqdari, qebul_miqdari, vahid_alis_qiymeti
+                 FROM purchase_order_items
+                 WHERE id = ?1 AND sifaris_id = ?2",
+                [item.setir_id, sifaris_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        let (mehsul_id, olcu_id, reng_id, sifaris_miqdari, qebul_miqdari, sifaris_qiymeti) =
+            setir.ok_or_else(|| "Sifariş sətri bu sifarişə aid deyil".to_string())?;
+
+        // Re-read after each line so a row listed twice is still capped
+        let qalan = sifaris_miqdari - qebul_miqdari;
+        if item.miqdar > qalan {
+            return Err(format!(
+                "Sifariş ediləndən artıq qəbul edilə bilməz: sifariş {}, qəbul edilib {}, qalıb {}, tələb olunan {}",
+                sifaris_miqdari, qebul_miqdari, qalan, item.miqdar
+            ));
+        }
+
+        let vahid_alis_qiymeti = item.vahid_alis_qiymeti.unwrap_or(sifaris_qiymeti);
+        let toplam_deyeri = item.miqdar as f64 * vahid_alis_qiymeti;
+
+        let evvelki: i32 = tx
+            .query_row(
+                "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3",
+                rusqlite::params![mehsul_id, olcu_id, reng_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .unwrap_or(0);
+
+        let yeni = evvelki + item.miqdar;
+
+        tx.execute(
+            "INSERT INTO stock (mehsul_id, olcu_id, reng_id, miqdar) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(mehsul_id, olcu_id, IFNULL(reng_id, 0)) DO UPDATE SET
+             miqdar = excluded.miqdar,
+             updated_at = CURRENT_TIMESTAMP",
+            rusqlite::params![mehsul_id, olcu_id, reng_id, yeni],
+        )
+        .map_err(|e| format!("Stok yenilənə bilmədi: {}", e))?;
+
+        tx.execute(
+            "INSERT INTO stock_movements (mehsul_id, olcu_id, reng_id, novu, miqdar, evvelki_miqdar, yeni_miqdar, vahid_alis_qiymeti, toplam_deyeri, sifaris_id, qeyd)
+             VALUES (?1, ?2, ?3, 'Daxil olma', ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                mehsul_id,
+                olcu_id,
+                reng_id,
+                item.miqdar,
+                evvelki,
+                yeni,
+                vahid_alis_qiymeti,
+                toplam_deyeri,
+                sifaris_id,
+                qeyd,
+            ],
+        )
+        .map_err(|e| format!("Stok hərəkəti yazıla bilmədi: {}", e))?;
+
+        let hereket_id = tx.last_insert_rowid();
+        cost::lay_elave_et(&tx, mehsul_id, olcu_id, reng_id, item.miqdar, vahid_alis_qiymeti, Some(hereket_id))?;
+
+        tx.execute(
+            "UPDATE purchase_order_items SET qebul_miqdari = qebul_miqdari + ?1 WHERE id = ?2",
+            [item.miqdar as i64, item.setir_id],
+        )
+        .map_err(|e| format!("Sifariş yenilənə bilmədi: {}", e))?;
+    }
+
+    // Fully received orders close themselves
+    let qalan_setir: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM purchase_order_items
+             WHERE sifaris_id = ?1 AND qebul_miqdari < sifaris_miqdari",
+            [sifaris_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let yeni_status = if qalan_setir == 0 { STATUS_QEBUL } else { STATUS_QISMEN };
+
+    tx.execute(
+        "UPDATE purchase_orders SET status = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+        rusqlite::params![yeni_status, sifaris_id],
+    )
+    .map_err(|e| format!("Sifariş yenilənə bilmədi: {}", e))?;
+
+    let result = sifaris_oxu(&tx, sifaris_id)?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "sifaris_qebul_et",
+        "purchase_orders",
+        Some(sifaris_id),
+        evvel,
+        serde_json::to_value(&result).ok(),
+    )?;
+
+    tx.commit().map_err(|e| format!("Qəbul yadda saxlanıla bilmədi: {}", e))?;
+
+    Ok(result)
+}
+
+/// Closes an order that will not be delivered in full; whatever was received stays.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn sifaris_bagla(
+    state: State<'_, AppState>,
+    token: String,
+    sifaris_id: i64,
+) -> Result<PurchaseOrderWithItems, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let status: String = tx
+        .query_row(
+            "SELECT status FROM purchase_orders WHERE id = ?1",
+            [sifaris_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Sifariş tapılmadı: {}", e))?;
+
+    if status == STATUS_QEBUL || status == STATUS_BAGLI {
+        return Err(format!("Sifariş artıq bağlıdır ({})", status));
+    }
+
+    let evvel = serde_json::to_value(sifaris_oxu(&tx, sifaris_id)?).ok();
+
+    tx
+        .execute(
+            "UPDATE purchase_orders SET status = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            rusqlite::params![STATUS_BAGLI, sifaris_id],
+        )
+        .map_err(|e| format!("Sifariş bağlana bilmədi: {}", e))?;
+
+    let result = sifaris_oxu(&tx, sifaris_id)?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "sifaris_bagla",
+        "purchase_orders",
+        Some(sifaris_id),
+        evvel,
+        serde_json::to_value(&result).ok(),
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(result)
+}
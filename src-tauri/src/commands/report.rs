@@ -1,5 +1,8 @@
+use crate::commands::category::alt_kateqoriyalar_cte;
 use crate::models::{LowStockAlert, ProductStatistics, ProductStatisticsReport, ProductMovementDetail};
 use crate::AppState;
+use chrono::NaiveDate;
+use rusqlite::OptionalExtension;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -30,17 +33,6 @@ pub struct MonthlySalesReport {
     pub net_mebleg: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct StockReport {
-    pub mehsul_id: i64,
-    pub mehsul_adi: String,
-    pub barkod: String,
-    pub olcu: String,
-    pub miqdar: i32,
-    pub minimum_miqdar: i32,
-    pub deger: f64,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProfitReportItem {
     pub mehsul_id: i64,
@@ -67,10 +59,57 @@ pub struct ProfitReport {
     pub net_qazanc: f64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrandReportItem {
+    pub marka_id: Option<i64>,
+    pub marka: String,
+    pub satis_miqdar: i32,
+    pub satis_deyeri: f64,
+    pub maya_deyeri: f64,
+    pub endirim: f64,
+    pub qazanc: f64,
+    pub qazanc_faizi: f64,
+    pub stok_miqdari: i32,
+    pub stok_alis_deyeri: f64,
+    pub stok_satis_deyeri: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
-pub struct SalesListFilter {
-    pub baslangic_tarix: Option<String>,
-    pub bitis_tarix: Option<String>,
+pub struct BrandReport {
+    pub baslangic_tarix: String,
+    pub bitis_tarix: String,
+    pub items: Vec<BrandReportItem>,
+    pub toplam_satis: f64,
+    pub toplam_qazanc: f64,
+    pub toplam_stok_alis_deyeri: f64,
+    pub toplam_stok_satis_deyeri: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromotionReportItem {
+    pub kampaniya_id: i64,
+    pub kampaniya: String,
+    pub novu: String,
+    pub satis_sayi: i32,
+    pub miqdar: i32,
+    pub brutto_satis: f64,
+    pub endirim: f64,
+    pub net_satis: f64,
+    pub maya_deyeri: f64,
+    pub qazanc: f64,
+    pub endirim_faizi: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromotionReport {
+    pub baslangic_tarix: String,
+    pub bitis_tarix: String,
+    pub items: Vec<PromotionReportItem>,
+    pub toplam_endirim: f64,
+    pub toplam_net_satis: f64,
+    /// Every line sold in the period, with or without a promotion
+    pub umumi_satis: f64,
+    pub kampaniya_payi: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -87,16 +126,87 @@ pub struct SaleListItem {
     pub iade_durumu: String,  // "Yoxdur", "Qismən", "Tam"
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum TarixXetasi {
+    #[error("Yanlış tarix: '{0}' (gözlənilən format: İİİİ-AA-GG)")]
+    YanlisTarix(String),
+    #[error("Yanlış ay: '{0}' (gözlənilən format: İİİİ-AA)")]
+    YanlisAy(String),
+    #[error("Başlanğıc tarix ({baslangic}) bitiş tarixindən ({bitis}) sonra ola bilməz")]
+    TersAraliq { baslangic: String, bitis: String },
+}
+
+impl From<TarixXetasi> for String {
+    fn from(e: TarixXetasi) -> Self {
+        e.to_string()
+    }
+}
+
+// Empty strings from the date pickers mean "no filter"
+fn bos_deyilse(tarix: Option<String>) -> Option<String> {
+    tarix.map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+pub fn tarix_yoxla(tarix: &str) -> Result<NaiveDate, TarixXetasi> {
+    NaiveDate::parse_from_str(tarix, "%Y-%m-%d").map_err(|_| TarixXetasi::YanlisTarix(tarix.to_string()))
+}
+
+/// Validates an optional date range; both ends stay optional.
+pub fn secimli_tarix_araligi(
+    baslangic: Option<String>,
+    bitis: Option<String>,
+) -> Result<(Option<String>, Option<String>), TarixXetasi> {
+    let baslangic = bos_deyilse(baslangic);
+    let bitis = bos_deyilse(bitis);
+
+    let b = baslangic.as_deref().map(tarix_yoxla).transpose()?;
+    let s = bitis.as_deref().map(tarix_yoxla).transpose()?;
+
+    if let (Some(b), Some(s)) = (b, s) {
+        if b > s {
+            return Err(TarixXetasi::TersAraliq {
+                baslangic: b.to_string(),
+                bitis: s.to_string(),
+            });
+        }
+    }
+
+    Ok((b.map(|d| d.to_string()), s.map(|d| d.to_string())))
+}
+
+/// Validates a date range, defaulting to the start of the current month through today.
+pub fn tarix_araligi(
+    baslangic: Option<String>,
+    bitis: Option<String>,
+) -> Result<(String, String), TarixXetasi> {
+    let (baslangic, bitis) = secimli_tarix_araligi(baslangic, bitis)?;
+
+    let baslangic = baslangic.unwrap_or_else(|| chrono::Local::now().format("%Y-%m-01").to_string());
+    let bitis = bitis.unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+
+    if baslangic > bitis {
+        return Err(TarixXetasi::TersAraliq { baslangic, bitis });
+    }
+
+    Ok((baslangic, bitis))
+}
+
 #[tauri::command]
 pub async fn gunluk_satis_hesabati(
     state: State<'_, AppState>,
+    token: String,
     tarix: Option<String>,
 ) -> Result<DailySalesReport, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
-    let date_filter = tarix.unwrap_or_else(|| "date('now')".to_string());
+    let date_filter = match bos_deyilse(tarix) {
+        Some(t) => tarix_yoxla(&t)?.to_string(),
+        None => chrono::Local::now().format("%Y-%m-%d").to_string(),
+    };
     
-    let query = format!(
+    let result = db.conn.query_row(
         "SELECT 
             date(created_at) as tarix,
             COUNT(*) as satis_sayi,
@@ -104,43 +214,53 @@ pub async fn gunluk_satis_hesabati(
             COALESCE(SUM(endirim), 0) as endirim,
             COALESCE(SUM(son_mebleg), 0) as net_mebleg
          FROM sales
-         WHERE date(created_at) = {}
+         WHERE date(created_at) = ?1
          GROUP BY date(created_at)",
-        if date_filter == "date('now')" { date_filter.clone() } else { format!("'{}'", date_filter) }
-    );
-    
-    let result = db.conn.query_row(&query, [], |row| {
-        Ok(DailySalesReport {
-            tarix: row.get(0)?,
-            satis_sayi: row.get(1)?,
-            toplam_mebleg: row.get(2)?,
-            endirim: row.get(3)?,
-            net_mebleg: row.get(4)?,
-        })
-    });
-    
-    match result {
-        Ok(report) => Ok(report),
-        Err(_) => Ok(DailySalesReport {
-            tarix: date_filter,
-            satis_sayi: 0,
-            toplam_mebleg: 0.0,
-            endirim: 0.0,
-            net_mebleg: 0.0,
-        }),
-    }
+        [&date_filter],
+        |row| {
+            Ok(DailySalesReport {
+                tarix: row.get(0)?,
+                satis_sayi: row.get(1)?,
+                toplam_mebleg: row.get(2)?,
+                endirim: row.get(3)?,
+                net_mebleg: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Hesabat hazırlana bilmədi: {}", e))?;
+
+    // No sales in the period gives no group at all
+    Ok(result.unwrap_or(DailySalesReport {
+        tarix: date_filter,
+        satis_sayi: 0,
+        toplam_mebleg: 0.0,
+        endirim: 0.0,
+        net_mebleg: 0.0,
+    }))
 }
 
 #[tauri::command]
 pub async fn ayliq_satis_hesabati(
     state: State<'_, AppState>,
+    token: String,
     ay: Option<String>,
 ) -> Result<MonthlySalesReport, String> {
+    state.sessions.admin_yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
-    let month_filter = ay.unwrap_or_else(|| "strftime('%Y-%m', 'now')".to_string());
+    let month_filter = match bos_deyilse(ay) {
+        Some(ay) => {
+            if ay.len() != 7 || NaiveDate::parse_from_str(&format!("{}-01", ay), "%Y-%m-%d").is_err() {
+                return Err(TarixXetasi::YanlisAy(ay).into());
+            }
+            ay
+        }
+        None => chrono::Local::now().format("%Y-%m").to_string(),
+    };
     
-    let query = format!(
+    let result = db.conn.query_row(
         "SELECT 
             strftime('%Y-%m', created_at) as ay,
             COUNT(*) as satis_sayi,
@@ -148,43 +268,45 @@ pub async fn ayliq_satis_hesabati(
             COALESCE(SUM(endirim), 0) as endirim,
             COALESCE(SUM(son_mebleg), 0) as net_mebleg
          FROM sales
-         WHERE strftime('%Y-%m', created_at) = {}
+         WHERE strftime('%Y-%m', created_at) = ?1
          GROUP BY strftime('%Y-%m', created_at)",
-        if month_filter.contains("strftime") { month_filter.clone() } else { format!("'{}'", month_filter) }
-    );
-    
-    let result = db.conn.query_row(&query, [], |row| {
-        Ok(MonthlySalesReport {
-            ay: row.get(0)?,
-            satis_sayi: row.get(1)?,
-            toplam_mebleg: row.get(2)?,
-            endirim: row.get(3)?,
-            net_mebleg: row.get(4)?,
-        })
-    });
-    
-    match result {
-        Ok(report) => Ok(report),
-        Err(_) => Ok(MonthlySalesReport {
-            ay: month_filter,
-            satis_sayi: 0,
-            toplam_mebleg: 0.0,
-            endirim: 0.0,
-            net_mebleg: 0.0,
-        }),
-    }
+        [&month_filter],
+        |row| {
+            Ok(MonthlySalesReport {
+                ay: row.get(0)?,
+                satis_sayi: row.get(1)?,
+                toplam_mebleg: row.get(2)?,
+                endirim: row.get(3)?,
+                net_mebleg: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Hesabat hazırlana bilmədi: {}", e))?;
+
+    // No sales in the period gives no group at all
+    Ok(result.unwrap_or(MonthlySalesReport {
+        ay: month_filter,
+        satis_sayi: 0,
+        toplam_mebleg: 0.0,
+        endirim: 0.0,
+        net_mebleg: 0.0,
+    }))
 }
 
 #[tauri::command]
-pub async fn stok_hesabati(state: State<'_, AppState>) -> Result<Vec<LowStockAlert>, String> {
+pub async fn stok_hesabati(state: State<'_, AppState>, token: String) -> Result<Vec<LowStockAlert>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
     let mut stmt = db.conn
         .prepare(
-            "SELECT s.mehsul_id, p.ad, p.barkod, sz.olcu, s.miqdar, s.minimum_miqdar
+            "SELECT s.mehsul_id, p.ad, p.barkod, sz.olcu, s.miqdar, s.minimum_miqdar, cl.ad
              FROM stock s
              JOIN products p ON s.mehsul_id = p.id
              JOIN sizes sz ON s.olcu_id = sz.id
+             LEFT JOIN colors cl ON s.reng_id = cl.id
              WHERE s.miqdar <= s.minimum_miqdar
              ORDER BY s.miqdar ASC",
         )
@@ -197,6 +319,7 @@ pub async fn stok_hesabati(state: State<'_, AppState>) -> Result<Vec<LowStockAle
                 mehsul_adi: row.get(1)?,
                 barkod: row.get(2)?,
                 olcu: row.get(3)?,
+                reng: row.get(6)?,
                 miqdar: row.get(4)?,
                 minimum_miqdar: row.get(5)?,
             })
@@ -208,15 +331,20 @@ pub async fn stok_hesabati(state: State<'_, AppState>) -> Result<Vec<LowStockAle
     Ok(alerts)
 }
 
-#[tauri::command]
+#[tauri::command(rename_all = "camelCase")]
 pub async fn satis_siyahisi_tarixe_gore(
     state: State<'_, AppState>,
-    baslangicTarix: Option<String>,
-    bitisTarix: Option<String>,
+    token: String,
+    baslangic_tarix: Option<String>,
+    bitis_tarix: Option<String>,
 ) -> Result<Vec<SaleListItem>, String> {
+    state.sessions.yoxla(&token)?;
+
+    let (baslangic, bitis) = secimli_tarix_araligi(baslangic_tarix, bitis_tarix)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
-    let mut query = String::from(
+    let mut stmt = db.conn.prepare(
         "SELECT s.id, s.satis_nomresi, s.toplam_mebleg, s.endirim, s.son_mebleg,
                 s.odenis_usulu, s.qeyd, s.created_at,
                 (SELECT COUNT(*) FROM sale_items WHERE satis_id = s.id) as mehsul_sayi,
@@ -228,23 +356,13 @@ pub async fn satis_siyahisi_tarixe_gore(
                     ELSE 'Qismən'
                 END as iade_durumu
          FROM sales s
-         WHERE 1=1"
-    );
-
-    if let Some(ref start) = baslangicTarix {
-        query.push_str(&format!(" AND date(s.created_at) >= '{}'", start));
-    }
-
-    if let Some(ref end) = bitisTarix {
-        query.push_str(&format!(" AND date(s.created_at) <= '{}'", end));
-    }
-
-    query.push_str(" ORDER BY s.created_at DESC");
-
-    let mut stmt = db.conn.prepare(&query).map_err(|e| e.to_string())?;
+         WHERE (?1 IS NULL OR date(s.created_at) >= ?1)
+           AND (?2 IS NULL OR date(s.created_at) <= ?2)
+         ORDER BY s.created_at DESC"
+    ).map_err(|e| e.to_string())?;
 
     let sales = stmt
-        .query_map([], |row| {
+        .query_map(rusqlite::params![baslangic, bitis], |row| {
             Ok(SaleListItem {
                 id: row.get(0)?,
                 satis_nomresi: row.get(1)?,
@@ -265,47 +383,49 @@ pub async fn satis_siyahisi_tarixe_gore(
     Ok(sales)
 }
 
-#[tauri::command]
+#[tauri::command(rename_all = "camelCase")]
 pub async fn qazanc_hesabati(
     state: State<'_, AppState>,
-    baslangicTarix: Option<String>,
-    bitisTarix: Option<String>,
+    token: String,
+    baslangic_tarix: Option<String>,
+    bitis_tarix: Option<String>,
+    kateqoriya_id: Option<i64>,
 ) -> Result<ProfitReport, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    // Defaults to the start of the current month through today
+    let (start_date, end_date) = tarix_araligi(baslangic_tarix, bitis_tarix)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
-    let start_date = baslangicTarix.unwrap_or_else(|| {
-        // Default to start of current month
-        chrono::Local::now().format("%Y-%m-01").to_string()
-    });
-    
-    let end_date = bitisTarix.unwrap_or_else(|| {
-        chrono::Local::now().format("%Y-%m-%d").to_string()
-    });
-    
-    // Get profit details per product sold - subtract returns
-    let query = format!(
-        "WITH satislar AS (
+    // Get profit details per product sold - subtract returns, at the cost realized on each line.
+    // ?3 is the optional category, taken together with its sub-categories.
+    let mut stmt = db.conn.prepare(&format!(
+        "WITH RECURSIVE {},
+         satislar AS (
             SELECT p.id, p.ad, p.barkod, sz.olcu, sz.id as olcu_id,
                    SUM(si.miqdar) as miqdar,
-                   p.alis_qiymeti, si.vahid_qiymeti,
-                   SUM(si.miqdar * p.alis_qiymeti) as toplam_alis,
+                   SUM(si.miqdar * COALESCE(si.vahid_maya_deyeri, p.alis_qiymeti)) / SUM(si.miqdar) as alis_qiymeti,
+                   si.vahid_qiymeti,
+                   SUM(si.miqdar * COALESCE(si.vahid_maya_deyeri, p.alis_qiymeti)) as toplam_alis,
                    SUM(si.toplam_qiymet) as toplam_satis
             FROM sale_items si
             JOIN sales s ON si.satis_id = s.id
             JOIN products p ON si.mehsul_id = p.id
             JOIN sizes sz ON si.olcu_id = sz.id
-            WHERE date(s.created_at) >= '{}' AND date(s.created_at) <= '{}'
+            WHERE date(s.created_at) >= ?1 AND date(s.created_at) <= ?2
+              AND (?3 IS NULL OR p.kateqoriya_id IN (SELECT id FROM alt_kateqoriyalar))
             GROUP BY p.id, sz.id
          ),
          iadeler AS (
             SELECT ri.mehsul_id, ri.olcu_id,
                    SUM(ri.miqdar) as miqdar,
-                   SUM(ri.miqdar * p.alis_qiymeti) as toplam_alis,
+                   SUM(ri.miqdar * COALESCE(ri.vahid_maya_deyeri, p.alis_qiymeti)) as toplam_alis,
                    SUM(ri.toplam_qiymet) as toplam_satis
             FROM return_items ri
             JOIN returns r ON ri.iade_id = r.id
             JOIN products p ON ri.mehsul_id = p.id
-            WHERE date(r.created_at) >= '{}' AND date(r.created_at) <= '{}'
+            WHERE date(r.created_at) >= ?1 AND date(r.created_at) <= ?2
             GROUP BY ri.mehsul_id, ri.olcu_id
          )
          SELECT s.id as mehsul_id,
@@ -322,13 +442,11 @@ pub async fn qazanc_hesabati(
          LEFT JOIN iadeler i ON s.id = i.mehsul_id AND s.olcu_id = i.olcu_id
          WHERE s.miqdar - COALESCE(i.miqdar, 0) > 0
          ORDER BY qazanc DESC",
-        start_date, end_date, start_date, end_date
-    );
-    
-    let mut stmt = db.conn.prepare(&query).map_err(|e| e.to_string())?;
+        alt_kateqoriyalar_cte("?3")
+    )).map_err(|e| e.to_string())?;
     
     let items: Vec<ProfitReportItem> = stmt
-        .query_map([], |row| {
+        .query_map(rusqlite::params![start_date, end_date, kateqoriya_id], |row| {
             Ok(ProfitReportItem {
                 mehsul_id: row.get(0)?,
                 mehsul_adi: row.get(1)?,
@@ -351,16 +469,38 @@ pub async fn qazanc_hesabati(
     let toplam_satis: f64 = items.iter().map(|i| i.toplam_satis).sum();
     let toplam_qazanc: f64 = items.iter().map(|i| i.qazanc).sum();
     
-    // Get total discount
-    let discount_query = format!(
-        "SELECT COALESCE(SUM(endirim), 0) FROM sales 
-         WHERE date(created_at) >= '{}' AND date(created_at) <= '{}'",
-        start_date, end_date
-    );
-    
-    let toplam_endirim: f64 = db.conn
-        .query_row(&discount_query, [], |row| row.get(0))
-        .unwrap_or(0.0);
+    // Get total discount; for one category, each sale's discount is shared
+    // out by that category's part of the sale
+    let toplam_endirim: f64 = match kateqoriya_id {
+        None => db.conn
+            .query_row(
+                "SELECT COALESCE(SUM(endirim), 0) FROM sales 
+                 WHERE date(created_at) >= ?1 AND date(created_at) <= ?2",
+                [&start_date, &end_date],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?,
+        Some(_) => db.conn
+            .query_row(
+                &format!(
+                    "WITH RECURSIVE {}
+                     SELECT COALESCE(SUM(s.endirim * k.mebleg / NULLIF(s.toplam_mebleg, 0)), 0)
+                     FROM sales s
+                     JOIN (
+                        SELECT si.satis_id, SUM(si.toplam_qiymet) as mebleg
+                        FROM sale_items si
+                        JOIN products p ON si.mehsul_id = p.id
+                        WHERE p.kateqoriya_id IN (SELECT id FROM alt_kateqoriyalar)
+                        GROUP BY si.satis_id
+                     ) k ON k.satis_id = s.id
+                     WHERE date(s.created_at) >= ?1 AND date(s.created_at) <= ?2",
+                    alt_kateqoriyalar_cte("?3")
+                ),
+                rusqlite::params![start_date, end_date, kateqoriya_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?,
+    };
     
     Ok(ProfitReport {
         baslangic_tarix: start_date,
@@ -374,20 +514,32 @@ pub async fn qazanc_hesabati(
     })
 }
 
-#[tauri::command]
-pub async fn stok_deyeri_hesabati(state: State<'_, AppState>) -> Result<StockValueReport, String> {
+#[tauri::command(rename_all = "camelCase")]
+pub async fn stok_deyeri_hesabati(
+    state: State<'_, AppState>,
+    token: String,
+    kateqoriya_id: Option<i64>,
+) -> Result<StockValueReport, String> {
+    state.sessions.admin_yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
-    let result = db.conn.query_row(
-        "SELECT
+    // ?1 narrows the totals to one category and everything below it
+    db.conn.query_row(
+        &format!(
+        "WITH RECURSIVE {}
+         SELECT
             COUNT(DISTINCT s.mehsul_id) as toplam_mehsul,
             COALESCE(SUM(s.miqdar), 0) as toplam_stok,
             COALESCE(SUM(s.miqdar * p.alis_qiymeti), 0) as alis_deyeri,
             COALESCE(SUM(s.miqdar * p.satis_qiymeti), 0) as satis_deyeri
          FROM stock s
          JOIN products p ON s.mehsul_id = p.id
-         WHERE s.miqdar > 0",
-        [],
+         WHERE s.miqdar > 0
+           AND (?1 IS NULL OR p.kateqoriya_id IN (SELECT id FROM alt_kateqoriyalar))",
+            alt_kateqoriyalar_cte("?1")
+        ),
+        [kateqoriya_id],
         |row| {
             let toplam_mehsul: i32 = row.get(0)?;
             let toplam_stok: i32 = row.get(1)?;
@@ -401,46 +553,228 @@ pub async fn stok_deyeri_hesabati(state: State<'_, AppState>) -> Result<StockVal
                 potensial_qazanc: satis_deyeri - alis_deyeri,
             })
         },
-    );
-
-    match result {
-        Ok(report) => Ok(report),
-        Err(_) => Ok(StockValueReport {
-            toplam_mehsul: 0,
-            toplam_stok: 0,
-            alis_deyeri: 0.0,
-            satis_deyeri: 0.0,
-            potensial_qazanc: 0.0,
-        }),
-    }
+    )
+    .map_err(|e| format!("Hesabat hazırlana bilmədi: {}", e))
 }
 
-#[tauri::command]
-pub async fn mehsul_statistikasi(
+/// Sales net of returns, margin and stock on hand per brand. Products
+/// without a brand are reported together as "Markasız".
+#[tauri::command(rename_all = "camelCase")]
+pub async fn marka_hesabati(
     state: State<'_, AppState>,
-    baslangicTarix: Option<String>,
-    bitisTarix: Option<String>,
-    kateqoriyaId: Option<i64>,
-) -> Result<ProductStatisticsReport, String> {
+    token: String,
+    baslangic_tarix: Option<String>,
+    bitis_tarix: Option<String>,
+) -> Result<BrandReport, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let (start_date, end_date) = tarix_araligi(baslangic_tarix, bitis_tarix)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
-    let start_date = baslangicTarix.unwrap_or_else(|| {
-        chrono::Local::now().format("%Y-%m-01").to_string()
-    });
+    // Brand 0 stands for "no brand". Each sale's discount is shared out by
+    // the brand's part of the sale, as in the category profit report.
+    let mut stmt = db.conn
+        .prepare(
+            "WITH satislar AS (
+                SELECT COALESCE(p.marka_id, 0) as marka,
+                       SUM(si.miqdar) as miqdar,
+                       SUM(si.toplam_qiymet) as mebleg,
+                       SUM(si.miqdar * COALESCE(si.vahid_maya_deyeri, p.alis_qiymeti)) as maya,
+                       SUM(s.endirim * si.toplam_qiymet / NULLIF(s.toplam_mebleg, 0)) as endirim
+                FROM sale_items si
+                JOIN sales s ON si.satis_id = s.id
+                JOIN products p ON si.mehsul_id = p.id
+                WHERE date(s.created_at) >= ?1 AND date(s.created_at) <= ?2
+                GROUP BY marka
+             ),
+             iadeler AS (
+                SELECT COALESCE(p.marka_id, 0) as marka,
+                       SUM(ri.miqdar) as miqdar,
+                       SUM(ri.toplam_qiymet) as mebleg,
+                       SUM(ri.miqdar * COALESCE(ri.vahid_maya_deyeri, p.alis_qiymeti)) as maya
+                FROM return_items ri
+                JOIN returns r ON ri.iade_id = r.id
+                JOIN products p ON ri.mehsul_id = p.id
+                WHERE date(r.created_at) >= ?1 AND date(r.created_at) <= ?2
+                GROUP BY marka
+             ),
+             stoklar AS (
+                SELECT COALESCE(p.marka_id, 0) as marka,
+                       SUM(s.miqdar) as miqdar,
+                       SUM(s.miqdar * p.alis_qiymeti) as alis_deyeri,
+                       SUM(s.miqdar * p.satis_qiymeti) as satis_deyeri
+                FROM stock s
+                JOIN products p ON s.mehsul_id = p.id
+                WHERE s.miqdar > 0
+                GROUP BY marka
+             ),
+             markalar AS (
+                SELECT marka FROM satislar
+                UNION SELECT marka FROM iadeler
+                UNION SELECT marka FROM stoklar
+             )
+             SELECT NULLIF(m.marka, 0),
+                    COALESCE(b.ad, 'Markasız'),
+                    COALESCE(s.miqdar, 0) - COALESCE(i.miqdar, 0),
+                    COALESCE(s.mebleg, 0) - COALESCE(i.mebleg, 0),
+                    COALESCE(s.maya, 0) - COALESCE(i.maya, 0),
+                    COALESCE(s.endirim, 0),
+                    COALESCE(st.miqdar, 0),
+                    COALESCE(st.alis_deyeri, 0),
+                    COALESCE(st.satis_deyeri, 0)
+             FROM markalar m
+             LEFT JOIN brands b ON b.id = m.marka
+             LEFT JOIN satislar s ON s.marka = m.marka
+             LEFT JOIN iadeler i ON i.marka = m.marka
+             LEFT JOIN stoklar st ON st.marka = m.marka",
+        )
+        .map_err(|e| e.to_string())?;
 
-    let end_date = bitisTarix.unwrap_or_else(|| {
-        chrono::Local::now().format("%Y-%m-%d").to_string()
-    });
+    let mut items: Vec<BrandReportItem> = stmt
+        .query_map([&start_date, &end_date], |row| {
+            let satis_deyeri: f64 = row.get(3)?;
+            let maya_deyeri: f64 = row.get(4)?;
+            let endirim: f64 = row.get(5)?;
+            let qazanc = satis_deyeri - maya_deyeri - endirim;
+            Ok(BrandReportItem {
+                marka_id: row.get(0)?,
+                marka: row.get(1)?,
+                satis_miqdar: row.get(2)?,
+                satis_deyeri,
+                maya_deyeri,
+                endirim,
+                qazanc,
+                qazanc_faizi: if satis_deyeri - endirim > 0.0 {
+                    qazanc / (satis_deyeri - endirim) * 100.0
+                } else {
+                    0.0
+                },
+                stok_miqdari: row.get(6)?,
+                stok_alis_deyeri: row.get(7)?,
+                stok_satis_deyeri: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-    // Category filter
-    let category_filter = match kateqoriyaId {
-        Some(id) => format!("AND p.kateqoriya_id = {}", id),
-        None => String::new(),
-    };
+    // Most profitable brands first
+    items.sort_by(|a, b| b.qazanc.total_cmp(&a.qazanc));
+
+    Ok(BrandReport {
+        baslangic_tarix: start_date,
+        bitis_tarix: end_date,
+        toplam_satis: items.iter().map(|i| i.satis_deyeri - i.endirim).sum(),
+        toplam_qazanc: items.iter().map(|i| i.qazanc).sum(),
+        toplam_stok_alis_deyeri: items.iter().map(|i| i.stok_alis_deyeri).sum(),
+        toplam_stok_satis_deyeri: items.iter().map(|i| i.stok_satis_deyeri).sum(),
+        items,
+    })
+}
+
+/// How much each promotion sold and gave away, and what share of all sales
+/// went through promotions. Lines are counted as sold; returns are not taken off.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kampaniya_hesabati(
+    state: State<'_, AppState>,
+    token: String,
+    baslangic_tarix: Option<String>,
+    bitis_tarix: Option<String>,
+) -> Result<PromotionReport, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let (start_date, end_date) = tarix_araligi(baslangic_tarix, bitis_tarix)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
 
-    // Complex query to get product statistics
-    let query = format!(
-        "WITH alislar AS (
+    let mut stmt = db.conn
+        .prepare(
+            "SELECT pr.id, pr.ad, pr.novu,
+                    COUNT(DISTINCT si.satis_id),
+                    SUM(si.miqdar),
+                    SUM(si.miqdar * si.vahid_qiymeti),
+                    SUM(si.endirim),
+                    SUM(si.toplam_qiymet),
+                    SUM(si.miqdar * COALESCE(si.vahid_maya_deyeri, p.alis_qiymeti))
+             FROM sale_items si
+             JOIN sales s ON si.satis_id = s.id
+             JOIN promotions pr ON si.kampaniya_id = pr.id
+             JOIN products p ON si.mehsul_id = p.id
+             WHERE date(s.created_at) >= ?1 AND date(s.created_at) <= ?2
+             GROUP BY pr.id
+             ORDER BY SUM(si.endirim) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let items: Vec<PromotionReportItem> = stmt
+        .query_map([&start_date, &end_date], |row| {
+            let brutto_satis: f64 = row.get(5)?;
+            let endirim: f64 = row.get(6)?;
+            let net_satis: f64 = row.get(7)?;
+            let maya_deyeri: f64 = row.get(8)?;
+            Ok(PromotionReportItem {
+                kampaniya_id: row.get(0)?,
+                kampaniya: row.get(1)?,
+                novu: row.get(2)?,
+                satis_sayi: row.get(3)?,
+                miqdar: row.get(4)?,
+                brutto_satis,
+                endirim,
+                net_satis,
+                maya_deyeri,
+                qazanc: net_satis - maya_deyeri,
+                endirim_faizi: if brutto_satis > 0.0 { endirim / brutto_satis * 100.0 } else { 0.0 },
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let umumi_satis: f64 = db.conn
+        .query_row(
+            "SELECT COALESCE(SUM(si.toplam_qiymet), 0)
+             FROM sale_items si
+             JOIN sales s ON si.satis_id = s.id
+             WHERE date(s.created_at) >= ?1 AND date(s.created_at) <= ?2",
+            [&start_date, &end_date],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let toplam_endirim: f64 = items.iter().map(|i| i.endirim).sum();
+    let toplam_net_satis: f64 = items.iter().map(|i| i.net_satis).sum();
+
+    Ok(PromotionReport {
+        baslangic_tarix: start_date,
+        bitis_tarix: end_date,
+        items,
+        toplam_endirim,
+        toplam_net_satis,
+        umumi_satis,
+        kampaniya_payi: if umumi_satis > 0.0 { toplam_net_satis / umumi_satis * 100.0 } else { 0.0 },
+    })
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn mehsul_statistikasi(
+    state: State<'_, AppState>,
+    token: String,
+    baslangic_tarix: Option<String>,
+    bitis_tarix: Option<String>,
+    kateqoriya_id: Option<i64>,
+) -> Result<ProductStatisticsReport, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let (start_date, end_date) = tarix_araligi(baslangic_tarix, bitis_tarix)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    // Complex query to get product statistics (?3 is the optional category
+    // filter, which takes in every sub-category below it)
+    let mut stmt = db.conn.prepare(&format!(
+        "WITH RECURSIVE {},
+        alislar AS (
             SELECT
                 sm.mehsul_id,
                 SUM(sm.miqdar) as alis_miqdar,
@@ -448,9 +782,9 @@ pub async fn mehsul_statistikasi(
             FROM stock_movements sm
             JOIN products p ON sm.mehsul_id = p.id
             WHERE sm.novu = 'Daxil olma'
-              AND date(sm.created_at) >= '{0}'
-              AND date(sm.created_at) <= '{1}'
-              {2}
+              AND date(sm.created_at) >= ?1
+              AND date(sm.created_at) <= ?2
+              AND (?3 IS NULL OR p.kateqoriya_id IN (SELECT id FROM alt_kateqoriyalar))
             GROUP BY sm.mehsul_id
         ),
         satislar AS (
@@ -458,13 +792,13 @@ pub async fn mehsul_statistikasi(
                 si.mehsul_id,
                 SUM(si.miqdar) as satis_miqdar,
                 SUM(si.toplam_qiymet) as satis_deyeri,
-                SUM(si.miqdar * p.alis_qiymeti) as satis_maya_deyeri
+                SUM(si.miqdar * COALESCE(si.vahid_maya_deyeri, p.alis_qiymeti)) as satis_maya_deyeri
             FROM sale_items si
             JOIN sales s ON si.satis_id = s.id
             JOIN products p ON si.mehsul_id = p.id
-            WHERE date(s.created_at) >= '{0}'
-              AND date(s.created_at) <= '{1}'
-              {2}
+            WHERE date(s.created_at) >= ?1
+              AND date(s.created_at) <= ?2
+              AND (?3 IS NULL OR p.kateqoriya_id IN (SELECT id FROM alt_kateqoriyalar))
             GROUP BY si.mehsul_id
         ),
         hazirki_stok AS (
@@ -495,15 +829,13 @@ pub async fn mehsul_statistikasi(
         LEFT JOIN satislar s ON p.id = s.mehsul_id
         LEFT JOIN hazirki_stok hs ON p.id = hs.mehsul_id
         WHERE (COALESCE(a.alis_miqdar, 0) > 0 OR COALESCE(s.satis_miqdar, 0) > 0)
-        {2}
+          AND (?3 IS NULL OR p.kateqoriya_id IN (SELECT id FROM alt_kateqoriyalar))
         ORDER BY toplam_qazanc DESC",
-        start_date, end_date, category_filter
-    );
-
-    let mut stmt = db.conn.prepare(&query).map_err(|e| e.to_string())?;
+        alt_kateqoriyalar_cte("?3")
+    )).map_err(|e| e.to_string())?;
 
     let items: Vec<ProductStatistics> = stmt
-        .query_map([], |row| {
+        .query_map(rusqlite::params![start_date, end_date, kateqoriya_id], |row| {
             Ok(ProductStatistics {
                 mehsul_id: row.get(0)?,
                 mehsul_adi: row.get(1)?,
@@ -549,16 +881,21 @@ pub async fn mehsul_statistikasi(
     })
 }
 
-#[tauri::command]
+#[tauri::command(rename_all = "camelCase")]
 pub async fn mehsul_hereketleri(
     state: State<'_, AppState>,
-    mehsulId: i64,
-    baslangicTarix: Option<String>,
-    bitisTarix: Option<String>,
+    token: String,
+    mehsul_id: i64,
+    baslangic_tarix: Option<String>,
+    bitis_tarix: Option<String>,
 ) -> Result<Vec<ProductMovementDetail>, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let (baslangic, bitis) = secimli_tarix_araligi(baslangic_tarix, bitis_tarix)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
-    let mut query = String::from(
+    let mut stmt = db.conn.prepare(
         "SELECT
             sm.id,
             sm.created_at as tarix,
@@ -568,23 +905,14 @@ pub async fn mehsul_hereketleri(
             sm.toplam_deyeri,
             sm.qeyd
          FROM stock_movements sm
-         WHERE sm.mehsul_id = ?"
-    );
-
-    if let Some(ref start) = baslangicTarix {
-        query.push_str(&format!(" AND date(sm.created_at) >= '{}'", start));
-    }
-
-    if let Some(ref end) = bitisTarix {
-        query.push_str(&format!(" AND date(sm.created_at) <= '{}'", end));
-    }
-
-    query.push_str(" ORDER BY sm.created_at DESC");
-
-    let mut stmt = db.conn.prepare(&query).map_err(|e| e.to_string())?;
+         WHERE sm.mehsul_id = ?1
+           AND (?2 IS NULL OR date(sm.created_at) >= ?2)
+           AND (?3 IS NULL OR date(sm.created_at) <= ?3)
+         ORDER BY sm.created_at DESC"
+    ).map_err(|e| e.to_string())?;
 
     let movements: Vec<ProductMovementDetail> = stmt
-        .query_map([mehsulId], |row| {
+        .query_map(rusqlite::params![mehsul_id, baslangic, bitis], |row| {
             Ok(ProductMovementDetail {
                 id: row.get(0)?,
                 tarix: row.get(1)?,
@@ -1,7 +1,12 @@
+use crate::audit;
+use crate::commands::shift::aktiv_novbe_id;
+use crate::inventory::cost;
+use crate::inventory::StokAcari;
+use crate::numbering::{self, Sened};
 use crate::AppState;
+use rusqlite::{Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use tauri::State;
-use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Return {
@@ -26,6 +31,8 @@ pub struct ReturnItem {
     pub mehsul_barkod: Option<String>,
     pub olcu_id: i64,
     pub olcu: Option<String>,
+    pub reng_id: Option<i64>,
+    pub reng: Option<String>,
     pub miqdar: i32,
     pub vahid_qiymeti: f64,
     pub toplam_qiymet: f64,
@@ -44,6 +51,7 @@ pub struct CreateReturn {
 pub struct CreateReturnItem {
     pub mehsul_id: i64,
     pub olcu_id: i64,
+    pub reng_id: Option<i64>,
     pub miqdar: i32,
     pub vahid_qiymeti: f64,
 }
@@ -55,57 +63,141 @@ pub struct ReturnWithItems {
     pub items: Vec<ReturnItem>,
 }
 
+// "Product (size, colour)" label used in validation messages
+fn mehsul_etiketi(conn: &Connection, mehsul_id: i64, olcu_id: i64, reng_id: Option<i64>) -> String {
+    conn.query_row(
+        "SELECT p.ad || ' (' || sz.olcu || COALESCE(', ' || cl.ad, '') || ')' FROM products p
+         JOIN sizes sz ON sz.id = ?2
+         LEFT JOIN colors cl ON cl.id = ?3
+         WHERE p.id = ?1",
+        rusqlite::params![mehsul_id, olcu_id, reng_id],
+        |row| row.get(0),
+    )
+    .unwrap_or_else(|_| "Məhsul".to_string())
+}
+
 #[tauri::command]
 pub async fn iade_yarat(
     state: State<'_, AppState>,
+    token: String,
     iade: CreateReturn,
 ) -> Result<Return, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.yoxla(&token)?;
 
-    // Generate return number
-    let iade_nomresi = format!("I-{}", Uuid::new_v4().to_string()[..8].to_uppercase());
+    if iade.items.is_empty() {
+        return Err("Geri qaytarılacaq məhsul seçilməyib".to_string());
+    }
+    if iade.items.iter().any(|item| item.miqdar <= 0) {
+        return Err("Geri qaytarma miqdarı müsbət olmalıdır".to_string());
+    }
 
-    // Get sale info
-    let (musteri_id, satis_nomresi): (Option<i64>, String) = db
-        .conn
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+
+    // Everything below either commits together or not at all
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    // Next return number; handed back if the transaction rolls back
+    let iade_nomresi = numbering::novbeti_nomre(&tx, Sened::Iade)?;
+
+    // Get sale info, with the Nisyə part of its payment and what earlier returns already refunded
+    let (musteri_id, nisye_hisse, onceki_iadeler): (Option<i64>, f64, f64) = tx
         .query_row(
-            "SELECT musteri_id, satis_nomresi FROM sales WHERE id = ?1",
+            "SELECT s.musteri_id,
+                    COALESCE((SELECT SUM(mebleg) FROM sale_payments
+                              WHERE satis_id = s.id AND odenis_usulu = 'Nisyə'), 0),
+                    COALESCE((SELECT SUM(toplam_mebleg) FROM returns WHERE satis_id = s.id), 0)
+             FROM sales s WHERE s.id = ?1",
             [iade.satis_id],
-            |row| Ok((row.get(0)?, row.get(1)?)),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
         .map_err(|e| format!("Satış tapılmadı: {}", e))?;
 
-    // Check if any item has already been returned
+    // Each return price must match what the customer actually paid, which
+    // after a promotion is the line total shared over its units. The matched
+    // sale line also gives the cost the units go back into stock at.
+    let mut vahid_mayalar = Vec::with_capacity(iade.items.len());
     for item in &iade.items {
-        let already_returned: Result<i32, _> = db
-            .conn
+        let satis_setri: Option<(f64, f64)> = tx
             .query_row(
-                "SELECT COUNT(*) FROM return_items ri
-                 JOIN returns r ON ri.iade_id = r.id
-                 WHERE r.satis_id = ?1 AND ri.mehsul_id = ?2 AND ri.olcu_id = ?3",
-                [iade.satis_id, item.mehsul_id, item.olcu_id],
-                |row| row.get(0),
-            );
-
-        if let Ok(count) = already_returned {
-            if count > 0 {
-                // Get product name for error message
-                let mehsul_adi: String = db
-                    .conn
-                    .query_row(
-                        "SELECT p.ad || ' (' || sz.olcu || ')' FROM products p
-                         JOIN sizes sz ON sz.id = ?2
-                         WHERE p.id = ?1",
-                        [item.mehsul_id, item.olcu_id],
-                        |row| row.get(0),
-                    )
-                    .unwrap_or_else(|_| "Məhsul".to_string());
-
-                return Err(format!("'{}' məhsulu artıq bu satış üçün geri qaytarılıb", mehsul_adi));
+                "SELECT si.toplam_qiymet / si.miqdar, COALESCE(si.vahid_maya_deyeri, p.alis_qiymeti)
+                 FROM sale_items si
+                 JOIN products p ON si.mehsul_id = p.id
+                 WHERE si.satis_id = ?1 AND si.mehsul_id = ?2 AND si.olcu_id = ?3 AND si.reng_id IS ?5
+                 ORDER BY ABS(si.toplam_qiymet / si.miqdar - ?4)
+                 LIMIT 1",
+                rusqlite::params![iade.satis_id, item.mehsul_id, item.olcu_id, item.vahid_qiymeti, item.reng_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        match satis_setri {
+            None => {
+                return Err(format!(
+                    "'{}' məhsulu bu satışda yoxdur",
+                    mehsul_etiketi(&tx, item.mehsul_id, item.olcu_id, item.reng_id)
+                ));
             }
+            Some((qiymet, _)) if (qiymet - item.vahid_qiymeti).abs() > 0.005 => {
+                return Err(format!(
+                    "'{}' üçün qaytarma qiyməti ({:.2}) satış qiymətindən ({:.2}) fərqlənir",
+                    mehsul_etiketi(&tx, item.mehsul_id, item.olcu_id, item.reng_id),
+                    item.vahid_qiymeti,
+                    qiymet
+                ));
+            }
+            Some((_, vahid_maya)) => vahid_mayalar.push(vahid_maya),
+        }
+    }
+
+    // Total requested quantity per product/size/colour (the same line may appear twice)
+    let mut teleb: Vec<(StokAcari, i32)> = Vec::new();
+    for item in &iade.items {
+        let acar = (item.mehsul_id, item.olcu_id, item.reng_id);
+        match teleb.iter_mut().find(|(key, _)| *key == acar) {
+            Some((_, miqdar)) => *miqdar += item.miqdar,
+            None => teleb.push((acar, item.miqdar)),
+        }
+    }
+
+    // Cannot return more than was sold minus earlier returns
+    let mut artiq_qaytarma = Vec::new();
+    for ((mehsul_id, olcu_id, reng_id), miqdar) in &teleb {
+        let (satilan, qaytarilan): (i32, i32) = tx
+            .query_row(
+                "SELECT
+                    COALESCE((SELECT SUM(miqdar) FROM sale_items
+                              WHERE satis_id = ?1 AND mehsul_id = ?2 AND olcu_id = ?3
+                                AND reng_id IS ?4), 0),
+                    COALESCE((SELECT SUM(ri.miqdar) FROM return_items ri
+                              JOIN returns r ON ri.iade_id = r.id
+                              WHERE r.satis_id = ?1 AND ri.mehsul_id = ?2 AND ri.olcu_id = ?3
+                                AND ri.reng_id IS ?4), 0)",
+                rusqlite::params![iade.satis_id, mehsul_id, olcu_id, reng_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| e.to_string())?;
+
+        let qalan = satilan - qaytarilan;
+        if *miqdar > qalan {
+            artiq_qaytarma.push(format!(
+                "{}: satılıb {}, artıq qaytarılıb {}, qaytarıla bilər {}, tələb olunan {}",
+                mehsul_etiketi(&tx, *mehsul_id, *olcu_id, *reng_id),
+                satilan,
+                qaytarilan,
+                qalan.max(0),
+                miqdar
+            ));
         }
     }
 
+    if !artiq_qaytarma.is_empty() {
+        return Err(format!(
+            "Satılandan artıq məhsul qaytarıla bilməz:\n{}",
+            artiq_qaytarma.join("\n")
+        ));
+    }
+
     // Calculate total
     let toplam_mebleg: f64 = iade
         .items
@@ -113,110 +205,114 @@ pub async fn iade_yarat(
         .map(|item| item.vahid_qiymeti * item.miqdar as f64)
         .sum();
 
-    // Insert return
-    db.conn
-        .execute(
-            "INSERT INTO returns (iade_nomresi, satis_id, musteri_id, toplam_mebleg, sebebi, qeyd)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            rusqlite::params![
-                iade_nomresi,
-                iade.satis_id,
-                musteri_id,
-                toplam_mebleg,
-                iade.sebebi,
-                iade.qeyd,
-            ],
-        )
-        .map_err(|e| format!("Geri Qaytarma yaradıla bilmədi: {}", e))?;
+    let novbe_id = aktiv_novbe_id(&tx, session.istifadeci_id)?;
 
-    let iade_id = db.conn.last_insert_rowid();
+    // Insert return
+    tx.execute(
+        "INSERT INTO returns (iade_nomresi, satis_id, musteri_id, toplam_mebleg, sebebi, qeyd, novbe_id, istifadeci_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            iade_nomresi,
+            iade.satis_id,
+            musteri_id,
+            toplam_mebleg,
+            iade.sebebi,
+            iade.qeyd,
+            novbe_id,
+            session.istifadeci_id,
+        ],
+    )
+    .map_err(|e| format!("Geri Qaytarma yaradıla bilmədi: {}", e))?;
+
+    let iade_id = tx.last_insert_rowid();
 
     // Insert return items and update stock
-    for item in &iade.items {
+    for (item, &vahid_maya) in iade.items.iter().zip(&vahid_mayalar) {
         let toplam_qiymet = item.vahid_qiymeti * item.miqdar as f64;
 
         // Insert return item
-        db.conn
-            .execute(
-                "INSERT INTO return_items (iade_id, mehsul_id, olcu_id, miqdar, vahid_qiymeti, toplam_qiymet)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                rusqlite::params![
-                    iade_id,
-                    item.mehsul_id,
-                    item.olcu_id,
-                    item.miqdar,
-                    item.vahid_qiymeti,
-                    toplam_qiymet,
-                ],
-            )
-            .map_err(|e| format!("Geri Qaytarma elementi əlavə edilə bilmədi: {}", e))?;
+        tx.execute(
+            "INSERT INTO return_items (iade_id, mehsul_id, olcu_id, reng_id, miqdar, vahid_qiymeti, toplam_qiymet, vahid_maya_deyeri)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                iade_id,
+                item.mehsul_id,
+                item.olcu_id,
+                item.reng_id,
+                item.miqdar,
+                item.vahid_qiymeti,
+                toplam_qiymet,
+                vahid_maya,
+            ],
+        )
+        .map_err(|e| format!("Geri Qaytarma elementi əlavə edilə bilmədi: {}", e))?;
 
         // Update stock (add back)
-        let evvelki: i32 = db
-            .conn
+        let evvelki: i32 = tx
             .query_row(
-                "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2",
-                [item.mehsul_id, item.olcu_id],
+                "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3",
+                rusqlite::params![item.mehsul_id, item.olcu_id, item.reng_id],
                 |row| row.get(0),
             )
+            .optional()
+            .map_err(|e| e.to_string())?
             .unwrap_or(0);
 
         let yeni = evvelki + item.miqdar;
 
-        db.conn
-            .execute(
-                "UPDATE stock SET miqdar = ?1, updated_at = CURRENT_TIMESTAMP
-                 WHERE mehsul_id = ?2 AND olcu_id = ?3",
-                rusqlite::params![yeni, item.mehsul_id, item.olcu_id],
-            )
-            .ok();
+        tx.execute(
+            "INSERT INTO stock (mehsul_id, olcu_id, reng_id, miqdar) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(mehsul_id, olcu_id, IFNULL(reng_id, 0)) DO UPDATE SET
+             miqdar = excluded.miqdar,
+             updated_at = CURRENT_TIMESTAMP",
+            rusqlite::params![item.mehsul_id, item.olcu_id, item.reng_id, yeni],
+        )
+        .map_err(|e| format!("Stok yenilənə bilmədi: {}", e))?;
 
         // Log stock movement
-        db.conn
-            .execute(
-                "INSERT INTO stock_movements (mehsul_id, olcu_id, novu, miqdar, evvelki_miqdar, yeni_miqdar, qeyd)
-                 VALUES (?1, ?2, 'Daxil olma', ?3, ?4, ?5, ?6)",
-                rusqlite::params![
-                    item.mehsul_id,
-                    item.olcu_id,
-                    item.miqdar,
-                    evvelki,
-                    yeni,
-                    format!("Geri Qaytarma: {}", iade_nomresi),
-                ],
-            )
-            .ok();
+        tx.execute(
+            "INSERT INTO stock_movements (mehsul_id, olcu_id, reng_id, novu, miqdar, evvelki_miqdar, yeni_miqdar, qeyd)
+             VALUES (?1, ?2, ?3, 'Daxil olma', ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                item.mehsul_id,
+                item.olcu_id,
+                item.reng_id,
+                item.miqdar,
+                evvelki,
+                yeni,
+                format!("Geri Qaytarma: {}", iade_nomresi),
+            ],
+        )
+        .map_err(|e| format!("Stok hərəkəti yazıla bilmədi: {}", e))?;
+
+        let hereket_id = tx.last_insert_rowid();
+        cost::lay_elave_et(&tx, item.mehsul_id, item.olcu_id, item.reng_id, item.miqdar, vahid_maya, Some(hereket_id))?;
     }
 
-    // If customer has debt (Nisyə), reduce the debt
+    // Returns write off the sale's Nisyə part first; anything beyond it was paid and is refunded
+    let borcdan_silinen = toplam_mebleg.min((nisye_hisse - onceki_iadeler).max(0.0));
     if let Some(cust_id) = musteri_id {
-        // Check if original sale was Nisyə
-        let odenis_usulu: String = db
-            .conn
-            .query_row(
-                "SELECT odenis_usulu FROM sales WHERE id = ?1",
-                [iade.satis_id],
-                |row| row.get(0),
+        if borcdan_silinen > 0.0 {
+            tx.execute(
+                "INSERT INTO debt_payments (musteri_id, mebleg, odenis_usulu, qeyd, novbe_id, istifadeci_id)
+                 VALUES (?1, ?2, 'Geri Qaytarma', ?3, ?4, ?5)",
+                rusqlite::params![
+                    cust_id,
+                    borcdan_silinen,
+                    format!("Geri Qaytarma: {}", iade_nomresi),
+                    novbe_id,
+                    session.istifadeci_id,
+                ],
             )
-            .unwrap_or_default();
-
-        if odenis_usulu == "Nisyə" {
-            // Add a payment to reduce debt
-            db.conn
-                .execute(
-                    "INSERT INTO debt_payments (musteri_id, mebleg, odenis_usulu, qeyd)
-                     VALUES (?1, ?2, 'Geri Qaytarma', ?3)",
-                    rusqlite::params![cust_id, toplam_mebleg, format!("Geri Qaytarma: {}", iade_nomresi)],
-                )
-                .ok();
+            .map_err(|e| format!("Borc yenilənə bilmədi: {}", e))?;
         }
     }
 
     // Get the created return
-    let result = db
-        .conn
+    let result = tx
         .query_row(
-            "SELECT r.*, s.satis_nomresi, c.ad || ' ' || c.soyad as musteri_adi
+            "SELECT r.id, r.iade_nomresi, r.satis_id, r.musteri_id, r.toplam_mebleg, r.sebebi, r.qeyd, r.created_at,
+                    s.satis_nomresi, c.ad || ' ' || c.soyad as musteri_adi
              FROM returns r
              JOIN sales s ON r.satis_id = s.id
              LEFT JOIN customers c ON r.musteri_id = c.id
@@ -239,17 +335,36 @@ pub async fn iade_yarat(
         )
         .map_err(|e| format!("Geri Qaytarma tapılmadı: {}", e))?;
 
+    let sonra = serde_json::json!({
+        "iade": audit::setir(&tx, "returns", iade_id)?,
+        "mehsullar": audit::setirler_json(&tx, "SELECT * FROM return_items WHERE iade_id = ?1", [iade_id])?,
+    });
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "iade_yarat",
+        "returns",
+        Some(iade_id),
+        None,
+        Some(sonra),
+    )?;
+
+    tx.commit().map_err(|e| format!("Geri Qaytarma yadda saxlanıla bilmədi: {}", e))?;
+
     Ok(result)
 }
 
 #[tauri::command]
-pub async fn iade_siyahisi(state: State<'_, AppState>) -> Result<Vec<Return>, String> {
+pub async fn iade_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Return>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = db
         .conn
         .prepare(
-            "SELECT r.*, s.satis_nomresi, c.ad || ' ' || c.soyad as musteri_adi
+            "SELECT r.id, r.iade_nomresi, r.satis_id, r.musteri_id, r.toplam_mebleg, r.sebebi, r.qeyd, r.created_at,
+                    s.satis_nomresi, c.ad || ' ' || c.soyad as musteri_adi
              FROM returns r
              JOIN sales s ON r.satis_id = s.id
              LEFT JOIN customers c ON r.musteri_id = c.id
@@ -282,15 +397,19 @@ pub async fn iade_siyahisi(state: State<'_, AppState>) -> Result<Vec<Return>, St
 #[tauri::command(rename_all = "camelCase")]
 pub async fn iade_detallari(
     state: State<'_, AppState>,
+    token: String,
     iade_id: i64,
 ) -> Result<ReturnWithItems, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     // Get return
     let iade = db
         .conn
         .query_row(
-            "SELECT r.*, s.satis_nomresi, c.ad || ' ' || c.soyad as musteri_adi
+            "SELECT r.id, r.iade_nomresi, r.satis_id, r.musteri_id, r.toplam_mebleg, r.sebebi, r.qeyd, r.created_at,
+                    s.satis_nomresi, c.ad || ' ' || c.soyad as musteri_adi
              FROM returns r
              JOIN sales s ON r.satis_id = s.id
              LEFT JOIN customers c ON r.musteri_id = c.id
@@ -317,10 +436,13 @@ pub async fn iade_detallari(
     let mut stmt = db
         .conn
         .prepare(
-            "SELECT ri.*, p.ad as mehsul_adi, p.barkod as mehsul_barkod, sz.olcu
+            "SELECT ri.id, ri.iade_id, ri.mehsul_id, ri.olcu_id, ri.miqdar, ri.vahid_qiymeti,
+                    ri.toplam_qiymet, ri.created_at, p.ad as mehsul_adi, p.barkod as mehsul_barkod, sz.olcu,
+                    ri.reng_id, cl.ad
              FROM return_items ri
              JOIN products p ON ri.mehsul_id = p.id
              JOIN sizes sz ON ri.olcu_id = sz.id
+             LEFT JOIN colors cl ON ri.reng_id = cl.id
              WHERE ri.iade_id = ?1",
         )
         .map_err(|e| e.to_string())?;
@@ -339,6 +461,8 @@ pub async fn iade_detallari(
                 mehsul_adi: row.get(8)?,
                 mehsul_barkod: row.get(9)?,
                 olcu: row.get(10)?,
+                reng_id: row.get(11)?,
+                reng: row.get(12)?,
             })
         })
         .map_err(|e| e.to_string())?
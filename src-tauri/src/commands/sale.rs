@@ -1,102 +1,300 @@
-use crate::models::{CreateSale, Sale, SaleItem, SaleWithItems};
+use crate::audit;
+use crate::commands::shift::aktiv_novbe_id;
+use crate::commands::variant;
+use crate::inventory::cost::{self, MayaUsulu};
+use crate::inventory::StokAcari;
+use crate::models::{CreateSale, CreateSaleItem, CreateSalePayment, Sale, SaleItem, SalePayment, SaleWithItems};
+use crate::numbering::{self, Sened};
+use crate::promotions;
 use crate::AppState;
+use rusqlite::{Connection, OptionalExtension};
 use tauri::State;
-use uuid::Uuid;
+
+/// Tenders the till and the supplier accounts know about.
+pub(crate) const ODENIS_USULLARI: [&str; 3] = ["Nağd", "Kart", "Nisyə"];
+
+pub(crate) fn odenis_usulu_yoxla(odenis_usulu: &str) -> Result<(), String> {
+    if ODENIS_USULLARI.contains(&odenis_usulu) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Naməlum ödəniş üsulu: '{}' ({} olmalıdır)",
+            odenis_usulu,
+            ODENIS_USULLARI.join(", ")
+        ))
+    }
+}
+
+// Tenders for a sale: the explicit split, or the single method for the whole total.
+// Same-method tenders are merged and the amounts must add up to son_mebleg.
+fn odenisleri_hazirla(satis: &CreateSale, son_mebleg: f64) -> Result<Vec<CreateSalePayment>, String> {
+    let odenisler = if satis.odenisler.is_empty() {
+        let odenis_usulu = satis
+            .odenis_usulu
+            .clone()
+            .filter(|u| !u.trim().is_empty())
+            .ok_or_else(|| "Ödəniş üsulu seçilməyib".to_string())?;
+        vec![CreateSalePayment { odenis_usulu, mebleg: son_mebleg }]
+    } else {
+        if satis.odenisler.iter().any(|o| o.mebleg <= 0.0) {
+            return Err("Ödəniş məbləği müsbət olmalıdır".to_string());
+        }
+        if satis.odenisler.iter().any(|o| o.odenis_usulu.trim().is_empty()) {
+            return Err("Ödəniş üsulu seçilməyib".to_string());
+        }
+
+        let mut birlesmis: Vec<CreateSalePayment> = Vec::new();
+        for odenis in &satis.odenisler {
+            match birlesmis.iter_mut().find(|o| o.odenis_usulu == odenis.odenis_usulu) {
+                Some(o) => o.mebleg += odenis.mebleg,
+                None => birlesmis.push(odenis.clone()),
+            }
+        }
+        birlesmis
+    };
+
+    for odenis in &odenisler {
+        odenis_usulu_yoxla(&odenis.odenis_usulu)?;
+    }
+
+    let cem: f64 = odenisler.iter().map(|o| o.mebleg).sum();
+    if (cem - son_mebleg).abs() > 0.005 {
+        return Err(format!(
+            "Ödənişlərin cəmi ({:.2}) satış məbləğinə ({:.2}) bərabər olmalıdır",
+            cem, son_mebleg
+        ));
+    }
+
+    if odenisler.iter().any(|o| o.odenis_usulu == "Nisyə") && satis.musteri_id.is_none() {
+        return Err("Nisyə satış üçün müştəri seçməlisiniz".to_string());
+    }
+
+    Ok(odenisler)
+}
+
+// Lines given by variant barcode take the variant's product and size
+pub(crate) fn variantlari_hell_et(conn: &Connection, items: &mut [CreateSaleItem]) -> Result<(), String> {
+    for item in items {
+        match item.variant_barkod.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+            Some(barkod) => {
+                let v = variant::barkodla_tap(conn, barkod)?
+                    .ok_or_else(|| format!("Bu barkodla variant tapılmadı: {}", barkod))?;
+                item.mehsul_id = v.mehsul_id;
+                item.olcu_id = v.olcu_id;
+                item.reng_id = v.reng_id;
+            }
+            None if item.mehsul_id == 0 || item.olcu_id == 0 => {
+                return Err("Satış sətrində məhsul və ölçü və ya variant barkodu göstərilməlidir".to_string());
+            }
+            None => {}
+        }
+    }
+
+    Ok(())
+}
 
 #[tauri::command]
 pub async fn satis_yarat(
     state: State<'_, AppState>,
-    satis: CreateSale,
+    token: String,
+    mut satis: CreateSale,
 ) -> Result<Sale, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    
-    // Generate sale number
-    let satis_nomresi = format!("S-{}", Uuid::new_v4().to_string()[..8].to_uppercase());
-    
+    let session = state.sessions.yoxla(&token)?;
+
+    if satis.items.is_empty() {
+        return Err("Satışda heç bir məhsul yoxdur".to_string());
+    }
+    if satis.items.iter().any(|item| item.miqdar <= 0) {
+        return Err("Məhsul miqdarı müsbət olmalıdır".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let menfi_stok_icazesi: bool = db.conn
+        .query_row(
+            "SELECT COALESCE(menfi_stok_icazesi, 0) FROM settings WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false);
+    let maya_usulu = MayaUsulu::oxu(&db.conn)?;
+
+    // Everything below either commits together or not at all
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    variantlari_hell_et(&tx, &mut satis.items)?;
+
+    // Running promotions come off the lines they cover before the manual discount
+    let kampaniyalar = promotions::hesabla(&tx, &satis.items)?;
+
     // Calculate totals
     let toplam_mebleg: f64 = satis.items.iter()
         .map(|item| item.vahid_qiymeti * item.miqdar as f64)
-        .sum();
+        .sum::<f64>() - kampaniyalar.endirim;
     
+    if satis.endirim < 0.0 {
+        return Err("Endirim mənfi ola bilməz".to_string());
+    }
+    if satis.endirim > toplam_mebleg + 0.005 {
+        return Err(format!(
+            "Endirim ({:.2}) satış məbləğindən ({:.2}) çox ola bilməz",
+            satis.endirim, toplam_mebleg
+        ));
+    }
+
     let son_mebleg = toplam_mebleg - satis.endirim;
+
+    let odenisler = odenisleri_hazirla(&satis, son_mebleg)?;
+    // The sale row keeps a single label; split payments show as 'Qarışıq'
+    let odenis_usulu = match odenisler.as_slice() {
+        [tek] => tek.odenis_usulu.clone(),
+        _ => "Qarışıq".to_string(),
+    };
+
+    // Total requested quantity per product/size/colour (the same line may appear twice)
+    let mut teleb: Vec<(StokAcari, i32)> = Vec::new();
+    for item in &satis.items {
+        let acar = (item.mehsul_id, item.olcu_id, item.reng_id);
+        match teleb.iter_mut().find(|(key, _)| *key == acar) {
+            Some((_, miqdar)) => *miqdar += item.miqdar,
+            None => teleb.push((acar, item.miqdar)),
+        }
+    }
+
+    // Check stock before touching anything
+    let mut catismazliqlar = Vec::new();
+    for ((mehsul_id, olcu_id, reng_id), miqdar) in &teleb {
+        let (barkod, olcu, movcud): (String, String, i32) = tx
+            .query_row(
+                "SELECT p.barkod, sz.olcu || COALESCE(', ' || cl.ad, ''), COALESCE(s.miqdar, 0)
+                 FROM products p
+                 JOIN sizes sz ON sz.id = ?2
+                 LEFT JOIN colors cl ON cl.id = ?3
+                 LEFT JOIN stock s ON s.mehsul_id = p.id AND s.olcu_id = sz.id AND s.reng_id IS ?3
+                 WHERE p.id = ?1",
+                rusqlite::params![mehsul_id, olcu_id, reng_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|_| format!("Məhsul və ya ölçü tapılmadı (məhsul: {}, ölçü: {})", mehsul_id, olcu_id))?;
+
+        if movcud < *miqdar {
+            catismazliqlar.push(format!(
+                "{} ({}): mövcud {}, tələb olunan {}",
+                barkod, olcu, movcud, miqdar
+            ));
+        }
+    }
+
+    if !catismazliqlar.is_empty() && !menfi_stok_icazesi {
+        return Err(format!(
+            "Stokda kifayət qədər məhsul yoxdur:\n{}",
+            catismazliqlar.join("\n")
+        ));
+    }
+
+    // Next sale number; handed back if the transaction rolls back
+    let satis_nomresi = numbering::novbeti_nomre(&tx, Sened::Satis)?;
+
+    // The cashier's open shift, if one is running
+    let novbe_id = aktiv_novbe_id(&tx, session.istifadeci_id)?;
     
     // Insert sale with customer_id
-    db.conn
-        .execute(
-            "INSERT INTO sales (satis_nomresi, musteri_id, toplam_mebleg, endirim, son_mebleg, odenis_usulu, qeyd)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            rusqlite::params![
-                satis_nomresi,
-                satis.musteri_id,
-                toplam_mebleg,
-                satis.endirim,
-                son_mebleg,
-                satis.odenis_usulu,
-                satis.qeyd,
-            ],
-        )
-        .map_err(|e| format!("Satış yaradıla bilmədi: {}", e))?;
+    tx.execute(
+        "INSERT INTO sales (satis_nomresi, musteri_id, toplam_mebleg, endirim, son_mebleg, odenis_usulu, qeyd, novbe_id, istifadeci_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        rusqlite::params![
+            satis_nomresi,
+            satis.musteri_id,
+            toplam_mebleg,
+            satis.endirim,
+            son_mebleg,
+            odenis_usulu,
+            satis.qeyd,
+            novbe_id,
+            session.istifadeci_id,
+        ],
+    )
+    .map_err(|e| format!("Satış yaradıla bilmədi: {}", e))?;
     
-    let satis_id = db.conn.last_insert_rowid();
+    let satis_id = tx.last_insert_rowid();
+
+    for odenis in &odenisler {
+        tx.execute(
+            "INSERT INTO sale_payments (satis_id, odenis_usulu, mebleg) VALUES (?1, ?2, ?3)",
+            rusqlite::params![satis_id, odenis.odenis_usulu, odenis.mebleg],
+        )
+        .map_err(|e| format!("Ödəniş yazıla bilmədi: {}", e))?;
+    }
     
     // Insert sale items and update stock
-    for item in &satis.items {
-        let toplam_qiymet = item.vahid_qiymeti * item.miqdar as f64;
+    for (item, kampaniya) in satis.items.iter().zip(&kampaniyalar.setirler) {
+        let toplam_qiymet = item.vahid_qiymeti * item.miqdar as f64 - kampaniya.endirim;
+
+        // Cost of the units leaving stock, fixed at the time of sale
+        let vahid_maya = cost::istehlak_et(&tx, item.mehsul_id, item.olcu_id, item.reng_id, item.miqdar, maya_usulu)?;
         
         // Insert sale item
-        db.conn
-            .execute(
-                "INSERT INTO sale_items (satis_id, mehsul_id, olcu_id, miqdar, vahid_qiymeti, toplam_qiymet)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                rusqlite::params![
-                    satis_id,
-                    item.mehsul_id,
-                    item.olcu_id,
-                    item.miqdar,
-                    item.vahid_qiymeti,
-                    toplam_qiymet,
-                ],
-            )
-            .map_err(|e| format!("Satış elementi əlavə edilə bilmədi: {}", e))?;
+        tx.execute(
+            "INSERT INTO sale_items (satis_id, mehsul_id, olcu_id, reng_id, miqdar, vahid_qiymeti, toplam_qiymet, vahid_maya_deyeri, endirim, kampaniya_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                satis_id,
+                item.mehsul_id,
+                item.olcu_id,
+                item.reng_id,
+                item.miqdar,
+                item.vahid_qiymeti,
+                toplam_qiymet,
+                vahid_maya,
+                kampaniya.endirim,
+                kampaniya.kampaniya_id,
+            ],
+        )
+        .map_err(|e| format!("Satış elementi əlavə edilə bilmədi: {}", e))?;
         
         // Update stock
-        let evvelki: i32 = db.conn
+        let evvelki: i32 = tx
             .query_row(
-                "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2",
-                [item.mehsul_id, item.olcu_id],
+                "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3",
+                rusqlite::params![item.mehsul_id, item.olcu_id, item.reng_id],
                 |row| row.get(0),
             )
+            .optional()
+            .map_err(|e| e.to_string())?
             .unwrap_or(0);
         
         let yeni = evvelki - item.miqdar;
         
-        db.conn
-            .execute(
-                "UPDATE stock SET miqdar = ?1, updated_at = CURRENT_TIMESTAMP
-                 WHERE mehsul_id = ?2 AND olcu_id = ?3",
-                rusqlite::params![yeni, item.mehsul_id, item.olcu_id],
-            )
-            .ok();
+        tx.execute(
+            "INSERT INTO stock (mehsul_id, olcu_id, reng_id, miqdar) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(mehsul_id, olcu_id, IFNULL(reng_id, 0)) DO UPDATE SET
+             miqdar = excluded.miqdar,
+             updated_at = CURRENT_TIMESTAMP",
+            rusqlite::params![item.mehsul_id, item.olcu_id, item.reng_id, yeni],
+        )
+        .map_err(|e| format!("Stok yenilənə bilmədi: {}", e))?;
         
         // Log stock movement
-        db.conn
-            .execute(
-                "INSERT INTO stock_movements (mehsul_id, olcu_id, novu, miqdar, evvelki_miqdar, yeni_miqdar, qeyd)
-                 VALUES (?1, ?2, 'Çıxış', ?3, ?4, ?5, ?6)",
-                rusqlite::params![
-                    item.mehsul_id,
-                    item.olcu_id,
-                    item.miqdar,
-                    evvelki,
-                    yeni,
-                    format!("Satış: {}", satis_nomresi),
-                ],
-            )
-            .ok();
+        tx.execute(
+            "INSERT INTO stock_movements (mehsul_id, olcu_id, reng_id, novu, miqdar, evvelki_miqdar, yeni_miqdar, qeyd)
+             VALUES (?1, ?2, ?3, 'Çıxış', ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                item.mehsul_id,
+                item.olcu_id,
+                item.reng_id,
+                item.miqdar,
+                evvelki,
+                yeni,
+                format!("Satış: {}", satis_nomresi),
+            ],
+        )
+        .map_err(|e| format!("Stok hərəkəti yazıla bilmədi: {}", e))?;
     }
     
     // Get the created sale with customer name
-    let sale = db.conn
+    let sale = tx
         .query_row(
             "SELECT s.id, s.satis_nomresi, s.musteri_id, 
                     CASE WHEN c.id IS NOT NULL THEN c.ad || ' ' || c.soyad ELSE NULL END as musteri_adi,
@@ -121,12 +319,31 @@ pub async fn satis_yarat(
             },
         )
         .map_err(|e| format!("Satış tapılmadı: {}", e))?;
+
+    let sonra = serde_json::json!({
+        "satis": audit::setir(&tx, "sales", satis_id)?,
+        "mehsullar": audit::setirler_json(&tx, "SELECT * FROM sale_items WHERE satis_id = ?1", [satis_id])?,
+        "odenisler": audit::setirler_json(&tx, "SELECT * FROM sale_payments WHERE satis_id = ?1", [satis_id])?,
+    });
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "satis_yarat",
+        "sales",
+        Some(satis_id),
+        None,
+        Some(sonra),
+    )?;
+
+    tx.commit().map_err(|e| format!("Satış yadda saxlanıla bilmədi: {}", e))?;
     
     Ok(sale)
 }
 
 #[tauri::command]
-pub async fn satis_siyahisi(state: State<'_, AppState>) -> Result<Vec<Sale>, String> {
+pub async fn satis_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Sale>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
     let mut stmt = db.conn
@@ -165,8 +382,11 @@ pub async fn satis_siyahisi(state: State<'_, AppState>) -> Result<Vec<Sale>, Str
 #[tauri::command(rename_all = "camelCase")]
 pub async fn satis_detallari(
     state: State<'_, AppState>,
+    token: String,
     satis_id: i64,
 ) -> Result<SaleWithItems, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
     // Get sale with customer info
@@ -199,15 +419,20 @@ pub async fn satis_detallari(
     // Get sale items with return info
     let mut stmt = db.conn
         .prepare(
-            "SELECT si.*, p.ad as mehsul_adi, p.barkod as mehsul_barkod, sz.olcu,
+            "SELECT si.id, si.satis_id, si.mehsul_id, si.olcu_id, si.miqdar, si.vahid_qiymeti,
+                    si.toplam_qiymet, si.created_at, p.ad as mehsul_adi, p.barkod as mehsul_barkod, sz.olcu,
                     COALESCE((SELECT SUM(ri.miqdar) FROM return_items ri
                               JOIN returns r ON ri.iade_id = r.id
                               WHERE r.satis_id = si.satis_id
                                 AND ri.mehsul_id = si.mehsul_id
-                                AND ri.olcu_id = si.olcu_id), 0) as iade_miqdar
+                                AND ri.olcu_id = si.olcu_id
+                                AND ri.reng_id IS si.reng_id), 0) as iade_miqdar,
+                    si.reng_id, cl.ad, si.endirim, si.kampaniya_id, pr.ad
              FROM sale_items si
              JOIN products p ON si.mehsul_id = p.id
              JOIN sizes sz ON si.olcu_id = sz.id
+             LEFT JOIN colors cl ON si.reng_id = cl.id
+             LEFT JOIN promotions pr ON si.kampaniya_id = pr.id
              WHERE si.satis_id = ?1",
         )
         .map_err(|e| e.to_string())?;
@@ -227,11 +452,37 @@ pub async fn satis_detallari(
                 mehsul_barkod: row.get(9)?,
                 olcu: row.get(10)?,
                 iade_miqdar: row.get(11)?,
+                reng_id: row.get(12)?,
+                reng: row.get(13)?,
+                endirim: row.get(14)?,
+                kampaniya_id: row.get(15)?,
+                kampaniya_adi: row.get(16)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = db.conn
+        .prepare(
+            "SELECT id, satis_id, odenis_usulu, mebleg FROM sale_payments
+             WHERE satis_id = ?1
+             ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let odenisler = stmt
+        .query_map([satis_id], |row| {
+            Ok(SalePayment {
+                id: row.get(0)?,
+                satis_id: row.get(1)?,
+                odenis_usulu: row.get(2)?,
+                mebleg: row.get(3)?,
             })
         })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
     
-    Ok(SaleWithItems { sale, items })
+    Ok(SaleWithItems { sale, items, odenisler })
 }
@@ -1,4 +1,10 @@
+use crate::audit;
+use crate::barcode;
+use crate::db::defaults;
+use crate::inventory::cost::MayaUsulu;
+use crate::numbering::{self, Sened};
 use crate::AppState;
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -15,6 +21,11 @@ pub struct Settings {
     pub olculer_aktiv: bool,
     pub qifil_sifresi: Option<String>,
     pub barkod_capinda_magaza_adi: bool,
+    pub menfi_stok_icazesi: bool,
+    pub maya_deyeri_usulu: String,
+    pub satis_nomre_formati: String,
+    pub iade_nomre_formati: String,
+    pub barkod_prefiksi: String,
     pub updated_at: Option<String>,
 }
 
@@ -30,46 +41,62 @@ pub struct UpdateSettings {
     pub olculer_aktiv: Option<bool>,
     pub qifil_sifresi: Option<String>,
     pub barkod_capinda_magaza_adi: Option<bool>,
+    pub menfi_stok_icazesi: Option<bool>,
+    pub maya_deyeri_usulu: Option<String>,
+    pub satis_nomre_formati: Option<String>,
+    pub iade_nomre_formati: Option<String>,
+    pub barkod_prefiksi: Option<String>,
+}
+
+pub fn parametrleri_oxu(conn: &Connection) -> Result<Settings, String> {
+    conn.query_row(
+        "SELECT id, magaza_adi, logo_yolu, telefon, adres, whatsapp, instagram, tiktok, olculer_aktiv, qifil_sifresi, barkod_capinda_magaza_adi, updated_at, menfi_stok_icazesi, COALESCE(maya_deyeri_usulu, 'FIFO'),
+                COALESCE(satis_nomre_formati, ?1), COALESCE(iade_nomre_formati, ?2),
+                COALESCE(barkod_prefiksi, ?3)
+         FROM settings WHERE id = 1",
+        [
+            Sened::Satis.standart_format(),
+            Sened::Iade.standart_format(),
+            barcode::STANDART_PREFIKS,
+        ],
+        |row| {
+            let olculer_aktiv_int: i64 = row.get(8)?;
+            let barkod_capinda_magaza_adi_int: i64 = row.get(10)?;
+            let menfi_stok_icazesi_int: i64 = row.get(12)?;
+            Ok(Settings {
+                id: row.get(0)?,
+                magaza_adi: row.get(1)?,
+                logo_yolu: row.get(2)?,
+                telefon: row.get(3)?,
+                adres: row.get(4)?,
+                whatsapp: row.get(5)?,
+                instagram: row.get(6)?,
+                tiktok: row.get(7)?,
+                olculer_aktiv: olculer_aktiv_int != 0,
+                qifil_sifresi: row.get(9)?,
+                barkod_capinda_magaza_adi: barkod_capinda_magaza_adi_int != 0,
+                menfi_stok_icazesi: menfi_stok_icazesi_int != 0,
+                maya_deyeri_usulu: row.get(13)?,
+                satis_nomre_formati: row.get(14)?,
+                iade_nomre_formati: row.get(15)?,
+                barkod_prefiksi: row.get(16)?,
+                updated_at: row.get(11)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Parametrlər tapılmadı: {}", e))
 }
 
 #[tauri::command]
-pub async fn parametrleri_al(state: State<'_, AppState>) -> Result<Settings, String> {
+pub async fn parametrleri_al(state: State<'_, AppState>, token: String) -> Result<Settings, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     // Əgər settings yoxdursa, default dəyərlərlə yarat
-    db.conn
-        .execute(
-            "INSERT OR IGNORE INTO settings (id, magaza_adi) VALUES (1, 'Mağaza')",
-            [],
-        )
-        .map_err(|e| format!("Default parametrlər yaradıla bilmədi: {}", e))?;
-
-    let settings = db
-        .conn
-        .query_row(
-            "SELECT id, magaza_adi, logo_yolu, telefon, adres, whatsapp, instagram, tiktok, olculer_aktiv, qifil_sifresi, barkod_capinda_magaza_adi, updated_at
-             FROM settings WHERE id = 1",
-            [],
-            |row| {
-                let olculer_aktiv_int: i64 = row.get(8)?;
-                let barkod_capinda_magaza_adi_int: i64 = row.get(10)?;
-                Ok(Settings {
-                    id: row.get(0)?,
-                    magaza_adi: row.get(1)?,
-                    logo_yolu: row.get(2)?,
-                    telefon: row.get(3)?,
-                    adres: row.get(4)?,
-                    whatsapp: row.get(5)?,
-                    instagram: row.get(6)?,
-                    tiktok: row.get(7)?,
-                    olculer_aktiv: olculer_aktiv_int != 0,
-                    qifil_sifresi: row.get(9)?,
-                    barkod_capinda_magaza_adi: barkod_capinda_magaza_adi_int != 0,
-                    updated_at: row.get(11)?,
-                })
-            },
-        )
-        .map_err(|e| format!("Parametrlər tapılmadı: {}", e))?;
+    defaults::parametrler(&db.conn).map_err(|e| format!("Default parametrlər yaradıla bilmədi: {}", e))?;
+
+    let settings = parametrleri_oxu(&db.conn)?;
 
     Ok(settings)
 }
@@ -77,14 +104,31 @@ pub async fn parametrleri_al(state: State<'_, AppState>) -> Result<Settings, Str
 #[tauri::command]
 pub async fn parametrleri_yenile(
     state: State<'_, AppState>,
+    token: String,
     settings: UpdateSettings,
 ) -> Result<Settings, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "settings", 1)?;
 
     let olculer_aktiv_int = settings.olculer_aktiv.map(|v| if v { 1 } else { 0 });
     let barkod_capinda_magaza_adi_int = settings.barkod_capinda_magaza_adi.map(|v| if v { 1 } else { 0 });
+    let menfi_stok_icazesi_int = settings.menfi_stok_icazesi.map(|v| if v { 1 } else { 0 });
+
+    if let Some(usul) = &settings.maya_deyeri_usulu {
+        usul.parse::<MayaUsulu>()?;
+    }
+    for format in [&settings.satis_nomre_formati, &settings.iade_nomre_formati].into_iter().flatten() {
+        numbering::format_yoxla(format.trim())?;
+    }
+    if let Some(prefiks) = &settings.barkod_prefiksi {
+        barcode::prefiks_yoxla(prefiks.trim())?;
+    }
     
-    db.conn
+    tx
         .execute(
             "UPDATE settings SET 
                 magaza_adi = COALESCE(?1, magaza_adi),
@@ -97,6 +141,11 @@ pub async fn parametrleri_yenile(
                 olculer_aktiv = COALESCE(?8, olculer_aktiv),
                 qifil_sifresi = ?9,
                 barkod_capinda_magaza_adi = COALESCE(?10, barkod_capinda_magaza_adi),
+                menfi_stok_icazesi = COALESCE(?11, menfi_stok_icazesi),
+                maya_deyeri_usulu = COALESCE(?12, maya_deyeri_usulu),
+                satis_nomre_formati = COALESCE(?13, satis_nomre_formati),
+                iade_nomre_formati = COALESCE(?14, iade_nomre_formati),
+                barkod_prefiksi = COALESCE(?15, barkod_prefiksi),
                 updated_at = CURRENT_TIMESTAMP
              WHERE id = 1",
             rusqlite::params![
@@ -110,37 +159,29 @@ pub async fn parametrleri_yenile(
                 olculer_aktiv_int,
                 settings.qifil_sifresi,
                 barkod_capinda_magaza_adi_int,
+                menfi_stok_icazesi_int,
+                settings.maya_deyeri_usulu,
+                settings.satis_nomre_formati.as_deref().map(str::trim),
+                settings.iade_nomre_formati.as_deref().map(str::trim),
+                settings.barkod_prefiksi.as_deref().map(str::trim),
             ],
         )
         .map_err(|e| format!("Parametrlər yenilənə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "parametrleri_yenile",
+        "settings",
+        Some(1),
+        evvel,
+        audit::setir(&tx, "settings", 1)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     // Return updated settings
-    let updated = db
-        .conn
-        .query_row(
-            "SELECT id, magaza_adi, logo_yolu, telefon, adres, whatsapp, instagram, tiktok, olculer_aktiv, qifil_sifresi, barkod_capinda_magaza_adi, updated_at 
-             FROM settings WHERE id = 1",
-            [],
-            |row| {
-                let olculer_aktiv_int: i64 = row.get(8)?;
-                let barkod_capinda_magaza_adi_int: i64 = row.get(10)?;
-                Ok(Settings {
-                    id: row.get(0)?,
-                    magaza_adi: row.get(1)?,
-                    logo_yolu: row.get(2)?,
-                    telefon: row.get(3)?,
-                    adres: row.get(4)?,
-                    whatsapp: row.get(5)?,
-                    instagram: row.get(6)?,
-                    tiktok: row.get(7)?,
-                    olculer_aktiv: olculer_aktiv_int != 0,
-                    qifil_sifresi: row.get(9)?,
-                    barkod_capinda_magaza_adi: barkod_capinda_magaza_adi_int != 0,
-                    updated_at: row.get(11)?,
-                })
-            },
-        )
-        .map_err(|e| format!("Parametrlər tapılmadı: {}", e))?;
+    let updated = parametrleri_oxu(&db.conn)?;
 
     Ok(updated)
 }
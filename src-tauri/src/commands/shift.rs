@@ -0,0 +1,400 @@
+use crate::audit;
+use crate::auth::session::{Session, ICAZE_XETASI};
+use crate::AppState;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+pub const STATUS_ACIQ: &str = "Açıq";
+pub const STATUS_BAGLI: &str = "Bağlı";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Shift {
+    pub id: i64,
+    pub istifadeci_id: i64,
+    pub istifadeci_adi: Option<String>,
+    pub acilis_meblegi: f64,
+    pub gozlenilen_nagd: Option<f64>,
+    pub sayilan_nagd: Option<f64>,
+    pub status: String,
+    pub qeyd: Option<String>,
+    pub acilma_tarixi: String,
+    pub baglanma_tarixi: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShiftPaymentTotal {
+    pub odenis_usulu: String,
+    pub satis_sayi: i32,
+    pub satis_meblegi: f64,
+    pub borc_odenisi: f64,
+    pub cem: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZReport {
+    pub novbe: Shift,
+    pub satis_sayi: i32,
+    pub toplam_satis: f64,
+    pub endirim: f64,
+    pub iade_sayi: i32,
+    pub toplam_iade: f64,
+    /// Part of the refunds paid back in cash (the rest went to the card or was written off Nisyə debt)
+    pub nagd_qaytarma: f64,
+    pub odenisler: Vec<ShiftPaymentTotal>,
+    pub gozlenilen_nagd: f64,
+    pub sayilan_nagd: Option<f64>,
+    pub ferq: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenShift {
+    pub acilis_meblegi: f64,
+    pub qeyd: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloseShift {
+    pub sayilan_nagd: f64,
+    pub qeyd: Option<String>,
+}
+
+const SHIFT_SELECT: &str = "SELECT sh.id, sh.istifadeci_id, u.ad || ' ' || u.soyad, sh.acilis_meblegi,
+            sh.gozlenilen_nagd, sh.sayilan_nagd, sh.status, sh.qeyd, sh.acilma_tarixi, sh.baglanma_tarixi
+     FROM shifts sh
+     LEFT JOIN users u ON sh.istifadeci_id = u.id";
+
+fn row_to_shift(row: &rusqlite::Row) -> rusqlite::Result<Shift> {
+    Ok(Shift {
+        id: row.get(0)?,
+        istifadeci_id: row.get(1)?,
+        istifadeci_adi: row.get(2)?,
+        acilis_meblegi: row.get(3)?,
+        gozlenilen_nagd: row.get(4)?,
+        sayilan_nagd: row.get(5)?,
+        status: row.get(6)?,
+        qeyd: row.get(7)?,
+        acilma_tarixi: row.get(8)?,
+        baglanma_tarixi: row.get(9)?,
+    })
+}
+
+fn novbe_oxu(conn: &Connection, novbe_id: i64) -> Result<Shift, String> {
+    conn.query_row(&format!("{} WHERE sh.id = ?1", SHIFT_SELECT), [novbe_id], row_to_shift)
+        .map_err(|e| format!("Növbə tapılmadı: {}", e))
+}
+
+/// The user's open shift, if any. Sales, returns and debt payments are attached to it.
+pub fn aktiv_novbe_id(conn: &Connection, istifadeci_id: i64) -> Result<Option<i64>, String> {
+    conn.query_row(
+        "SELECT id FROM shifts WHERE istifadeci_id = ?1 AND status = 'Açıq'",
+        [istifadeci_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+// Cashiers may only see their own shifts
+fn novbeye_icaze(session: &Session, novbe: &Shift) -> Result<(), String> {
+    if novbe.istifadeci_id != session.istifadeci_id && !session.admin_dir() {
+        return Err(ICAZE_XETASI.to_string());
+    }
+    Ok(())
+}
+
+fn z_hesabati_hazirla(conn: &Connection, novbe: Shift) -> Result<ZReport, String> {
+    let (satis_sayi, toplam_satis, endirim): (i32, f64, f64) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(son_mebleg), 0), COALESCE(SUM(endirim), 0)
+             FROM sales WHERE novbe_id = ?1",
+            [novbe.id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let (iade_sayi, toplam_iade): (i32, f64) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(toplam_mebleg), 0) FROM returns WHERE novbe_id = ?1",
+            [novbe.id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    // A return first writes off what is left of its sale's Nisyə part, as
+    // `iade_yarat` does; the rest is refunded in the tenders the sale was paid
+    // with, in proportion, so only the Nağd share leaves the drawer
+    let nagd_qaytarma: f64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(
+                        (r.toplam_mebleg - MIN(r.toplam_mebleg, MAX(p.nisye - COALESCE(
+                            (SELECT SUM(r2.toplam_mebleg) FROM returns r2
+                             WHERE r2.satis_id = r.satis_id AND r2.id < r.id), 0), 0)))
+                        * CASE WHEN p.odenen > 0 THEN p.nagd / p.odenen ELSE 0 END
+                    ), 0)
+             FROM returns r
+             JOIN (SELECT satis_id,
+                          SUM(CASE WHEN odenis_usulu = 'Nisyə' THEN mebleg ELSE 0 END) AS nisye,
+                          SUM(CASE WHEN odenis_usulu = 'Nağd' THEN mebleg ELSE 0 END) AS nagd,
+                          SUM(CASE WHEN odenis_usulu != 'Nisyə' THEN mebleg ELSE 0 END) AS odenen
+                   FROM sale_payments GROUP BY satis_id) p ON p.satis_id = r.satis_id
+             WHERE r.novbe_id = ?1",
+            [novbe.id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut odenisler: Vec<ShiftPaymentTotal> = Vec::new();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT sp.odenis_usulu, COUNT(DISTINCT s.id), SUM(sp.mebleg)
+             FROM sale_payments sp
+             JOIN sales s ON sp.satis_id = s.id
+             WHERE s.novbe_id = ?1
+             GROUP BY sp.odenis_usulu",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let satislar = stmt
+        .query_map([novbe.id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?, row.get::<_, f64>(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for (odenis_usulu, sayi, mebleg) in satislar {
+        odenisler.push(ShiftPaymentTotal {
+            odenis_usulu,
+            satis_sayi: sayi,
+            satis_meblegi: mebleg,
+            borc_odenisi: 0.0,
+            cem: 0.0,
+        });
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT odenis_usulu, SUM(mebleg) FROM debt_payments
+             WHERE novbe_id = ?1 AND odenis_usulu != 'Geri Qaytarma'
+             GROUP BY odenis_usulu",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let borc_odenisleri = stmt
+        .query_map([novbe.id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for (odenis_usulu, mebleg) in borc_odenisleri {
+        match odenisler.iter_mut().find(|o| o.odenis_usulu == odenis_usulu) {
+            Some(o) => o.borc_odenisi += mebleg,
+            None => odenisler.push(ShiftPaymentTotal {
+                odenis_usulu,
+                satis_sayi: 0,
+                satis_meblegi: 0.0,
+                borc_odenisi: mebleg,
+                cem: 0.0,
+            }),
+        }
+    }
+
+    // Nisyə is not money collected, so it does not add to the drawer
+    for o in odenisler.iter_mut() {
+        o.cem = if o.odenis_usulu == "Nisyə" {
+            0.0
+        } else {
+            o.satis_meblegi + o.borc_odenisi
+        };
+    }
+    odenisler.sort_by(|a, b| a.odenis_usulu.cmp(&b.odenis_usulu));
+
+    let nagd_daxilolma: f64 = odenisler
+        .iter()
+        .filter(|o| o.odenis_usulu == "Nağd")
+        .map(|o| o.cem)
+        .sum();
+
+    // Once closed, the figure stored at closing time is the one of record
+    let gozlenilen_nagd = novbe
+        .gozlenilen_nagd
+        .unwrap_or(novbe.acilis_meblegi + nagd_daxilolma - nagd_qaytarma);
+    let sayilan_nagd = novbe.sayilan_nagd;
+
+    Ok(ZReport {
+        novbe,
+        satis_sayi,
+        toplam_satis,
+        endirim,
+        iade_sayi,
+        toplam_iade,
+        nagd_qaytarma,
+        odenisler,
+        gozlenilen_nagd,
+        sayilan_nagd,
+        ferq: sayilan_nagd.map(|s| s - gozlenilen_nagd),
+    })
+}
+
+#[tauri::command]
+pub async fn novbe_ac(
+    state: State<'_, AppState>,
+    token: String,
+    novbe: OpenShift,
+) -> Result<Shift, String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    if novbe.acilis_meblegi < 0.0 {
+        return Err("Açılış məbləği mənfi ola bilməz".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    if aktiv_novbe_id(&tx, session.istifadeci_id)?.is_some() {
+        return Err("Sizin artıq açıq növbəniz var".to_string());
+    }
+
+    tx
+        .execute(
+            "INSERT INTO shifts (istifadeci_id, acilis_meblegi, status, qeyd) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![session.istifadeci_id, novbe.acilis_meblegi, STATUS_ACIQ, novbe.qeyd],
+        )
+        .map_err(|e| format!("Növbə açıla bilmədi: {}", e))?;
+
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "novbe_ac",
+        "shifts",
+        Some(id),
+        None,
+        audit::setir(&tx, "shifts", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    novbe_oxu(&db.conn, id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn novbe_bagla(
+    state: State<'_, AppState>,
+    token: String,
+    novbe_id: i64,
+    baglama: CloseShift,
+) -> Result<ZReport, String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    if baglama.sayilan_nagd < 0.0 {
+        return Err("Sayılan nağd mənfi ola bilməz".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "shifts", novbe_id)?;
+
+    let novbe = novbe_oxu(&tx, novbe_id)?;
+    novbeye_icaze(&session, &novbe)?;
+
+    if novbe.status == STATUS_BAGLI {
+        return Err("Növbə artıq bağlanıb".to_string());
+    }
+
+    let hesabat = z_hesabati_hazirla(&tx, novbe)?;
+
+    tx.execute(
+        "UPDATE shifts SET
+            status = ?1,
+            gozlenilen_nagd = ?2,
+            sayilan_nagd = ?3,
+            qeyd = COALESCE(?4, qeyd),
+            baglanma_tarixi = CURRENT_TIMESTAMP
+         WHERE id = ?5",
+        rusqlite::params![
+            STATUS_BAGLI,
+            hesabat.gozlenilen_nagd,
+            baglama.sayilan_nagd,
+            baglama.qeyd,
+            novbe_id,
+        ],
+    )
+    .map_err(|e| format!("Növbə bağlana bilmədi: {}", e))?;
+
+    let novbe = novbe_oxu(&tx, novbe_id)?;
+    let hesabat = z_hesabati_hazirla(&tx, novbe)?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "novbe_bagla",
+        "shifts",
+        Some(novbe_id),
+        evvel,
+        audit::setir(&tx, "shifts", novbe_id)?,
+    )?;
+
+    tx.commit().map_err(|e| format!("Növbə bağlana bilmədi: {}", e))?;
+
+    Ok(hesabat)
+}
+
+#[tauri::command]
+pub async fn aktiv_novbe(state: State<'_, AppState>, token: String) -> Result<Option<Shift>, String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    match aktiv_novbe_id(&db.conn, session.istifadeci_id)? {
+        Some(id) => Ok(Some(novbe_oxu(&db.conn, id)?)),
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+pub async fn novbe_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Shift>, String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    // Admins see every shift, cashiers only their own
+    let istifadeci_filtri = if session.admin_dir() { None } else { Some(session.istifadeci_id) };
+
+    let mut stmt = db
+        .conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR sh.istifadeci_id = ?1) ORDER BY sh.acilma_tarixi DESC",
+            SHIFT_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let shifts = stmt
+        .query_map([istifadeci_filtri], row_to_shift)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(shifts)
+}
+
+/// Z-report for a closed shift, or a running (X) report for an open one.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn novbe_z_hesabati(
+    state: State<'_, AppState>,
+    token: String,
+    novbe_id: i64,
+) -> Result<ZReport, String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let novbe = novbe_oxu(&db.conn, novbe_id)?;
+    novbeye_icaze(&session, &novbe)?;
+
+    z_hesabati_hazirla(&db.conn, novbe)
+}
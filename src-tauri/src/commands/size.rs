@@ -1,32 +1,54 @@
+use crate::audit;
 use crate::models::{CreateSize, Size};
+use crate::promotions;
 use crate::AppState;
+use rusqlite::{Connection, OptionalExtension};
 use tauri::State;
 
 #[tauri::command]
 pub async fn olcu_elave_et(
     state: State<'_, AppState>,
+    token: String,
     olcu: CreateSize,
 ) -> Result<Size, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
     
-    db.conn
+    tx
         .execute(
-            "INSERT INTO sizes (olcu) VALUES (?1)",
-            [&olcu.olcu],
+            "INSERT INTO sizes (olcu, qrup_id, sira)
+             VALUES (?1, ?2, (SELECT COALESCE(MAX(sira), 0) + 1 FROM sizes WHERE qrup_id IS ?2))",
+            rusqlite::params![&olcu.olcu, olcu.qrup_id],
         )
         .map_err(|e| format!("Ölçü əlavə edilə bilmədi: {}", e))?;
     
-    let id = db.conn.last_insert_rowid();
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "olcu_elave_et",
+        "sizes",
+        Some(id),
+        None,
+        audit::setir(&tx, "sizes", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
     
     let size = db.conn
         .query_row(
-            "SELECT id, olcu, created_at FROM sizes WHERE id = ?1",
+            "SELECT id, olcu, qrup_id, sira, created_at FROM sizes WHERE id = ?1",
             [id],
             |row| {
                 Ok(Size {
                     id: row.get(0)?,
                     olcu: row.get(1)?,
-                    created_at: row.get(2)?,
+                    qrup_id: row.get(2)?,
+                    sira: row.get(3)?,
+                    created_at: row.get(4)?,
                 })
             },
         )
@@ -36,11 +58,13 @@ pub async fn olcu_elave_et(
 }
 
 #[tauri::command]
-pub async fn olcu_siyahisi(state: State<'_, AppState>) -> Result<Vec<Size>, String> {
+pub async fn olcu_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Size>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
     let mut stmt = db.conn
-        .prepare("SELECT id, olcu, created_at FROM sizes ORDER BY id")
+        .prepare("SELECT id, olcu, qrup_id, sira, created_at FROM sizes ORDER BY qrup_id IS NULL, qrup_id, sira, id")
         .map_err(|e| e.to_string())?;
     
     let sizes = stmt
@@ -48,7 +72,9 @@ pub async fn olcu_siyahisi(state: State<'_, AppState>) -> Result<Vec<Size>, Stri
             Ok(Size {
                 id: row.get(0)?,
                 olcu: row.get(1)?,
-                created_at: row.get(2)?,
+                qrup_id: row.get(2)?,
+                sira: row.get(3)?,
+                created_at: row.get(4)?,
             })
         })
         .map_err(|e| e.to_string())?
@@ -58,16 +84,92 @@ pub async fn olcu_siyahisi(state: State<'_, AppState>) -> Result<Vec<Size>, Stri
     Ok(sizes)
 }
 
+fn olcu_setri(row: &rusqlite::Row) -> rusqlite::Result<Size> {
+    Ok(Size {
+        id: row.get(0)?,
+        olcu: row.get(1)?,
+        qrup_id: row.get(2)?,
+        sira: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+/// Size group of a category; a sub-category without one uses the nearest
+/// parent's.
+pub(crate) fn kateqoriya_olcu_qrupu(conn: &Connection, kateqoriya_id: Option<i64>) -> Result<Option<i64>, String> {
+    let Some(id) = kateqoriya_id else {
+        return Ok(None);
+    };
+
+    conn.query_row(
+        "WITH RECURSIVE ustler(id, parent_id, olcu_qrupu_id, derinlik) AS (
+            SELECT id, parent_id, olcu_qrupu_id, 0 FROM categories WHERE id = ?1
+            UNION ALL
+            SELECT c.id, c.parent_id, c.olcu_qrupu_id, u.derinlik + 1
+            FROM categories c JOIN ustler u ON c.id = u.parent_id
+         )
+         SELECT olcu_qrupu_id FROM ustler
+         WHERE olcu_qrupu_id IS NOT NULL
+         ORDER BY derinlik
+         LIMIT 1",
+        [id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Sizes a category's goods can come in, in group order; every size when the
+/// category (or the product) has no size group.
+pub(crate) fn kateqoriya_olculeri_oxu(conn: &Connection, kateqoriya_id: Option<i64>) -> Result<Vec<Size>, String> {
+    let qrup_id = kateqoriya_olcu_qrupu(conn, kateqoriya_id)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, olcu, qrup_id, sira, created_at FROM sizes
+             WHERE ?1 IS NULL OR qrup_id = ?1
+             ORDER BY qrup_id IS NULL, qrup_id, sira, id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let sizes = stmt
+        .query_map([qrup_id], olcu_setri)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(sizes)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn kateqoriya_olculeri(
+    state: State<'_, AppState>,
+    token: String,
+    kateqoriya_id: Option<i64>,
+) -> Result<Vec<Size>, String> {
+    state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    kateqoriya_olculeri_oxu(&db.conn, kateqoriya_id)
+}
+
 #[tauri::command]
 pub async fn olcu_yenile(
     state: State<'_, AppState>,
+    token: String,
     id: i64,
     olcu: String,
 ) -> Result<Size, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "sizes", id)?;
 
     // Check if the new value already exists for a different size
-    let existing = db.conn.query_row(
+    let existing = tx.query_row(
         "SELECT COUNT(*) FROM sizes WHERE olcu = ?1 AND id != ?2",
         rusqlite::params![&olcu, id],
         |row| row.get::<_, i32>(0),
@@ -77,23 +179,37 @@ pub async fn olcu_yenile(
         return Err("Bu ölçü adı artıq mövcuddur".to_string());
     }
 
-    db.conn
+    tx
         .execute(
             "UPDATE sizes SET olcu = ?1 WHERE id = ?2",
             rusqlite::params![olcu, id],
         )
         .map_err(|e| format!("Ölçü yenilənə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "olcu_yenile",
+        "sizes",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "sizes", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     let size = db
         .conn
         .query_row(
-            "SELECT id, olcu, created_at FROM sizes WHERE id = ?1",
+            "SELECT id, olcu, qrup_id, sira, created_at FROM sizes WHERE id = ?1",
             [id],
             |row| {
                 Ok(Size {
                     id: row.get(0)?,
                     olcu: row.get(1)?,
-                    created_at: row.get(2)?,
+                    qrup_id: row.get(2)?,
+                    sira: row.get(3)?,
+                    created_at: row.get(4)?,
                 })
             },
         )
@@ -103,12 +219,31 @@ pub async fn olcu_yenile(
 }
 
 #[tauri::command]
-pub async fn olcu_sil(state: State<'_, AppState>, id: i64) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub async fn olcu_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
 
-    db.conn
+    let evvel = audit::setir(&tx, "sizes", id)?;
+
+    promotions::aid_kampaniya_yoxla(&tx, "olcu_id", id)?;
+
+    tx
         .execute("DELETE FROM sizes WHERE id = ?1", [id])
         .map_err(|e| format!("Ölçü silinə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "olcu_sil",
+        "sizes",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     Ok(())
 }
@@ -0,0 +1,227 @@
+use crate::audit;
+use crate::models::{CreateSizeGroup, Size, SizeGroup};
+use crate::AppState;
+use rusqlite::Connection;
+use tauri::State;
+
+fn qrup_olculeri(conn: &Connection, qrup_id: i64) -> Result<Vec<Size>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, olcu, qrup_id, sira, created_at FROM sizes
+             WHERE qrup_id = ?1
+             ORDER BY sira, id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let sizes = stmt
+        .query_map([qrup_id], |row| {
+            Ok(Size {
+                id: row.get(0)?,
+                olcu: row.get(1)?,
+                qrup_id: row.get(2)?,
+                sira: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(sizes)
+}
+
+fn qrup_oxu(conn: &Connection, id: i64) -> Result<SizeGroup, String> {
+    let (ad, created_at) = conn
+        .query_row(
+            "SELECT ad, created_at FROM size_groups WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Ölçü qrupu tapılmadı: {}", e))?;
+
+    Ok(SizeGroup {
+        id,
+        ad,
+        olculer: qrup_olculeri(conn, id)?,
+        created_at,
+    })
+}
+
+// Audit snapshot of a group together with its ordered sizes
+fn qrup_setri(conn: &Connection, id: i64) -> Result<Option<serde_json::Value>, String> {
+    let mut setir = match audit::setir(conn, "size_groups", id)? {
+        Some(setir) => setir,
+        None => return Ok(None),
+    };
+    setir["olculer"] = serde_json::Value::Array(audit::setirler_json(
+        conn,
+        "SELECT * FROM sizes WHERE qrup_id = ?1 ORDER BY sira, id",
+        [id],
+    )?);
+    Ok(Some(setir))
+}
+
+#[tauri::command]
+pub async fn olcu_qrupu_elave_et(
+    state: State<'_, AppState>,
+    token: String,
+    qrup: CreateSizeGroup,
+) -> Result<SizeGroup, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    tx
+        .execute("INSERT INTO size_groups (ad) VALUES (?1)", [qrup.ad.trim()])
+        .map_err(|e| format!("Ölçü qrupu əlavə edilə bilmədi: {}", e))?;
+
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "olcu_qrupu_elave_et",
+        "size_groups",
+        Some(id),
+        None,
+        audit::setir(&tx, "size_groups", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    qrup_oxu(&db.conn, id)
+}
+
+#[tauri::command]
+pub async fn olcu_qrupu_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<SizeGroup>, String> {
+    state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let idler: Vec<i64> = db.conn
+        .prepare("SELECT id FROM size_groups ORDER BY id")
+        .map_err(|e| e.to_string())?
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    idler.into_iter().map(|id| qrup_oxu(&db.conn, id)).collect()
+}
+
+#[tauri::command]
+pub async fn olcu_qrupu_yenile(
+    state: State<'_, AppState>,
+    token: String,
+    id: i64,
+    ad: String,
+) -> Result<SizeGroup, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "size_groups", id)?;
+
+    tx
+        .execute(
+            "UPDATE size_groups SET ad = ?1 WHERE id = ?2",
+            rusqlite::params![ad.trim(), id],
+        )
+        .map_err(|e| format!("Ölçü qrupu yenilənə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "olcu_qrupu_yenile",
+        "size_groups",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "size_groups", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    qrup_oxu(&db.conn, id)
+}
+
+/// Sets which sizes make up the group and in what order. Sizes left out of
+/// the list leave the group; ones taken from another group move here.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn olcu_qrupu_sirala(
+    state: State<'_, AppState>,
+    token: String,
+    id: i64,
+    olcu_idler: Vec<i64>,
+) -> Result<SizeGroup, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = qrup_setri(&tx, id)?;
+    if evvel.is_none() {
+        return Err("Ölçü qrupu tapılmadı".to_string());
+    }
+
+    tx.execute("UPDATE sizes SET qrup_id = NULL WHERE qrup_id = ?1", [id])
+        .map_err(|e| format!("Ölçü qrupu yenilənə bilmədi: {}", e))?;
+
+    for (sira, olcu_id) in olcu_idler.iter().enumerate() {
+        let deyisen = tx
+            .execute(
+                "UPDATE sizes SET qrup_id = ?1, sira = ?2 WHERE id = ?3",
+                rusqlite::params![id, sira as i64 + 1, olcu_id],
+            )
+            .map_err(|e| format!("Ölçü qrupu yenilənə bilmədi: {}", e))?;
+        if deyisen == 0 {
+            return Err(format!("Ölçü tapılmadı: {}", olcu_id));
+        }
+    }
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "olcu_qrupu_sirala",
+        "size_groups",
+        Some(id),
+        evvel,
+        qrup_setri(&tx, id)?,
+    )?;
+
+    let qrup = qrup_oxu(&tx, id)?;
+
+    tx.commit().map_err(|e| format!("Ölçü qrupu yadda saxlanıla bilmədi: {}", e))?;
+
+    Ok(qrup)
+}
+
+/// Sizes and categories in the group are kept, just without a group.
+#[tauri::command]
+pub async fn olcu_qrupu_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = qrup_setri(&tx, id)?;
+
+    tx
+        .execute("DELETE FROM size_groups WHERE id = ?1", [id])
+        .map_err(|e| format!("Ölçü qrupu silinə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "olcu_qrupu_sil",
+        "size_groups",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
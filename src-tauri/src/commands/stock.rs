@@ -1,117 +1,273 @@
+use crate::audit;
+use crate::auth::session::Session;
+use crate::commands::size;
+use crate::inventory::cost::{self, MayaUsulu};
 use crate::models::{CreateStock, Stock, UpdateStock};
 use crate::AppState;
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::Value;
 use tauri::State;
 
-#[tauri::command]
-pub async fn stok_elave_et(
-    state: State<'_, AppState>,
-    stok: CreateStock,
+const STOK_SELECT: &str = "SELECT s.id, s.mehsul_id, s.olcu_id, sz.olcu, s.reng_id, cl.ad, s.miqdar,
+            s.minimum_miqdar, s.created_at, s.updated_at, p.ad, p.barkod, p.kateqoriya_id, c.ad
+     FROM stock s
+     JOIN products p ON s.mehsul_id = p.id
+     LEFT JOIN categories c ON p.kateqoriya_id = c.id
+     JOIN sizes sz ON s.olcu_id = sz.id
+     LEFT JOIN colors cl ON s.reng_id = cl.id";
+
+fn stok_setri_oxu(row: &rusqlite::Row) -> rusqlite::Result<Stock> {
+    Ok(Stock {
+        id: row.get(0)?,
+        mehsul_id: row.get(1)?,
+        olcu_id: row.get(2)?,
+        olcu: row.get(3)?,
+        reng_id: row.get(4)?,
+        reng: row.get(5)?,
+        miqdar: row.get(6)?,
+        minimum_miqdar: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+        mehsul_adi: row.get(10)?,
+        mehsul_barkod: row.get(11)?,
+        kateqoriya_id: row.get(12)?,
+        kateqoriya_adi: row.get(13)?,
+    })
+}
+
+/// Audit snapshot of one stock row, which is keyed by product, size and colour.
+fn stok_setri(
+    conn: &Connection,
+    mehsul_id: i64,
+    olcu_id: i64,
+    reng_id: Option<i64>,
+) -> Result<Option<Value>, String> {
+    audit::setir_json(
+        conn,
+        "SELECT * FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3",
+        rusqlite::params![mehsul_id, olcu_id, reng_id],
+    )
+}
+
+/// A product only comes in the sizes of its category's size group; any size
+/// goes when the category has none.
+pub(crate) fn olcu_yoxla(conn: &Connection, mehsul_id: i64, olcu_id: i64) -> Result<(), String> {
+    let kateqoriya_id: Option<i64> = conn
+        .query_row("SELECT kateqoriya_id FROM products WHERE id = ?1", [mehsul_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    let Some(olcu_qrupu_id) = size::kateqoriya_olcu_qrupu(conn, kateqoriya_id)? else {
+        return Ok(());
+    };
+
+    let qrup_id: Option<i64> = conn
+        .query_row("SELECT qrup_id FROM sizes WHERE id = ?1", [olcu_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    if qrup_id != Some(olcu_qrupu_id) {
+        return Err("Seçilmiş ölçü bu kateqoriyanın ölçü qrupuna aid deyil".to_string());
+    }
+
+    Ok(())
+}
+
+/// Stock left in a size outside its category's size group, for the category
+/// `kateqoriya_id` and everything below it: (stock id, label).
+pub(crate) fn qrupdan_kenar_stok(conn: &Connection, kateqoriya_id: i64) -> Result<Vec<(i64, String)>, String> {
+    let olcu_qrupu_id = size::kateqoriya_olcu_qrupu(conn, Some(kateqoriya_id))?;
+
+    // A category without its own group takes its parent's
+    let mut stmt = conn
+        .prepare(
+            "WITH RECURSIVE agac(id, olcu_qrupu_id) AS (
+                SELECT ?1, ?2
+                UNION
+                SELECT c.id, COALESCE(c.olcu_qrupu_id, a.olcu_qrupu_id)
+                FROM categories c JOIN agac a ON c.parent_id = a.id
+             )
+             SELECT s.id, p.ad || ' (' || sz.olcu || COALESCE(', ' || cl.ad, '') || '): ' || s.miqdar
+             FROM stock s
+             JOIN products p ON p.id = s.mehsul_id
+             JOIN agac a ON a.id = p.kateqoriya_id
+             JOIN sizes sz ON sz.id = s.olcu_id
+             LEFT JOIN colors cl ON cl.id = s.reng_id
+             WHERE s.miqdar != 0 AND a.olcu_qrupu_id IS NOT NULL AND sz.qrup_id IS NOT a.olcu_qrupu_id
+             ORDER BY p.ad, sz.sira, cl.ad",
+        )
+        .map_err(|e| e.to_string())?;
+    let setirler = stmt
+        .query_map(rusqlite::params![kateqoriya_id, olcu_qrupu_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(setirler)
+}
+
+/// After a change to the category tree or its size groups, refuses it when it
+/// put stock that is not empty outside its size group. `evvel` is what
+/// `qrupdan_kenar_stok` found before the change; rows already there do not count.
+pub(crate) fn qrupdan_kenar_stok_yoxla(
+    conn: &Connection,
+    kateqoriya_id: i64,
+    evvel: &[(i64, String)],
+) -> Result<(), String> {
+    let yeni: Vec<String> = qrupdan_kenar_stok(conn, kateqoriya_id)?
+        .into_iter()
+        .filter(|(id, _)| !evvel.iter().any(|(e, _)| e == id))
+        .map(|(_, etiket)| etiket)
+        .collect();
+
+    if !yeni.is_empty() {
+        return Err(format!(
+            "Bu stok yeni ölçü qrupuna aid olmayan ölçülərdədir, əvvəlcə onu sıfırlayın:\n{}",
+            yeni.join("\n")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Adds to a stock row (creating it if needed), with the movement, cost
+/// layer and audit entry that go with it. Call inside a transaction.
+pub(crate) fn stok_daxil_et(
+    conn: &Connection,
+    session: &Session,
+    emr: &str,
+    stok: &CreateStock,
 ) -> Result<Stock, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    olcu_yoxla(conn, stok.mehsul_id, stok.olcu_id)?;
+
+    let evvel = stok_setri(conn, stok.mehsul_id, stok.olcu_id, stok.reng_id)?;
     
     let minimum = stok.minimum_miqdar.unwrap_or(1);
     
-    db.conn
+    conn
         .execute(
-            "INSERT INTO stock (mehsul_id, olcu_id, miqdar, minimum_miqdar)
-             VALUES (?1, ?2, ?3, ?4)
-             ON CONFLICT(mehsul_id, olcu_id) DO UPDATE SET
+            "INSERT INTO stock (mehsul_id, olcu_id, reng_id, miqdar, minimum_miqdar)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(mehsul_id, olcu_id, IFNULL(reng_id, 0)) DO UPDATE SET
              miqdar = miqdar + excluded.miqdar,
              updated_at = CURRENT_TIMESTAMP",
-            rusqlite::params![stok.mehsul_id, stok.olcu_id, stok.miqdar, minimum],
+            rusqlite::params![stok.mehsul_id, stok.olcu_id, stok.reng_id, stok.miqdar, minimum],
         )
         .map_err(|e| format!("Stok əlavə edilə bilmədi: {}", e))?;
 
-    // Get product alis_qiymeti for logging
-    let alis_qiymeti: f64 = db.conn
-        .query_row(
-            "SELECT alis_qiymeti FROM products WHERE id = ?1",
-            [stok.mehsul_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0.0);
+    // A zero line only sets up the row, e.g. an empty cell of a new style
+    if stok.miqdar != 0 {
+        // Get product alis_qiymeti for logging
+        let alis_qiymeti: f64 = conn
+            .query_row(
+                "SELECT alis_qiymeti FROM products WHERE id = ?1",
+                [stok.mehsul_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(0.0);
 
-    let toplam_deyeri = stok.miqdar as f64 * alis_qiymeti;
+        let toplam_deyeri = stok.miqdar as f64 * alis_qiymeti;
+
+        let qeyd = match emr {
+            "mehsul_idxal_et" => "İdxal",
+            _ => "Stok əlavə edildi",
+        };
+
+        // Log stock movement with price info
+        conn
+            .execute(
+                "INSERT INTO stock_movements (mehsul_id, olcu_id, reng_id, novu, miqdar, vahid_alis_qiymeti, toplam_deyeri, qeyd)
+                 VALUES (?1, ?2, ?3, 'Daxil olma', ?4, ?5, ?6, ?7)",
+                rusqlite::params![stok.mehsul_id, stok.olcu_id, stok.reng_id, stok.miqdar, alis_qiymeti, toplam_deyeri, qeyd],
+            )
+            .map_err(|e| format!("Stok hərəkəti yazıla bilmədi: {}", e))?;
+
+        let hereket_id = conn.last_insert_rowid();
+        cost::lay_elave_et(conn, stok.mehsul_id, stok.olcu_id, stok.reng_id, stok.miqdar, alis_qiymeti, Some(hereket_id))?;
+    }
 
-    // Log stock movement with price info
-    db.conn
-        .execute(
-            "INSERT INTO stock_movements (mehsul_id, olcu_id, novu, miqdar, vahid_alis_qiymeti, toplam_deyeri, qeyd)
-             VALUES (?1, ?2, 'Daxil olma', ?3, ?4, ?5, 'Stok əlavə edildi')",
-            rusqlite::params![stok.mehsul_id, stok.olcu_id, stok.miqdar, alis_qiymeti, toplam_deyeri],
-        )
-        .ok();
-    
     // Get the stock record
-    let stock = db.conn
+    let stock = conn
         .query_row(
-            "SELECT s.*, p.ad as mehsul_adi, p.barkod as mehsul_barkod, 
-                    p.kateqoriya_id, c.ad as kateqoriya_adi, sz.olcu
-             FROM stock s
-             JOIN products p ON s.mehsul_id = p.id
-             LEFT JOIN categories c ON p.kateqoriya_id = c.id
-             JOIN sizes sz ON s.olcu_id = sz.id
-             WHERE s.mehsul_id = ?1 AND s.olcu_id = ?2",
-            [stok.mehsul_id, stok.olcu_id],
-            |row| {
-                Ok(Stock {
-                    id: row.get(0)?,
-                    mehsul_id: row.get(1)?,
-                    olcu_id: row.get(2)?,
-                    miqdar: row.get(3)?,
-                    minimum_miqdar: row.get(4)?,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                    mehsul_adi: row.get(7)?,
-                    mehsul_barkod: row.get(8)?,
-                    kateqoriya_id: row.get(9)?,
-                    kateqoriya_adi: row.get(10)?,
-                    olcu: row.get(11)?,
-                })
-            },
+            &format!("{} WHERE s.mehsul_id = ?1 AND s.olcu_id = ?2 AND s.reng_id IS ?3", STOK_SELECT),
+            rusqlite::params![stok.mehsul_id, stok.olcu_id, stok.reng_id],
+            stok_setri_oxu,
         )
         .map_err(|e| format!("Stok tapılmadı: {}", e))?;
-    
+
+    audit::qeyde_al(
+        conn,
+        session,
+        emr,
+        "stock",
+        Some(stock.id),
+        evvel,
+        stok_setri(conn, stok.mehsul_id, stok.olcu_id, stok.reng_id)?,
+    )?;
+
     Ok(stock)
 }
 
 #[tauri::command]
+pub async fn stok_elave_et(
+    state: State<'_, AppState>,
+    token: String,
+    stok: CreateStock,
+) -> Result<Stock, String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let stock = stok_daxil_et(&tx, &session, "stok_elave_et", &stok)?;
+
+    tx.commit().map_err(|e| format!("Stok yadda saxlanıla bilmədi: {}", e))?;
+    
+    Ok(stock)
+}
+
+#[tauri::command(rename_all = "camelCase")]
 pub async fn stok_yenile(
     state: State<'_, AppState>,
-    mehsulId: i64,
-    olcuId: i64,
+    token: String,
+    mehsul_id: i64,
+    olcu_id: i64,
+    reng_id: Option<i64>,
     stok: UpdateStock,
 ) -> Result<Stock, String> {
-    println!("stok_yenile called: mehsul_id={}, olcu_id={}, miqdar={}", mehsulId, olcuId, stok.miqdar);
-    
-    let mehsul_id = mehsulId;
-    let olcu_id = olcuId;
+    let session = state.sessions.yoxla(&token)?;
     
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let maya_usulu = MayaUsulu::oxu(&db.conn)?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = stok_setri(&tx, mehsul_id, olcu_id, reng_id)?;
+
+    // A row left over from before the size groups may still be emptied
+    if evvel.is_none() || stok.miqdar != 0 {
+        olcu_yoxla(&tx, mehsul_id, olcu_id)?;
+    }
     
     // Get current stock (if exists)
-    let evvelki: i32 = db.conn
+    let evvelki: i32 = tx
         .query_row(
-            "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2",
-            [mehsul_id, olcu_id],
+            "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3",
+            rusqlite::params![mehsul_id, olcu_id, reng_id],
             |row| row.get(0),
         )
         .unwrap_or(0);
     
-    println!("Previous stock: {}", evvelki);
-    
     let minimum = stok.minimum_miqdar.unwrap_or(1);
     
     // Use INSERT OR REPLACE to handle both insert and update
-    db.conn
+    tx
         .execute(
-            "INSERT INTO stock (mehsul_id, olcu_id, miqdar, minimum_miqdar) 
-             VALUES (?1, ?2, ?3, ?4)
-             ON CONFLICT(mehsul_id, olcu_id) DO UPDATE SET 
+            "INSERT INTO stock (mehsul_id, olcu_id, reng_id, miqdar, minimum_miqdar) 
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(mehsul_id, olcu_id, IFNULL(reng_id, 0)) DO UPDATE SET 
              miqdar = excluded.miqdar,
              minimum_miqdar = excluded.minimum_miqdar,
              updated_at = CURRENT_TIMESTAMP",
-            rusqlite::params![mehsul_id, olcu_id, stok.miqdar, minimum],
+            rusqlite::params![mehsul_id, olcu_id, reng_id, stok.miqdar, minimum],
         )
         .map_err(|e| format!("Stok yenilənə bilmədi: {}", e))?;
     
@@ -122,7 +278,7 @@ pub async fn stok_yenile(
 
         // Get product alis_qiymeti for logging (only for "Daxil olma")
         let (vahid_alis_qiymeti, toplam_deyeri): (Option<f64>, Option<f64>) = if novu == "Daxil olma" {
-            let alis_qiymeti: f64 = db.conn
+            let alis_qiymeti: f64 = tx
                 .query_row(
                     "SELECT alis_qiymeti FROM products WHERE id = ?1",
                     [mehsul_id],
@@ -134,81 +290,64 @@ pub async fn stok_yenile(
             (None, None)
         };
 
-        db.conn
+        tx
             .execute(
-                "INSERT INTO stock_movements (mehsul_id, olcu_id, novu, miqdar, evvelki_miqdar, yeni_miqdar, vahid_alis_qiymeti, toplam_deyeri, qeyd)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'Stok düzəliş')",
-                rusqlite::params![mehsul_id, olcu_id, novu, ferq, evvelki, stok.miqdar, vahid_alis_qiymeti, toplam_deyeri],
+                "INSERT INTO stock_movements (mehsul_id, olcu_id, reng_id, novu, miqdar, evvelki_miqdar, yeni_miqdar, vahid_alis_qiymeti, toplam_deyeri, qeyd)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'Stok düzəliş')",
+                rusqlite::params![mehsul_id, olcu_id, reng_id, novu, ferq, evvelki, stok.miqdar, vahid_alis_qiymeti, toplam_deyeri],
             )
-            .ok();
+            .map_err(|e| format!("Stok hərəkəti yazıla bilmədi: {}", e))?;
+
+        // Increases open a cost layer, decreases write units off the oldest ones
+        match vahid_alis_qiymeti {
+            Some(maya) => {
+                let hereket_id = tx.last_insert_rowid();
+                cost::lay_elave_et(&tx, mehsul_id, olcu_id, reng_id, ferq, maya, Some(hereket_id))?;
+            }
+            None => {
+                cost::istehlak_et(&tx, mehsul_id, olcu_id, reng_id, ferq, maya_usulu)?;
+            }
+        }
     }
     
     // Get updated stock
-    let stock = db.conn
+    let stock = tx
         .query_row(
-            "SELECT s.*, p.ad as mehsul_adi, p.barkod as mehsul_barkod, 
-                    p.kateqoriya_id, c.ad as kateqoriya_adi, sz.olcu
-             FROM stock s
-             JOIN products p ON s.mehsul_id = p.id
-             LEFT JOIN categories c ON p.kateqoriya_id = c.id
-             JOIN sizes sz ON s.olcu_id = sz.id
-             WHERE s.mehsul_id = ?1 AND s.olcu_id = ?2",
-            [mehsul_id, olcu_id],
-            |row| {
-                Ok(Stock {
-                    id: row.get(0)?,
-                    mehsul_id: row.get(1)?,
-                    olcu_id: row.get(2)?,
-                    miqdar: row.get(3)?,
-                    minimum_miqdar: row.get(4)?,
-                    created_at: row.get(5)?,
-                    updated_at: row.get(6)?,
-                    mehsul_adi: row.get(7)?,
-                    mehsul_barkod: row.get(8)?,
-                    kateqoriya_id: row.get(9)?,
-                    kateqoriya_adi: row.get(10)?,
-                    olcu: row.get(11)?,
-                })
-            },
+            &format!("{} WHERE s.mehsul_id = ?1 AND s.olcu_id = ?2 AND s.reng_id IS ?3", STOK_SELECT),
+            rusqlite::params![mehsul_id, olcu_id, reng_id],
+            stok_setri_oxu,
         )
         .map_err(|e| format!("Stok tapılmadı: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "stok_yenile",
+        "stock",
+        Some(stock.id),
+        evvel,
+        stok_setri(&tx, mehsul_id, olcu_id, reng_id)?,
+    )?;
+
+    tx.commit().map_err(|e| format!("Stok yadda saxlanıla bilmədi: {}", e))?;
     
     Ok(stock)
 }
 
 #[tauri::command]
-pub async fn stok_siyahisi(state: State<'_, AppState>) -> Result<Vec<Stock>, String> {
+pub async fn stok_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Stock>, String> {
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     
     let mut stmt = db.conn
         .prepare(
-            "SELECT s.*, p.ad as mehsul_adi, p.barkod as mehsul_barkod, 
-                    p.kateqoriya_id, c.ad as kateqoriya_adi, sz.olcu
-             FROM stock s
-             JOIN products p ON s.mehsul_id = p.id
-             LEFT JOIN categories c ON p.kateqoriya_id = c.id
-             JOIN sizes sz ON s.olcu_id = sz.id
-             ORDER BY p.ad, sz.olcu",
+            &format!("{} ORDER BY p.ad, sz.olcu, cl.ad", STOK_SELECT),
         )
         .map_err(|e| e.to_string())?;
     
     let stocks = stmt
-        .query_map([], |row| {
-            Ok(Stock {
-                id: row.get(0)?,
-                mehsul_id: row.get(1)?,
-                olcu_id: row.get(2)?,
-                miqdar: row.get(3)?,
-                minimum_miqdar: row.get(4)?,
-                created_at: row.get(5)?,
-                updated_at: row.get(6)?,
-                mehsul_adi: row.get(7)?,
-                mehsul_barkod: row.get(8)?,
-                kateqoriya_id: row.get(9)?,
-                kateqoriya_adi: row.get(10)?,
-                olcu: row.get(11)?,
-            })
-        })
+        .query_map([], stok_setri_oxu)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
@@ -216,44 +355,38 @@ pub async fn stok_siyahisi(state: State<'_, AppState>) -> Result<Vec<Stock>, Str
     Ok(stocks)
 }
 
-#[tauri::command]
+#[tauri::command(rename_all = "camelCase")]
 pub async fn stok_mehsul_ucun(
     state: State<'_, AppState>,
-    mehsulId: i64,
+    token: String,
+    mehsul_id: i64,
 ) -> Result<Vec<Stock>, String> {
-    let mehsul_id = mehsulId;
+    state.sessions.yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let kateqoriya_id: Option<i64> = db.conn
+        .query_row("SELECT kateqoriya_id FROM products WHERE id = ?1", [mehsul_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    let olcu_qrupu_id = size::kateqoriya_olcu_qrupu(&db.conn, kateqoriya_id)?;
     
     let mut stmt = db.conn
         .prepare(
-            "SELECT s.*, p.ad as mehsul_adi, p.barkod as mehsul_barkod, 
-                    p.kateqoriya_id, c.ad as kateqoriya_adi, sz.olcu
-             FROM stock s
-             JOIN products p ON s.mehsul_id = p.id
-             LEFT JOIN categories c ON p.kateqoriya_id = c.id
-             JOIN sizes sz ON s.olcu_id = sz.id
-             WHERE s.mehsul_id = ?1
-             ORDER BY sz.id",
+            // Only sizes from the category's size group, plus any other row
+            // that still holds stock so nothing on the shelf goes missing
+            &format!(
+                "{} WHERE s.mehsul_id = ?1
+                   AND (?2 IS NULL OR sz.qrup_id = ?2 OR s.miqdar <> 0)
+                 ORDER BY sz.sira, sz.id, cl.ad",
+                STOK_SELECT
+            ),
         )
         .map_err(|e| e.to_string())?;
     
     let stocks = stmt
-        .query_map([mehsul_id], |row| {
-            Ok(Stock {
-                id: row.get(0)?,
-                mehsul_id: row.get(1)?,
-                olcu_id: row.get(2)?,
-                miqdar: row.get(3)?,
-                minimum_miqdar: row.get(4)?,
-                created_at: row.get(5)?,
-                updated_at: row.get(6)?,
-                mehsul_adi: row.get(7)?,
-                mehsul_barkod: row.get(8)?,
-                kateqoriya_id: row.get(9)?,
-                kateqoriya_adi: row.get(10)?,
-                olcu: row.get(11)?,
-            })
-        })
+        .query_map(rusqlite::params![mehsul_id, olcu_qrupu_id], stok_setri_oxu)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
@@ -261,42 +394,66 @@ pub async fn stok_mehsul_ucun(
     Ok(stocks)
 }
 
-#[tauri::command]
+#[tauri::command(rename_all = "camelCase")]
 pub async fn stok_sil(
     state: State<'_, AppState>,
-    mehsulId: i64,
-    olcuId: i64,
+    token: String,
+    mehsul_id: i64,
+    olcu_id: i64,
+    reng_id: Option<i64>,
 ) -> Result<(), String> {
-    let mehsul_id = mehsulId;
-    let olcu_id = olcuId;
+    let session = state.sessions.admin_yoxla(&token)?;
     
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = stok_setri(&tx, mehsul_id, olcu_id, reng_id)?;
     
     // Get current stock for logging
-    let miqdar: i32 = db.conn
+    let miqdar: i32 = tx
         .query_row(
-            "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2",
-            [mehsul_id, olcu_id],
+            "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3",
+            rusqlite::params![mehsul_id, olcu_id, reng_id],
             |row| row.get(0),
         )
         .unwrap_or(0);
     
     // Delete stock record
-    db.conn
+    tx
         .execute(
-            "DELETE FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2",
-            [mehsul_id, olcu_id],
+            "DELETE FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3",
+            rusqlite::params![mehsul_id, olcu_id, reng_id],
         )
         .map_err(|e| format!("Stok silinə bilmədi: {}", e))?;
     
     // Log stock movement
-    db.conn
+    tx
         .execute(
-            "INSERT INTO stock_movements (mehsul_id, olcu_id, novu, miqdar, qeyd)
-             VALUES (?1, ?2, 'Çıxış', ?3, 'Stok silindi')",
-            rusqlite::params![mehsul_id, olcu_id, miqdar],
+            "INSERT INTO stock_movements (mehsul_id, olcu_id, reng_id, novu, miqdar, qeyd)
+             VALUES (?1, ?2, ?3, 'Çıxış', ?4, 'Stok silindi')",
+            rusqlite::params![mehsul_id, olcu_id, reng_id, miqdar],
         )
-        .ok();
+        .map_err(|e| format!("Stok hərəkəti yazıla bilmədi: {}", e))?;
+
+    // Nothing is left on hand, so no cost layers either
+    tx.execute(
+        "DELETE FROM cost_layers WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3",
+        rusqlite::params![mehsul_id, olcu_id, reng_id],
+    )
+    .map_err(|e| format!("Maya dəyəri yenilənə bilmədi: {}", e))?;
+
+    let stok_id = evvel.as_ref().and_then(|v| v["id"].as_i64());
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "stok_sil",
+        "stock",
+        stok_id,
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| format!("Stok silinə bilmədi: {}", e))?;
     
     Ok(())
 }
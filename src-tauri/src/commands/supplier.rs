@@ -0,0 +1,248 @@
+use crate::audit;
+use crate::AppState;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Supplier {
+    pub id: i64,
+    pub ad: String,
+    pub elaqe_sexsi: Option<String>,
+    pub telefon: Option<String>,
+    pub email: Option<String>,
+    pub unvan: Option<String>,
+    pub qeyd: Option<String>,
+    pub aktiv: bool,
+    pub baslangic_borcu: f64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSupplier {
+    pub ad: String,
+    pub elaqe_sexsi: Option<String>,
+    pub telefon: Option<String>,
+    pub email: Option<String>,
+    pub unvan: Option<String>,
+    pub qeyd: Option<String>,
+    pub baslangic_borcu: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSupplier {
+    pub ad: Option<String>,
+    pub elaqe_sexsi: Option<String>,
+    pub telefon: Option<String>,
+    pub email: Option<String>,
+    pub unvan: Option<String>,
+    pub qeyd: Option<String>,
+    pub aktiv: Option<bool>,
+}
+
+const SUPPLIER_COLUMNS: &str =
+    "id, ad, elaqe_sexsi, telefon, email, unvan, qeyd, aktiv, COALESCE(baslangic_borcu, 0), created_at, updated_at";
+
+fn row_to_supplier(row: &rusqlite::Row) -> rusqlite::Result<Supplier> {
+    let aktiv_int: i64 = row.get(7)?;
+    Ok(Supplier {
+        id: row.get(0)?,
+        ad: row.get(1)?,
+        elaqe_sexsi: row.get(2)?,
+        telefon: row.get(3)?,
+        email: row.get(4)?,
+        unvan: row.get(5)?,
+        qeyd: row.get(6)?,
+        aktiv: aktiv_int != 0,
+        baslangic_borcu: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+pub fn tedarukcu_oxu(conn: &Connection, id: i64) -> Result<Supplier, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM suppliers WHERE id = ?1", SUPPLIER_COLUMNS),
+        [id],
+        row_to_supplier,
+    )
+    .map_err(|e| format!("Tədarükçü tapılmadı: {}", e))
+}
+
+#[tauri::command]
+pub async fn tedarukcu_elave_et(
+    state: State<'_, AppState>,
+    token: String,
+    tedarukcu: CreateSupplier,
+) -> Result<Supplier, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    if tedarukcu.ad.trim().is_empty() {
+        return Err("Tədarükçü adı boş ola bilməz".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    tx
+        .execute(
+            "INSERT INTO suppliers (ad, elaqe_sexsi, telefon, email, unvan, qeyd, baslangic_borcu)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                tedarukcu.ad.trim(),
+                tedarukcu.elaqe_sexsi,
+                tedarukcu.telefon,
+                tedarukcu.email,
+                tedarukcu.unvan,
+                tedarukcu.qeyd,
+                tedarukcu.baslangic_borcu.unwrap_or(0.0),
+            ],
+        )
+        .map_err(|e| format!("Tədarükçü əlavə edilə bilmədi: {}", e))?;
+
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "tedarukcu_elave_et",
+        "suppliers",
+        Some(id),
+        None,
+        audit::setir(&tx, "suppliers", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    tedarukcu_oxu(&db.conn, id)
+}
+
+#[tauri::command]
+pub async fn tedarukcu_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<Supplier>, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = db
+        .conn
+        .prepare(&format!("SELECT {} FROM suppliers ORDER BY ad", SUPPLIER_COLUMNS))
+        .map_err(|e| e.to_string())?;
+
+    let suppliers = stmt
+        .query_map([], row_to_supplier)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(suppliers)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn tedarukcu_yenile(
+    state: State<'_, AppState>,
+    token: String,
+    id: i64,
+    tedarukcu: UpdateSupplier,
+) -> Result<Supplier, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    if tedarukcu.ad.as_deref().is_some_and(|ad| ad.trim().is_empty()) {
+        return Err("Tədarükçü adı boş ola bilməz".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "suppliers", id)?;
+
+    let aktiv_int = tedarukcu.aktiv.map(|v| if v { 1 } else { 0 });
+
+    let updated = tx
+        .execute(
+            "UPDATE suppliers SET
+                ad = COALESCE(?1, ad),
+                elaqe_sexsi = COALESCE(?2, elaqe_sexsi),
+                telefon = COALESCE(?3, telefon),
+                email = COALESCE(?4, email),
+                unvan = COALESCE(?5, unvan),
+                qeyd = COALESCE(?6, qeyd),
+                aktiv = COALESCE(?7, aktiv),
+                updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?8",
+            rusqlite::params![
+                tedarukcu.ad.as_deref().map(str::trim),
+                tedarukcu.elaqe_sexsi,
+                tedarukcu.telefon,
+                tedarukcu.email,
+                tedarukcu.unvan,
+                tedarukcu.qeyd,
+                aktiv_int,
+                id,
+            ],
+        )
+        .map_err(|e| format!("Tədarükçü yenilənə bilmədi: {}", e))?;
+
+    if updated == 0 {
+        return Err("Tədarükçü tapılmadı".to_string());
+    }
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "tedarukcu_yenile",
+        "suppliers",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "suppliers", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    tedarukcu_oxu(&db.conn, id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn tedarukcu_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "suppliers", id)?;
+
+    // Suppliers with order or payment history are deactivated instead of deleted
+    let (sifaris_sayi, odeme_sayi): (i64, i64) = tx
+        .query_row(
+            "SELECT (SELECT COUNT(*) FROM purchase_orders WHERE tedarukcu_id = ?1),
+                    (SELECT COUNT(*) FROM supplier_payments WHERE tedarukcu_id = ?1)",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    if sifaris_sayi > 0 || odeme_sayi > 0 {
+        return Err(format!(
+            "Bu tədarükçünün {} sifarişi və {} ödənişi var, silmək əvəzinə deaktiv edin",
+            sifaris_sayi, odeme_sayi
+        ));
+    }
+
+    tx
+        .execute("DELETE FROM suppliers WHERE id = ?1", [id])
+        .map_err(|e| format!("Tədarükçü silinə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "tedarukcu_sil",
+        "suppliers",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
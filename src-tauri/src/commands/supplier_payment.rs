@@ -0,0 +1,321 @@
+use crate::audit;
+use crate::commands::supplier::{tedarukcu_oxu, Supplier};
+use crate::AppState;
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+// Received goods count as owed only for orders bought on credit
+const ALIS_BORCU: &str = "SELECT COALESCE(SUM(sm.toplam_deyeri), 0)
+     FROM stock_movements sm
+     JOIN purchase_orders po ON sm.sifaris_id = po.id
+     WHERE po.tedarukcu_id = t.id AND COALESCE(po.odenis_usulu, 'Nisyə') = 'Nisyə'";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SupplierPayment {
+    pub id: i64,
+    pub tedarukcu_id: i64,
+    pub tedarukcu_adi: Option<String>,
+    pub sifaris_id: Option<i64>,
+    pub sifaris_nomresi: Option<String>,
+    pub mebleg: f64,
+    pub odenis_usulu: String,
+    pub qeyd: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSupplierPayment {
+    pub tedarukcu_id: i64,
+    pub sifaris_id: Option<i64>,
+    pub mebleg: f64,
+    pub odenis_usulu: String,
+    pub qeyd: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupplierDebtSummary {
+    pub tedarukcu_id: i64,
+    pub tedarukcu_adi: String,
+    pub telefon: Option<String>,
+    pub toplam_borc: f64,
+    pub toplam_odenis: f64,
+    pub qalan_borc: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupplierStatementEntry {
+    pub tarix: String,
+    /// 'Başlanğıc borc', 'Alış' or 'Ödəniş'
+    pub novu: String,
+    pub sifaris_nomresi: Option<String>,
+    pub qeyd: Option<String>,
+    pub borc: f64,
+    pub odenis: f64,
+    pub qaliq: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupplierStatement {
+    pub tedarukcu: Supplier,
+    pub emeliyyatlar: Vec<SupplierStatementEntry>,
+    pub toplam_borc: f64,
+    pub toplam_odenis: f64,
+    pub qalan_borc: f64,
+}
+
+const PAYMENT_SELECT: &str = "SELECT sp.id, sp.tedarukcu_id, t.ad, sp.sifaris_id, po.sifaris_nomresi,
+            sp.mebleg, sp.odenis_usulu, sp.qeyd, sp.created_at
+     FROM supplier_payments sp
+     JOIN suppliers t ON sp.tedarukcu_id = t.id
+     LEFT JOIN purchase_orders po ON sp.sifaris_id = po.id";
+
+fn row_to_payment(row: &rusqlite::Row) -> rusqlite::Result<SupplierPayment> {
+    Ok(SupplierPayment {
+        id: row.get(0)?,
+        tedarukcu_id: row.get(1)?,
+        tedarukcu_adi: row.get(2)?,
+        sifaris_id: row.get(3)?,
+        sifaris_nomresi: row.get(4)?,
+        mebleg: row.get(5)?,
+        odenis_usulu: row.get(6)?,
+        qeyd: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+#[tauri::command]
+pub async fn tedarukcu_odeme_yarat(
+    state: State<'_, AppState>,
+    token: String,
+    odeme: CreateSupplierPayment,
+) -> Result<SupplierPayment, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    if odeme.mebleg <= 0.0 {
+        return Err("Ödəniş məbləği müsbət olmalıdır".to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    tedarukcu_oxu(&tx, odeme.tedarukcu_id)?;
+
+    if let Some(sifaris_id) = odeme.sifaris_id {
+        let sifaris_tedarukcusu: Option<i64> = tx
+            .query_row(
+                "SELECT tedarukcu_id FROM purchase_orders WHERE id = ?1",
+                [sifaris_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        if sifaris_tedarukcusu != Some(odeme.tedarukcu_id) {
+            return Err("Sifariş bu tədarükçüyə aid deyil".to_string());
+        }
+    }
+
+    tx
+        .execute(
+            "INSERT INTO supplier_payments (tedarukcu_id, sifaris_id, mebleg, odenis_usulu, qeyd)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                odeme.tedarukcu_id,
+                odeme.sifaris_id,
+                odeme.mebleg,
+                odeme.odenis_usulu,
+                odeme.qeyd,
+            ],
+        )
+        .map_err(|e| format!("Ödəniş yaradıla bilmədi: {}", e))?;
+
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "tedarukcu_odeme_yarat",
+        "supplier_payments",
+        Some(id),
+        None,
+        audit::setir(&tx, "supplier_payments", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let payment = db
+        .conn
+        .query_row(&format!("{} WHERE sp.id = ?1", PAYMENT_SELECT), [id], row_to_payment)
+        .map_err(|e| format!("Ödəniş tapılmadı: {}", e))?;
+
+    Ok(payment)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn tedarukcu_odeme_siyahisi(
+    state: State<'_, AppState>,
+    token: String,
+    tedarukcu_id: Option<i64>,
+) -> Result<Vec<SupplierPayment>, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = db
+        .conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR sp.tedarukcu_id = ?1) ORDER BY sp.created_at DESC",
+            PAYMENT_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let payments = stmt
+        .query_map([tedarukcu_id], row_to_payment)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(payments)
+}
+
+#[tauri::command]
+pub async fn tedarukcu_borc_xulasesi(
+    state: State<'_, AppState>,
+    token: String,
+) -> Result<Vec<SupplierDebtSummary>, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = db
+        .conn
+        .prepare(&format!(
+            "SELECT tedarukcu_id, tedarukcu_adi, telefon, toplam_borc, toplam_odenis
+             FROM (
+                SELECT
+                    t.id as tedarukcu_id,
+                    t.ad as tedarukcu_adi,
+                    t.telefon,
+                    COALESCE(t.baslangic_borcu, 0) + ({}) as toplam_borc,
+                    COALESCE((SELECT SUM(mebleg) FROM supplier_payments WHERE tedarukcu_id = t.id), 0) as toplam_odenis
+                FROM suppliers t
+             )
+             WHERE toplam_borc > 0 OR toplam_odenis > 0
+             ORDER BY toplam_borc - toplam_odenis DESC",
+            ALIS_BORCU
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let summaries = stmt
+        .query_map([], |row| {
+            let toplam_borc: f64 = row.get(3)?;
+            let toplam_odenis: f64 = row.get(4)?;
+            Ok(SupplierDebtSummary {
+                tedarukcu_id: row.get(0)?,
+                tedarukcu_adi: row.get(1)?,
+                telefon: row.get(2)?,
+                toplam_borc,
+                toplam_odenis,
+                qalan_borc: toplam_borc - toplam_odenis,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(summaries)
+}
+
+/// Chronological statement for one supplier with a running balance.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn tedarukcu_hesab_cixarisi(
+    state: State<'_, AppState>,
+    token: String,
+    tedarukcu_id: i64,
+) -> Result<SupplierStatement, String> {
+    state.sessions.admin_yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let tedarukcu = tedarukcu_oxu(&db.conn, tedarukcu_id)?;
+
+    // Lines received in one go share a timestamp and are shown as one purchase
+    let mut stmt = db
+        .conn
+        .prepare(
+            "SELECT tarix, novu, sifaris_nomresi, qeyd, borc, odenis FROM (
+                SELECT sm.created_at as tarix, 'Alış' as novu, po.sifaris_nomresi, MIN(sm.qeyd) as qeyd,
+                       SUM(sm.toplam_deyeri) as borc, 0.0 as odenis, 1 as sira
+                FROM stock_movements sm
+                JOIN purchase_orders po ON sm.sifaris_id = po.id
+                WHERE po.tedarukcu_id = ?1 AND COALESCE(po.odenis_usulu, 'Nisyə') = 'Nisyə'
+                GROUP BY sm.sifaris_id, sm.created_at
+
+                UNION ALL
+
+                SELECT sp.created_at, 'Ödəniş', po.sifaris_nomresi, sp.qeyd,
+                       0.0, sp.mebleg, 2
+                FROM supplier_payments sp
+                LEFT JOIN purchase_orders po ON sp.sifaris_id = po.id
+                WHERE sp.tedarukcu_id = ?1
+             )
+             ORDER BY tarix, sira",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let setirler = stmt
+        .query_map([tedarukcu_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, f64>(4)?,
+                row.get::<_, f64>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut emeliyyatlar = Vec::new();
+    let mut qaliq = 0.0;
+
+    if tedarukcu.baslangic_borcu != 0.0 {
+        qaliq = tedarukcu.baslangic_borcu;
+        emeliyyatlar.push(SupplierStatementEntry {
+            tarix: tedarukcu.created_at.clone(),
+            novu: "Başlanğıc borc".to_string(),
+            sifaris_nomresi: None,
+            qeyd: None,
+            borc: tedarukcu.baslangic_borcu,
+            odenis: 0.0,
+            qaliq,
+        });
+    }
+
+    for (tarix, novu, sifaris_nomresi, qeyd, borc, odenis) in setirler {
+        qaliq += borc - odenis;
+        emeliyyatlar.push(SupplierStatementEntry {
+            tarix,
+            novu,
+            sifaris_nomresi,
+            qeyd,
+            borc,
+            odenis,
+            qaliq,
+        });
+    }
+
+    let toplam_borc: f64 = emeliyyatlar.iter().map(|e| e.borc).sum();
+    let toplam_odenis: f64 = emeliyyatlar.iter().map(|e| e.odenis).sum();
+
+    Ok(SupplierStatement {
+        tedarukcu,
+        emeliyyatlar,
+        toplam_borc,
+        toplam_odenis,
+        qalan_borc: toplam_borc - toplam_odenis,
+    })
+}
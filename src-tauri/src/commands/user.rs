@@ -1,3 +1,6 @@
+use crate::audit;
+use crate::auth::password::{hash_olunub, sifre_hash, sifre_yoxla};
+use crate::auth::session::ICAZE_XETASI;
 use crate::models::{CreateUser, UpdateUser, UserPublic, LoginRequest, LoginResponse};
 use crate::AppState;
 use tauri::State;
@@ -33,7 +36,7 @@ pub async fn giris_yap(
     let (id, ad, soyad, istifadeci_adi, sifre, rol, aktiv, created_at) = user;
 
     // Check password
-    if sifre != login.sifre {
+    if !sifre_yoxla(&login.sifre, &sifre) {
         return Err("İstifadəçi adı və ya şifrə yanlışdır".to_string());
     }
 
@@ -42,7 +45,21 @@ pub async fn giris_yap(
         return Err("Bu hesab deaktiv edilib".to_string());
     }
 
+    // Legacy plaintext password: rehash it now that we know it is correct
+    if !hash_olunub(&sifre) {
+        let yeni_hash = sifre_hash(&login.sifre)?;
+        db.conn
+            .execute(
+                "UPDATE users SET sifre = ?1 WHERE id = ?2",
+                rusqlite::params![yeni_hash, id],
+            )
+            .map_err(|e| format!("Şifrə yenilənə bilmədi: {}", e))?;
+    }
+
+    let token = state.sessions.yarat(id, &istifadeci_adi, &rol)?;
+
     Ok(LoginResponse {
+        token,
         user: UserPublic {
             id,
             ad,
@@ -56,17 +73,27 @@ pub async fn giris_yap(
     })
 }
 
+#[tauri::command]
+pub async fn cixis_et(state: State<'_, AppState>, token: String) -> Result<(), String> {
+    state.sessions.sil(&token)
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn istifadeci_elave_et(
     state: State<'_, AppState>,
+    token: String,
     user: CreateUser,
 ) -> Result<UserPublic, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.admin_yoxla(&token)?;
 
-    db.conn
+    let sifre = sifre_hash(&user.sifre)?;
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    tx
         .execute(
             "INSERT INTO users (ad, soyad, istifadeci_adi, sifre, rol) VALUES (?1, ?2, ?3, ?4, ?5)",
-            rusqlite::params![user.ad, user.soyad, user.istifadeci_adi, user.sifre, user.rol],
+            rusqlite::params![user.ad, user.soyad, user.istifadeci_adi, sifre, user.rol],
         )
         .map_err(|e| {
             if e.to_string().contains("UNIQUE") {
@@ -76,7 +103,19 @@ pub async fn istifadeci_elave_et(
             }
         })?;
 
-    let id = db.conn.last_insert_rowid();
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "istifadeci_elave_et",
+        "users",
+        Some(id),
+        None,
+        audit::setir(&tx, "users", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
 
     Ok(UserPublic {
         id,
@@ -90,7 +129,9 @@ pub async fn istifadeci_elave_et(
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub async fn istifadeci_siyahisi(state: State<'_, AppState>) -> Result<Vec<UserPublic>, String> {
+pub async fn istifadeci_siyahisi(state: State<'_, AppState>, token: String) -> Result<Vec<UserPublic>, String> {
+    state.sessions.admin_yoxla(&token)?;
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let mut stmt = db
@@ -123,10 +164,16 @@ pub async fn istifadeci_siyahisi(state: State<'_, AppState>) -> Result<Vec<UserP
 #[tauri::command(rename_all = "camelCase")]
 pub async fn istifadeci_yenile(
     state: State<'_, AppState>,
+    token: String,
     id: i64,
     user: UpdateUser,
 ) -> Result<UserPublic, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "users", id)?;
 
     // Build dynamic update query
     let mut updates = Vec::new();
@@ -146,7 +193,7 @@ pub async fn istifadeci_yenile(
     }
     if let Some(ref sifre) = user.sifre {
         updates.push("sifre = ?");
-        params.push(Box::new(sifre.clone()));
+        params.push(Box::new(sifre_hash(sifre)?));
     }
     if let Some(ref rol) = user.rol {
         updates.push("rol = ?");
@@ -167,10 +214,27 @@ pub async fn istifadeci_yenile(
 
     let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
     
-    db.conn
+    tx
         .execute(&query, params_refs.as_slice())
         .map_err(|e| format!("İstifadəçi yenilənə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "istifadeci_yenile",
+        "users",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "users", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    // Role or status changes take effect immediately
+    if user.rol.is_some() || user.aktiv == Some(false) {
+        state.sessions.istifadecini_sil(id)?;
+    }
+
     // Return updated user
     let updated = db
         .conn
@@ -196,12 +260,16 @@ pub async fn istifadeci_yenile(
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub async fn istifadeci_sil(state: State<'_, AppState>, id: i64) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+pub async fn istifadeci_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "users", id)?;
 
     // Prevent deleting the last admin
-    let admin_count: i32 = db
-        .conn
+    let admin_count: i32 = tx
         .query_row(
             "SELECT COUNT(*) FROM users WHERE rol = 'admin' AND aktiv = 1",
             [],
@@ -209,8 +277,7 @@ pub async fn istifadeci_sil(state: State<'_, AppState>, id: i64) -> Result<(), S
         )
         .unwrap_or(0);
 
-    let is_admin: bool = db
-        .conn
+    let is_admin: bool = tx
         .query_row(
             "SELECT rol = 'admin' FROM users WHERE id = ?1",
             [id],
@@ -222,40 +289,76 @@ pub async fn istifadeci_sil(state: State<'_, AppState>, id: i64) -> Result<(), S
         return Err("Son admin istifadəçini silə bilməzsiniz".to_string());
     }
 
-    db.conn
+    tx
         .execute("DELETE FROM users WHERE id = ?1", [id])
         .map_err(|e| format!("İstifadəçi silinə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "istifadeci_sil",
+        "users",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    state.sessions.istifadecini_sil(id)?;
+
     Ok(())
 }
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn sifre_deyis(
     state: State<'_, AppState>,
+    token: String,
     id: i64,
     kohne_sifre: String,
     yeni_sifre: String,
 ) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = state.sessions.yoxla(&token)?;
+
+    // Cashiers may only change their own password
+    if session.istifadeci_id != id && !session.admin_dir() {
+        return Err(ICAZE_XETASI.to_string());
+    }
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
 
     // Verify old password
-    let current_password: String = db
-        .conn
+    let current_password: String = tx
         .query_row("SELECT sifre FROM users WHERE id = ?1", [id], |row| {
             row.get(0)
         })
         .map_err(|_| "İstifadəçi tapılmadı".to_string())?;
 
-    if current_password != kohne_sifre {
+    if !sifre_yoxla(&kohne_sifre, &current_password) {
         return Err("Köhnə şifrə yanlışdır".to_string());
     }
 
-    db.conn
+    let yeni_hash = sifre_hash(&yeni_sifre)?;
+
+    tx
         .execute(
             "UPDATE users SET sifre = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
-            rusqlite::params![yeni_sifre, id],
+            rusqlite::params![yeni_hash, id],
         )
         .map_err(|e| format!("Şifrə dəyişdirilə bilmədi: {}", e))?;
 
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "sifre_deyis",
+        "users",
+        Some(id),
+        None,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
     Ok(())
 }
@@ -0,0 +1,181 @@
+use crate::audit;
+use crate::barcode;
+use crate::commands::stock;
+use crate::models::{CreateVariant, Variant};
+use crate::AppState;
+use rusqlite::{Connection, OptionalExtension};
+use tauri::State;
+
+const VARIANT_SELECT: &str = "SELECT v.id, v.mehsul_id, v.olcu_id, sz.olcu, v.reng_id, cl.ad, v.barkod,
+            COALESCE(s.miqdar, 0), v.created_at
+     FROM variants v
+     LEFT JOIN sizes sz ON sz.id = v.olcu_id
+     LEFT JOIN colors cl ON cl.id = v.reng_id
+     LEFT JOIN stock s ON s.mehsul_id = v.mehsul_id AND s.olcu_id = v.olcu_id AND s.reng_id IS v.reng_id";
+
+fn variant_setri(row: &rusqlite::Row) -> rusqlite::Result<Variant> {
+    Ok(Variant {
+        id: row.get(0)?,
+        mehsul_id: row.get(1)?,
+        olcu_id: row.get(2)?,
+        olcu: row.get(3)?,
+        reng_id: row.get(4)?,
+        reng: row.get(5)?,
+        barkod: row.get(6)?,
+        miqdar: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+fn variant_oxu(conn: &Connection, id: i64) -> Result<Variant, String> {
+    conn.query_row(&format!("{} WHERE v.id = ?1", VARIANT_SELECT), [id], variant_setri)
+        .map_err(|e| format!("Variant tapılmadı: {}", e))
+}
+
+/// The variant a scanned barcode belongs to, if any.
+pub fn barkodla_tap(conn: &Connection, barkod: &str) -> Result<Option<Variant>, String> {
+    conn.query_row(&format!("{} WHERE v.barkod = ?1", VARIANT_SELECT), [barkod], variant_setri)
+        .optional()
+        .map_err(|e| format!("Axtarış xətası: {}", e))
+}
+
+#[tauri::command]
+pub async fn variant_elave_et(
+    state: State<'_, AppState>,
+    token: String,
+    variant: CreateVariant,
+) -> Result<Variant, String> {
+    let session = state.sessions.yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    // A variant is sold from stock, so its size follows the same rule
+    stock::olcu_yoxla(&tx, variant.mehsul_id, variant.olcu_id)?;
+
+    let barkod = match variant.barkod.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+        Some(barkod) => {
+            barcode::yoxla(barkod)?;
+            barkod.to_string()
+        }
+        None => barcode::daxili_yarat(&tx)?,
+    };
+
+    tx
+        .execute(
+            "INSERT INTO variants (mehsul_id, olcu_id, reng_id, barkod) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![variant.mehsul_id, variant.olcu_id, variant.reng_id, barkod],
+        )
+        .map_err(|e| format!("Variant əlavə edilə bilmədi: {}", e))?;
+
+    let id = tx.last_insert_rowid();
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "variant_elave_et",
+        "variants",
+        Some(id),
+        None,
+        audit::setir(&tx, "variants", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    variant_oxu(&db.conn, id)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn variant_siyahisi(
+    state: State<'_, AppState>,
+    token: String,
+    mehsul_id: i64,
+) -> Result<Vec<Variant>, String> {
+    state.sessions.yoxla(&token)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = db.conn
+        .prepare(&format!(
+            "{} WHERE v.mehsul_id = ?1 ORDER BY sz.id, cl.ad",
+            VARIANT_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let variants = stmt
+        .query_map([mehsul_id], variant_setri)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(variants)
+}
+
+/// Only the barcode of a variant can change; a different size or colour is
+/// a different variant.
+#[tauri::command]
+pub async fn variant_yenile(
+    state: State<'_, AppState>,
+    token: String,
+    id: i64,
+    barkod: String,
+) -> Result<Variant, String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let barkod = barkod.trim();
+    barcode::yoxla(barkod)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "variants", id)?;
+
+    tx
+        .execute(
+            "UPDATE variants SET barkod = ?1 WHERE id = ?2",
+            rusqlite::params![barkod, id],
+        )
+        .map_err(|e| format!("Variant yenilənə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "variant_yenile",
+        "variants",
+        Some(id),
+        evvel,
+        audit::setir(&tx, "variants", id)?,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    variant_oxu(&db.conn, id)
+}
+
+#[tauri::command]
+pub async fn variant_sil(state: State<'_, AppState>, token: String, id: i64) -> Result<(), String> {
+    let session = state.sessions.admin_yoxla(&token)?;
+
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    let tx = db.conn.transaction().map_err(|e| e.to_string())?;
+
+    let evvel = audit::setir(&tx, "variants", id)?;
+
+    tx
+        .execute("DELETE FROM variants WHERE id = ?1", [id])
+        .map_err(|e| format!("Variant silinə bilmədi: {}", e))?;
+
+    audit::qeyde_al(
+        &tx,
+        &session,
+        "variant_sil",
+        "variants",
+        Some(id),
+        evvel,
+        None,
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
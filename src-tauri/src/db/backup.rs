@@ -0,0 +1,225 @@
+//! Copies of the database file, kept in a `backups` folder next to it.
+//!
+//! File names are `<novu>-<YYYYMMDD-HHMMSS>.db`; the prefix tells what made the copy.
+
+use super::connection::Database;
+use super::migrations;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const EL_ILE: &str = "el-ile";
+pub const AVTOMATIK: &str = "avtomatik";
+pub const MIQRASIYA_ONCESI: &str = "miqrasiya-oncesi";
+pub const BERPA_ONCESI: &str = "berpa-oncesi";
+pub const SIFIRLAMA_ONCESI: &str = "sifirlama-oncesi";
+
+// How many copies of each automatic kind are kept; manual backups are never deleted
+const SAXLANILAN: [(&str, usize); 4] = [
+    (AVTOMATIK, 7),
+    (MIQRASIYA_ONCESI, 5),
+    (BERPA_ONCESI, 5),
+    (SIFIRLAMA_ONCESI, 5),
+];
+const YOXLAMA_ARALIGI: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupFile {
+    pub ad: String,
+    /// 'Əl ilə', 'Avtomatik', 'Miqrasiya öncəsi', 'Bərpa öncəsi' or 'Sıfırlama öncəsi'
+    pub novu: String,
+    pub olcu: u64,
+    pub yaradilma_tarixi: String,
+}
+
+/// Outcome of the automatic backup's last run, shown in settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AutoBackupStatus {
+    pub son_yoxlama: Option<String>,
+    /// Why the last run failed; `None` when it succeeded
+    pub xeta: Option<String>,
+}
+
+fn novu(ad: &str) -> &'static str {
+    if ad.starts_with(AVTOMATIK) {
+        "Avtomatik"
+    } else if ad.starts_with(MIQRASIYA_ONCESI) {
+        "Miqrasiya öncəsi"
+    } else if ad.starts_with(BERPA_ONCESI) {
+        "Bərpa öncəsi"
+    } else if ad.starts_with(SIFIRLAMA_ONCESI) {
+        "Sıfırlama öncəsi"
+    } else {
+        "Əl ilə"
+    }
+}
+
+pub fn ehtiyat_qovlugu() -> PathBuf {
+    let mut path = Database::get_db_path();
+    path.pop();
+    path.push("backups");
+    path
+}
+
+/// Writes a consistent copy of the open database to `backups/<ad>-<time>.db`
+/// using SQLite's online backup API, so the app keeps working meanwhile.
+pub fn snapshot_yarat(conn: &Connection, ad: &str) -> Result<PathBuf, String> {
+    let qovluq = ehtiyat_qovlugu();
+    std::fs::create_dir_all(&qovluq).map_err(|e| format!("Ehtiyat qovluğu yaradıla bilmədi: {}", e))?;
+
+    let vaxt = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let yol = qovluq.join(format!("{}-{}.db", ad, vaxt));
+
+    conn.backup(DatabaseName::Main, &yol, None)
+        .map_err(|e| format!("Ehtiyat nüsxəsi yaradıla bilmədi: {}", e))?;
+
+    Ok(yol)
+}
+
+pub fn fayl_melumati(yol: &Path) -> Result<BackupFile, String> {
+    let ad = yol
+        .file_name()
+        .map(|a| a.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let meta = std::fs::metadata(yol).map_err(|e| format!("Ehtiyat faylı oxuna bilmədi: {}", e))?;
+    let tarix: chrono::DateTime<chrono::Local> = meta
+        .modified()
+        .map_err(|e| e.to_string())?
+        .into();
+
+    Ok(BackupFile {
+        novu: novu(&ad).to_string(),
+        ad,
+        olcu: meta.len(),
+        yaradilma_tarixi: tarix.format("%Y-%m-%d %H:%M:%S").to_string(),
+    })
+}
+
+/// All backups, newest first.
+pub fn siyahi() -> Result<Vec<BackupFile>, String> {
+    let qovluq = ehtiyat_qovlugu();
+    if !qovluq.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut fayllar = Vec::new();
+    for entry in std::fs::read_dir(&qovluq).map_err(|e| format!("Ehtiyat qovluğu oxuna bilmədi: {}", e))? {
+        let yol = entry.map_err(|e| e.to_string())?.path();
+        if yol.extension().is_some_and(|e| e == "db") {
+            fayllar.push(fayl_melumati(&yol)?);
+        }
+    }
+
+    // Copies made within the same second fall back to name order
+    fayllar.sort_by(|a, b| {
+        b.yaradilma_tarixi
+            .cmp(&a.yaradilma_tarixi)
+            .then_with(|| b.ad.cmp(&a.ad))
+    });
+
+    Ok(fayllar)
+}
+
+/// Resolves a backup name from `siyahi` to its path, refusing anything outside the folder.
+pub fn fayl_yolu(ad: &str) -> Result<PathBuf, String> {
+    if ad.is_empty() || ad.contains(['/', '\\']) || ad.contains("..") || !ad.ends_with(".db") {
+        return Err("Yanlış ehtiyat faylı adı".to_string());
+    }
+
+    let yol = ehtiyat_qovlugu().join(ad);
+    if !yol.is_file() {
+        return Err(format!("Ehtiyat faylı tapılmadı: {}", ad));
+    }
+
+    Ok(yol)
+}
+
+/// Checks that a backup is intact and not from a newer version of the app.
+pub fn yoxla(yol: &Path) -> Result<(), String> {
+    let conn = Connection::open_with_flags(yol, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Ehtiyat faylı açıla bilmədi: {}", e))?;
+
+    let netice: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| format!("Ehtiyat faylı yoxlanıla bilmədi: {}", e))?;
+    if netice != "ok" {
+        return Err(format!("Ehtiyat faylı zədələnib: {}", netice));
+    }
+
+    let cedvel_var = |ad: &str| -> Result<bool, String> {
+        conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [ad],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())
+    };
+
+    if !cedvel_var("products")? {
+        return Err("Bu fayl mağaza bazasının ehtiyat nüsxəsi deyil".to_string());
+    }
+
+    if cedvel_var("schema_migrations")? {
+        let versiya: i64 = conn
+            .query_row("SELECT COALESCE(MAX(versiya), 0) FROM schema_migrations", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        if versiya > migrations::son_versiya() {
+            return Err(format!(
+                "Ehtiyat nüsxəsi proqramın daha yeni versiyasına aiddir (v{}), əvvəlcə proqramı yeniləyin",
+                versiya
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Keeps the newest `saxla` backups of one kind and deletes the rest.
+fn kohneleri_sil(prefiks: &str, saxla: usize) -> Result<(), String> {
+    let kohneler = siyahi()?
+        .into_iter()
+        .filter(|f| f.ad.starts_with(prefiks))
+        .skip(saxla);
+
+    for fayl in kohneler {
+        std::fs::remove_file(ehtiyat_qovlugu().join(&fayl.ad))
+            .map_err(|e| format!("Köhnə ehtiyat nüsxəsi silinə bilmədi: {}", e))?;
+    }
+
+    Ok(())
+}
+
+fn gunluk_ehtiyat(db: &Mutex<Database>) -> Result<(), String> {
+    // Held throughout so a restore never reads a file that is being deleted
+    let db = db.lock().map_err(|e| e.to_string())?;
+
+    let bu_gun = format!("{}-{}", AVTOMATIK, chrono::Local::now().format("%Y%m%d"));
+    if !siyahi()?.iter().any(|f| f.ad.starts_with(&bu_gun)) {
+        snapshot_yarat(&db.conn, AVTOMATIK)?;
+    }
+
+    // Restores, migrations and resets each leave a full copy behind too
+    for (prefiks, saxla) in SAXLANILAN {
+        kohneleri_sil(prefiks, saxla)?;
+    }
+
+    Ok(())
+}
+
+/// Takes one automatic backup per day in the background and prunes old
+/// automatic copies, checking every hour. The outcome of each run is kept in
+/// `veziyyet`.
+pub fn avtomatik_ehtiyat_baslat(db: Arc<Mutex<Database>>, veziyyet: Arc<Mutex<AutoBackupStatus>>) {
+    std::thread::spawn(move || loop {
+        let netice = gunluk_ehtiyat(&db);
+        if let Ok(mut veziyyet) = veziyyet.lock() {
+            *veziyyet = AutoBackupStatus {
+                son_yoxlama: Some(chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+                xeta: netice.err(),
+            };
+        }
+        std::thread::sleep(YOXLAMA_ARALIGI);
+    });
+}
@@ -0,0 +1,90 @@
+//! Rows a new database starts with. Both first start-up and a catalogue reset
+//! seed from here, so the two cannot drift apart.
+
+use rusqlite::{Connection, Result};
+
+pub const MAGAZA_ADI: &str = "Geyim";
+
+/// Size groups and their sizes, in the order they are shown.
+pub const OLCU_QRUPLARI: [(&str, &[&str]); 3] = [
+    ("Hərf ölçüləri", &["XS", "S", "M", "L", "XL", "XXL", "XXXL"]),
+    ("Şalvar beli", &["28", "29", "30", "31", "32", "33", "34", "36"]),
+    (
+        "Ayaqqabı (EU)",
+        &["38", "39", "40", "41", "42", "43", "44", "45", "46", "47", "48", "49", "50"],
+    ),
+];
+
+/// Categories with the size group their goods are measured in.
+pub const KATEQORIYALAR: [(&str, Option<&str>); 5] = [
+    ("Şalvar", Some("Şalvar beli")),
+    ("Köynək", Some("Hərf ölçüləri")),
+    ("Ayaqabı", Some("Ayaqqabı (EU)")),
+    ("Kostyum", Some("Hərf ölçüləri")),
+    ("Aksesuar", None),
+];
+
+const HERF_SIRASI: [&str; 9] = ["XXS", "XS", "S", "M", "L", "XL", "XXL", "XXXL", "XXXXL"];
+
+pub fn reqemli_olcu(olcu: &str) -> bool {
+    olcu.trim().replace(',', ".").parse::<f64>().is_ok()
+}
+
+/// Where a size sorts in its group until someone reorders it: letter sizes
+/// in wearing order, numbers by value (in tenths, for half sizes).
+pub fn ilkin_sira(olcu: &str) -> i64 {
+    let olcu = olcu.trim();
+    if let Ok(reqem) = olcu.replace(',', ".").parse::<f64>() {
+        return (reqem * 10.0).round() as i64;
+    }
+    let boyuk = olcu.to_uppercase();
+    HERF_SIRASI
+        .iter()
+        .position(|h| *h == boyuk)
+        .unwrap_or(HERF_SIRASI.len()) as i64
+        + 1
+}
+
+pub fn parametrler(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO settings (id, magaza_adi) VALUES (1, ?1)",
+        [MAGAZA_ADI],
+    )?;
+    Ok(())
+}
+
+fn olcu_qrupu_id(conn: &Connection, ad: &str) -> Result<i64> {
+    conn.execute("INSERT OR IGNORE INTO size_groups (ad) VALUES (?1)", [ad])?;
+    conn.query_row("SELECT id FROM size_groups WHERE ad = ?1", [ad], |row| row.get(0))
+}
+
+// A size that already exists keeps the group and place it has
+pub fn olculer(conn: &Connection) -> Result<()> {
+    for (qrup, olculer) in OLCU_QRUPLARI {
+        let qrup_id = olcu_qrupu_id(conn, qrup)?;
+        for olcu in olculer {
+            conn.execute(
+                "INSERT OR IGNORE INTO sizes (olcu, qrup_id, sira) VALUES (?1, ?2, ?3)",
+                rusqlite::params![olcu, qrup_id, ilkin_sira(olcu)],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+pub fn kateqoriyalar(conn: &Connection) -> Result<()> {
+    for (kateqoriya, qrup) in KATEQORIYALAR {
+        let qrup_id = qrup.map(|q| olcu_qrupu_id(conn, q)).transpose()?;
+        conn.execute(
+            "INSERT OR IGNORE INTO categories (ad, olcu_qrupu_id) VALUES (?1, ?2)",
+            rusqlite::params![kateqoriya, qrup_id],
+        )?;
+    }
+    Ok(())
+}
+
+pub fn hamisi(conn: &Connection) -> Result<()> {
+    parametrler(conn)?;
+    olculer(conn)?;
+    kateqoriyalar(conn)
+}
@@ -0,0 +1,913 @@
+//! Ordered schema migrations. Each one runs once, inside its own transaction,
+//! and is recorded in `schema_migrations`. Append new migrations to the end of
+//! `MIGRATIONS` with the next version number; never edit or reorder old ones.
+//!
+//! Migrations up to 35 predate the registry and may find their changes already
+//! applied on older databases, so they check before altering anything.
+
+use super::defaults;
+use super::schema::SCHEMA;
+use crate::auth::password::sifre_hash;
+use rusqlite::{Connection, Result};
+
+pub struct Migration {
+    pub versiya: i64,
+    pub ad: &'static str,
+    pub tetbiq: fn(&Connection) -> Result<()>,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { versiya: 1, ad: "Create base tables", tetbiq: ilkin_sxem },
+    Migration { versiya: 2, ad: "Add musteri_id column to sales", tetbiq: satis_musteri_id },
+    Migration { versiya: 3, ad: "Add debt_payments table", tetbiq: borc_odemeleri },
+    Migration { versiya: 4, ad: "Add returns table", tetbiq: iadeler },
+    Migration { versiya: 5, ad: "Add return_items table", tetbiq: iade_mehsullari },
+    Migration { versiya: 6, ad: "Add settings table", tetbiq: parametrler },
+    Migration { versiya: 7, ad: "Add users table", tetbiq: istifadeciler },
+    Migration { versiya: 8, ad: "Add missing sizes", tetbiq: eksik_olculer },
+    Migration { versiya: 9, ad: "Add baslangic_borcu column to customers table", tetbiq: musteri_baslangic_borcu },
+    Migration { versiya: 10, ad: "Add olculer_aktiv column to settings table", tetbiq: olculer_aktiv },
+    Migration { versiya: 11, ad: "Add qifil_sifresi column to settings table", tetbiq: qifil_sifresi },
+    Migration { versiya: 12, ad: "Add barkod_capinda_magaza_adi column to settings table", tetbiq: barkod_capinda_magaza_adi },
+    Migration { versiya: 13, ad: "Add varsayilan_barkod_yazici column to settings table", tetbiq: varsayilan_barkod_yazici },
+    Migration { versiya: 14, ad: "Add varsayilan_makbuz_yazici column to settings table", tetbiq: varsayilan_makbuz_yazici },
+    Migration { versiya: 15, ad: "Add vahid_alis_qiymeti column to stock_movements table", tetbiq: hereket_vahid_alis_qiymeti },
+    Migration { versiya: 16, ad: "Add toplam_deyeri column to stock_movements table", tetbiq: hereket_toplam_deyeri },
+    Migration { versiya: 17, ad: "Add menfi_stok_icazesi column to settings table", tetbiq: menfi_stok_icazesi },
+    Migration { versiya: 18, ad: "Add cost_layers table", tetbiq: maya_laylari },
+    Migration { versiya: 19, ad: "Add vahid_maya_deyeri column to sale_items table", tetbiq: satis_maya_deyeri },
+    Migration { versiya: 20, ad: "Add vahid_maya_deyeri column to return_items table", tetbiq: iade_maya_deyeri },
+    Migration { versiya: 21, ad: "Add maya_deyeri_usulu column to settings table", tetbiq: maya_deyeri_usulu },
+    Migration { versiya: 22, ad: "Add suppliers table", tetbiq: tedarukculer },
+    Migration { versiya: 23, ad: "Add purchase_orders table", tetbiq: alis_sifarisleri },
+    Migration { versiya: 24, ad: "Add purchase_order_items table", tetbiq: alis_sifarisi_mehsullari },
+    Migration { versiya: 25, ad: "Add sifaris_id column to stock_movements table", tetbiq: hereket_sifaris_id },
+    Migration { versiya: 26, ad: "Add baslangic_borcu column to suppliers table", tetbiq: tedarukcu_baslangic_borcu },
+    Migration { versiya: 27, ad: "Add odenis_usulu column to purchase_orders table", tetbiq: sifaris_odenis_usulu },
+    Migration { versiya: 28, ad: "Add supplier_payments table", tetbiq: tedarukcu_odemeleri },
+    Migration { versiya: 29, ad: "Add sale_payments table", tetbiq: satis_odenisleri },
+    Migration { versiya: 30, ad: "Add shifts table", tetbiq: novbeler },
+    Migration { versiya: 31, ad: "Add novbe_id and istifadeci_id columns to sales, returns and debt_payments", tetbiq: novbe_sutunlari },
+    Migration { versiya: 32, ad: "Closed shifts are read-only and take no new transactions", tetbiq: bagli_novbe_triggerleri },
+    Migration { versiya: 33, ad: "Add audit_log table (append-only)", tetbiq: audit_jurnali },
+    Migration { versiya: 34, ad: "Add sequences table for sale and return numbering", tetbiq: sened_sayaclari },
+    Migration { versiya: 35, ad: "Add document number formats to settings table", tetbiq: sened_nomre_formatlari },
+    Migration { versiya: 36, ad: "Add internal barcode prefix to settings table", tetbiq: barkod_prefiksi },
+    Migration { versiya: 37, ad: "Add variants table with per-size barcodes", tetbiq: variantlar },
+    Migration { versiya: 38, ad: "Key stock, sales, returns and movements by colour", tetbiq: stok_rengleri },
+    Migration { versiya: 39, ad: "Add size_groups and link sizes and categories to them", tetbiq: olcu_qruplari },
+    Migration { versiya: 40, ad: "Add parent_id column to categories table", tetbiq: kateqoriya_agaci },
+    Migration { versiya: 41, ad: "Add brands table and link products to it", tetbiq: markalar },
+    Migration { versiya: 42, ad: "Add promotions table and per-line promotion discounts on sale_items", tetbiq: kampaniyalar },
+];
+
+/// Latest version this build knows about.
+pub fn son_versiya() -> i64 {
+    MIGRATIONS.last().map(|m| m.versiya).unwrap_or(0)
+}
+
+/// Empty in-memory database at the latest schema, for tests.
+#[cfg(test)]
+pub fn test_bazasi() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+    for miqrasiya in MIGRATIONS {
+        (miqrasiya.tetbiq)(&conn).unwrap();
+    }
+    conn
+}
+
+fn cedvel_var(conn: &Connection, cedvel: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [cedvel],
+        |row| row.get(0),
+    )
+}
+
+fn sutun_var(conn: &Connection, cedvel: &str, sutun: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        [cedvel, sutun],
+        |row| row.get(0),
+    )
+}
+
+/// Adds a column unless it is already there; returns whether it was added.
+fn sutun_elave_et(conn: &Connection, cedvel: &str, sutun: &str, tanim: &str) -> Result<bool> {
+    if sutun_var(conn, cedvel, sutun)? {
+        return Ok(false);
+    }
+    conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", cedvel, sutun, tanim), [])?;
+    Ok(true)
+}
+
+fn ilkin_sxem(conn: &Connection) -> Result<()> {
+    conn.execute_batch(SCHEMA)
+}
+
+fn satis_musteri_id(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "sales", "musteri_id", "INTEGER REFERENCES customers(id)")?;
+
+    Ok(())
+}
+
+fn borc_odemeleri(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS debt_payments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            musteri_id INTEGER NOT NULL,
+            mebleg REAL NOT NULL,
+            odenis_usulu TEXT DEFAULT 'Nağd',
+            qeyd TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (musteri_id) REFERENCES customers(id)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_debt_payments_musteri ON debt_payments(musteri_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn iadeler(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS returns (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            iade_nomresi TEXT UNIQUE NOT NULL,
+            satis_id INTEGER NOT NULL,
+            musteri_id INTEGER,
+            toplam_mebleg REAL NOT NULL,
+            sebebi TEXT,
+            qeyd TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (satis_id) REFERENCES sales(id),
+            FOREIGN KEY (musteri_id) REFERENCES customers(id)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_returns_satis ON returns(satis_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn iade_mehsullari(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS return_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            iade_id INTEGER NOT NULL,
+            mehsul_id INTEGER NOT NULL,
+            olcu_id INTEGER NOT NULL,
+            miqdar INTEGER NOT NULL,
+            vahid_qiymeti REAL NOT NULL,
+            toplam_qiymet REAL NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (iade_id) REFERENCES returns(id) ON DELETE CASCADE,
+            FOREIGN KEY (mehsul_id) REFERENCES products(id),
+            FOREIGN KEY (olcu_id) REFERENCES sizes(id)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn parametrler(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            magaza_adi TEXT DEFAULT 'Geyim',
+            logo_yolu TEXT,
+            telefon TEXT,
+            adres TEXT,
+            whatsapp TEXT,
+            instagram TEXT,
+            tiktok TEXT,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Insert default settings if not exists
+    conn.execute(
+        "INSERT OR IGNORE INTO settings (id, magaza_adi) VALUES (1, 'Geyim')",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn istifadeciler(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS users (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ad TEXT NOT NULL,
+            soyad TEXT NOT NULL,
+            istifadeci_adi TEXT UNIQUE NOT NULL,
+            sifre TEXT NOT NULL,
+            rol TEXT NOT NULL DEFAULT 'isci',
+            aktiv INTEGER DEFAULT 1,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Insert default admin user if not exists (password: admin123, stored as bcrypt hash).
+    // Rows created by older versions still hold plaintext and are rehashed on next login.
+    let has_admin: bool = conn.query_row(
+        "SELECT COUNT(*) FROM users WHERE id = 1",
+        [],
+        |row| {
+            let count: i32 = row.get(0)?;
+            Ok(count > 0)
+        },
+    ).unwrap_or(false);
+
+    if !has_admin {
+        let admin_hash = sifre_hash("admin123")
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(e.into()))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO users (id, ad, soyad, istifadeci_adi, sifre, rol)
+             VALUES (1, 'Admin', 'İstifadəçi', 'admin', ?1, 'admin')",
+            [admin_hash],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn eksik_olculer(conn: &Connection) -> Result<()> {
+    let missing_sizes = ["XXXL", "39", "41", "43", "45", "47", "49"];
+    for size in missing_sizes {
+        conn.execute(
+            "INSERT OR IGNORE INTO sizes (olcu) VALUES (?1)",
+            [size],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn musteri_baslangic_borcu(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "customers", "baslangic_borcu", "REAL DEFAULT 0")?;
+
+    Ok(())
+}
+
+fn olculer_aktiv(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "settings", "olculer_aktiv", "INTEGER DEFAULT 1")?;
+
+    Ok(())
+}
+
+fn qifil_sifresi(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "settings", "qifil_sifresi", "TEXT")?;
+
+    Ok(())
+}
+
+fn barkod_capinda_magaza_adi(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "settings", "barkod_capinda_magaza_adi", "INTEGER DEFAULT 0")?;
+
+    Ok(())
+}
+
+fn varsayilan_barkod_yazici(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "settings", "varsayilan_barkod_yazici", "TEXT")?;
+
+    Ok(())
+}
+
+fn varsayilan_makbuz_yazici(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "settings", "varsayilan_makbuz_yazici", "TEXT")?;
+
+    Ok(())
+}
+
+fn hereket_vahid_alis_qiymeti(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "stock_movements", "vahid_alis_qiymeti", "REAL")?;
+
+    Ok(())
+}
+
+fn hereket_toplam_deyeri(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "stock_movements", "toplam_deyeri", "REAL")?;
+
+    Ok(())
+}
+
+fn menfi_stok_icazesi(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "settings", "menfi_stok_icazesi", "INTEGER DEFAULT 0")?;
+
+    Ok(())
+}
+
+fn maya_laylari(conn: &Connection) -> Result<()> {
+    if !cedvel_var(conn, "cost_layers")? {
+        conn.execute_batch(
+            "CREATE TABLE cost_layers (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                mehsul_id INTEGER NOT NULL,
+                olcu_id INTEGER NOT NULL,
+                hereket_id INTEGER,
+                ilkin_miqdar INTEGER NOT NULL,
+                qalan_miqdar INTEGER NOT NULL,
+                vahid_maya REAL NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (mehsul_id) REFERENCES products(id) ON DELETE CASCADE,
+                FOREIGN KEY (olcu_id) REFERENCES sizes(id),
+                FOREIGN KEY (hereket_id) REFERENCES stock_movements(id) ON DELETE SET NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_cost_layers_mehsul ON cost_layers(mehsul_id, olcu_id);",
+        )?;
+
+        // Opening layers for stock on hand, valued at its last recorded receipt
+        conn.execute(
+            "INSERT INTO cost_layers (mehsul_id, olcu_id, ilkin_miqdar, qalan_miqdar, vahid_maya)
+             SELECT s.mehsul_id, s.olcu_id, s.miqdar, s.miqdar,
+                    COALESCE(
+                        (SELECT sm.vahid_alis_qiymeti FROM stock_movements sm
+                         WHERE sm.mehsul_id = s.mehsul_id AND sm.olcu_id = s.olcu_id
+                           AND sm.novu = 'Daxil olma' AND sm.vahid_alis_qiymeti IS NOT NULL
+                         ORDER BY sm.created_at DESC, sm.id DESC LIMIT 1),
+                        p.alis_qiymeti)
+             FROM stock s
+             JOIN products p ON s.mehsul_id = p.id
+             WHERE s.miqdar > 0",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn satis_maya_deyeri(conn: &Connection) -> Result<()> {
+    if sutun_elave_et(conn, "sale_items", "vahid_maya_deyeri", "REAL")? {
+        // Freeze past sales at the purchase price known today
+        conn.execute(
+            "UPDATE sale_items SET vahid_maya_deyeri =
+                (SELECT alis_qiymeti FROM products WHERE products.id = sale_items.mehsul_id)",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn iade_maya_deyeri(conn: &Connection) -> Result<()> {
+    if sutun_elave_et(conn, "return_items", "vahid_maya_deyeri", "REAL")? {
+        conn.execute(
+            "UPDATE return_items SET vahid_maya_deyeri =
+                (SELECT alis_qiymeti FROM products WHERE products.id = return_items.mehsul_id)",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn maya_deyeri_usulu(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "settings", "maya_deyeri_usulu", "TEXT DEFAULT 'FIFO'")?;
+
+    Ok(())
+}
+
+fn tedarukculer(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS suppliers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ad TEXT NOT NULL,
+            elaqe_sexsi TEXT,
+            telefon TEXT,
+            email TEXT,
+            unvan TEXT,
+            qeyd TEXT,
+            aktiv INTEGER DEFAULT 1,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn alis_sifarisleri(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS purchase_orders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            sifaris_nomresi TEXT UNIQUE NOT NULL,
+            tedarukcu_id INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'Açıq',
+            gozlenilen_tarix TEXT,
+            qeyd TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (tedarukcu_id) REFERENCES suppliers(id)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn alis_sifarisi_mehsullari(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS purchase_order_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            sifaris_id INTEGER NOT NULL,
+            mehsul_id INTEGER NOT NULL,
+            olcu_id INTEGER NOT NULL,
+            sifaris_miqdari INTEGER NOT NULL,
+            qebul_miqdari INTEGER NOT NULL DEFAULT 0,
+            vahid_alis_qiymeti REAL NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (sifaris_id) REFERENCES purchase_orders(id) ON DELETE CASCADE,
+            FOREIGN KEY (mehsul_id) REFERENCES products(id),
+            FOREIGN KEY (olcu_id) REFERENCES sizes(id)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn hereket_sifaris_id(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "stock_movements", "sifaris_id", "INTEGER REFERENCES purchase_orders(id)")?;
+
+    Ok(())
+}
+
+fn tedarukcu_baslangic_borcu(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "suppliers", "baslangic_borcu", "REAL DEFAULT 0")?;
+
+    Ok(())
+}
+
+fn sifaris_odenis_usulu(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "purchase_orders", "odenis_usulu", "TEXT DEFAULT 'Nisyə'")?;
+
+    Ok(())
+}
+
+fn tedarukcu_odemeleri(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS supplier_payments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tedarukcu_id INTEGER NOT NULL,
+            sifaris_id INTEGER,
+            mebleg REAL NOT NULL,
+            odenis_usulu TEXT DEFAULT 'Nağd',
+            qeyd TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (tedarukcu_id) REFERENCES suppliers(id),
+            FOREIGN KEY (sifaris_id) REFERENCES purchase_orders(id)
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn satis_odenisleri(conn: &Connection) -> Result<()> {
+    if !cedvel_var(conn, "sale_payments")? {
+        conn.execute_batch(
+            "CREATE TABLE sale_payments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                satis_id INTEGER NOT NULL,
+                odenis_usulu TEXT NOT NULL,
+                mebleg REAL NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (satis_id) REFERENCES sales(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_sale_payments_satis ON sale_payments(satis_id);",
+        )?;
+
+        // Every existing sale was paid with a single tender
+        conn.execute(
+            "INSERT INTO sale_payments (satis_id, odenis_usulu, mebleg, created_at)
+             SELECT id, odenis_usulu, son_mebleg, created_at FROM sales",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn novbeler(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS shifts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            istifadeci_id INTEGER NOT NULL,
+            acilis_meblegi REAL NOT NULL DEFAULT 0,
+            gozlenilen_nagd REAL,
+            sayilan_nagd REAL,
+            status TEXT NOT NULL DEFAULT 'Açıq',
+            qeyd TEXT,
+            acilma_tarixi DATETIME DEFAULT CURRENT_TIMESTAMP,
+            baglanma_tarixi DATETIME,
+            FOREIGN KEY (istifadeci_id) REFERENCES users(id)
+        );
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_shifts_aciq ON shifts(istifadeci_id) WHERE status = 'Açıq';",
+    )?;
+
+    Ok(())
+}
+
+fn novbe_sutunlari(conn: &Connection) -> Result<()> {
+    for table in ["sales", "returns", "debt_payments"] {
+        if !sutun_var(conn, table, "novbe_id")? {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {0} ADD COLUMN novbe_id INTEGER REFERENCES shifts(id);
+                 ALTER TABLE {0} ADD COLUMN istifadeci_id INTEGER REFERENCES users(id);
+                 CREATE INDEX IF NOT EXISTS idx_{0}_novbe ON {0}(novbe_id);",
+                table
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn bagli_novbe_triggerleri(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS trg_shifts_bagli_yenilenmesin
+         BEFORE UPDATE ON shifts
+         WHEN OLD.status = 'Bağlı'
+         BEGIN
+            SELECT RAISE(ABORT, 'Bağlanmış növbə dəyişdirilə bilməz');
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS trg_sales_bagli_novbe
+         BEFORE INSERT ON sales
+         WHEN NEW.novbe_id IS NOT NULL
+          AND (SELECT status FROM shifts WHERE id = NEW.novbe_id) = 'Bağlı'
+         BEGIN
+            SELECT RAISE(ABORT, 'Bağlanmış növbəyə satış yazıla bilməz');
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS trg_returns_bagli_novbe
+         BEFORE INSERT ON returns
+         WHEN NEW.novbe_id IS NOT NULL
+          AND (SELECT status FROM shifts WHERE id = NEW.novbe_id) = 'Bağlı'
+         BEGIN
+            SELECT RAISE(ABORT, 'Bağlanmış növbəyə geri qaytarma yazıla bilməz');
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS trg_debt_payments_bagli_novbe
+         BEFORE INSERT ON debt_payments
+         WHEN NEW.novbe_id IS NOT NULL
+          AND (SELECT status FROM shifts WHERE id = NEW.novbe_id) = 'Bağlı'
+         BEGIN
+            SELECT RAISE(ABORT, 'Bağlanmış növbəyə ödəniş yazıla bilməz');
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS trg_sales_novbe_deyismesin
+         BEFORE UPDATE OF novbe_id ON sales
+         WHEN OLD.novbe_id IS NOT NULL
+          AND (SELECT status FROM shifts WHERE id = OLD.novbe_id) = 'Bağlı'
+         BEGIN
+            SELECT RAISE(ABORT, 'Bağlanmış növbənin satışı dəyişdirilə bilməz');
+         END;",
+    )?;
+
+    Ok(())
+}
+
+fn audit_jurnali(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            istifadeci_id INTEGER,
+            istifadeci_adi TEXT,
+            emr TEXT NOT NULL,
+            obyekt TEXT NOT NULL,
+            obyekt_id INTEGER,
+            evvel TEXT,
+            sonra TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE INDEX IF NOT EXISTS idx_audit_log_tarix ON audit_log(created_at);
+        CREATE INDEX IF NOT EXISTS idx_audit_log_obyekt ON audit_log(obyekt, obyekt_id);
+        CREATE INDEX IF NOT EXISTS idx_audit_log_istifadeci ON audit_log(istifadeci_id);
+
+        CREATE TRIGGER IF NOT EXISTS trg_audit_log_yenilenmesin
+        BEFORE UPDATE ON audit_log
+        BEGIN
+            SELECT RAISE(ABORT, 'Audit jurnalı dəyişdirilə bilməz');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_audit_log_silinmesin
+        BEFORE DELETE ON audit_log
+        BEGIN
+            SELECT RAISE(ABORT, 'Audit jurnalı silinə bilməz');
+        END;",
+    )?;
+
+    Ok(())
+}
+
+fn sened_sayaclari(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sequences (
+            ad TEXT PRIMARY KEY,
+            il INTEGER NOT NULL DEFAULT 0,
+            son_nomre INTEGER NOT NULL DEFAULT 0,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn sened_nomre_formatlari(conn: &Connection) -> Result<()> {
+    for (sutun, standart) in [
+        ("satis_nomre_formati", "S-{IL}-{NOMRE:6}"),
+        ("iade_nomre_formati", "I-{IL}-{NOMRE:6}"),
+    ] {
+        sutun_elave_et(conn, "settings", sutun, &format!("TEXT DEFAULT '{}'", standart))?;
+    }
+
+    Ok(())
+}
+
+fn barkod_prefiksi(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "settings", "barkod_prefiksi", "TEXT DEFAULT '200'")?;
+
+    Ok(())
+}
+
+fn variantlar(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS variants (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            mehsul_id INTEGER NOT NULL,
+            olcu_id INTEGER NOT NULL,
+            reng_id INTEGER,
+            barkod TEXT UNIQUE NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (mehsul_id) REFERENCES products(id) ON DELETE CASCADE,
+            FOREIGN KEY (olcu_id) REFERENCES sizes(id),
+            FOREIGN KEY (reng_id) REFERENCES colors(id)
+        );
+
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_variants_mehsul_olcu_reng
+            ON variants(mehsul_id, olcu_id, IFNULL(reng_id, 0));
+
+        CREATE TRIGGER IF NOT EXISTS trg_variants_barkod_mehsulda
+        BEFORE INSERT ON variants
+        WHEN EXISTS (SELECT 1 FROM products WHERE barkod = NEW.barkod)
+        BEGIN
+            SELECT RAISE(ABORT, 'Bu barkod artıq bir məhsula verilib');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_variants_barkod_mehsulda_yenile
+        BEFORE UPDATE OF barkod ON variants
+        WHEN EXISTS (SELECT 1 FROM products WHERE barkod = NEW.barkod)
+        BEGIN
+            SELECT RAISE(ABORT, 'Bu barkod artıq bir məhsula verilib');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_products_barkod_variantda
+        BEFORE INSERT ON products
+        WHEN EXISTS (SELECT 1 FROM variants WHERE barkod = NEW.barkod)
+        BEGIN
+            SELECT RAISE(ABORT, 'Bu barkod artıq bir varianta verilib');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_products_barkod_variantda_yenile
+        BEFORE UPDATE OF barkod ON products
+        WHEN EXISTS (SELECT 1 FROM variants WHERE barkod = NEW.barkod)
+        BEGIN
+            SELECT RAISE(ABORT, 'Bu barkod artıq bir varianta verilib');
+        END;",
+    )?;
+
+    Ok(())
+}
+
+// Free-text products.reng becomes a colour id on every row keyed by product
+// and size. Names are matched case-insensitively in Rust, since SQLite's
+// LOWER() leaves letters like 'Ə' and 'Ğ' alone; unknown ones become colours.
+fn stok_rengleri(conn: &Connection) -> Result<()> {
+    for cedvel in ["sale_items", "return_items", "stock_movements", "cost_layers", "purchase_order_items"] {
+        sutun_elave_et(conn, cedvel, "reng_id", "INTEGER REFERENCES colors(id)")?;
+    }
+
+    // The (mehsul_id, olcu_id) constraint is part of the table, so it is rebuilt
+    conn.execute_batch(
+        "CREATE TABLE stock_yeni (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            mehsul_id INTEGER NOT NULL,
+            olcu_id INTEGER NOT NULL,
+            reng_id INTEGER,
+            miqdar INTEGER DEFAULT 0,
+            minimum_miqdar INTEGER DEFAULT 5,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (mehsul_id) REFERENCES products(id) ON DELETE CASCADE,
+            FOREIGN KEY (olcu_id) REFERENCES sizes(id),
+            FOREIGN KEY (reng_id) REFERENCES colors(id)
+        );
+        INSERT INTO stock_yeni (id, mehsul_id, olcu_id, miqdar, minimum_miqdar, created_at, updated_at)
+            SELECT id, mehsul_id, olcu_id, miqdar, minimum_miqdar, created_at, updated_at FROM stock;
+        DROP TABLE stock;
+        ALTER TABLE stock_yeni RENAME TO stock;
+        CREATE INDEX IF NOT EXISTS idx_stock_mehsul ON stock(mehsul_id);
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_stock_mehsul_olcu_reng
+            ON stock(mehsul_id, olcu_id, IFNULL(reng_id, 0));
+        DROP INDEX IF EXISTS idx_cost_layers_mehsul;
+        CREATE INDEX IF NOT EXISTS idx_cost_layers_mehsul ON cost_layers(mehsul_id, olcu_id, reng_id);",
+    )?;
+
+    let mut rengler: Vec<(i64, String)> = conn
+        .prepare("SELECT id, ad FROM colors")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+
+    let mehsullar: Vec<(i64, String)> = conn
+        .prepare("SELECT id, TRIM(reng) FROM products WHERE TRIM(IFNULL(reng, '')) <> ''")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+
+    for (mehsul_id, reng) in mehsullar {
+        let acar = reng.to_lowercase();
+        let reng_id = match rengler.iter().find(|(_, ad)| ad.trim().to_lowercase() == acar) {
+            Some((id, _)) => *id,
+            None => {
+                conn.execute("INSERT INTO colors (ad) VALUES (?1)", [&reng])?;
+                let id = conn.last_insert_rowid();
+                rengler.push((id, reng.clone()));
+                id
+            }
+        };
+
+        for cedvel in ["stock", "sale_items", "return_items", "stock_movements", "cost_layers", "purchase_order_items"] {
+            conn.execute(
+                &format!("UPDATE {} SET reng_id = ?1 WHERE mehsul_id = ?2", cedvel),
+                [reng_id, mehsul_id],
+            )?;
+        }
+        conn.execute(
+            "UPDATE products SET reng = (SELECT ad FROM colors WHERE id = ?1) WHERE id = ?2",
+            [reng_id, mehsul_id],
+        )?;
+    }
+
+    Ok(())
+}
+
+// Sizes are put into groups, and the seeded categories get the group their
+// goods are measured in. A numeric size goes to whichever of the waist and
+// shoe groups stocks it most; one nobody stocks is taken for a shoe size.
+fn olcu_qruplari(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS size_groups (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ad TEXT NOT NULL UNIQUE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );",
+    )?;
+    sutun_elave_et(conn, "sizes", "qrup_id", "INTEGER REFERENCES size_groups(id) ON DELETE SET NULL")?;
+    sutun_elave_et(conn, "sizes", "sira", "INTEGER NOT NULL DEFAULT 0")?;
+    sutun_elave_et(conn, "categories", "olcu_qrupu_id", "INTEGER REFERENCES size_groups(id) ON DELETE SET NULL")?;
+
+    let qrup = |ad: &str| -> Result<i64> {
+        conn.execute("INSERT OR IGNORE INTO size_groups (ad) VALUES (?1)", [ad])?;
+        conn.query_row("SELECT id FROM size_groups WHERE ad = ?1", [ad], |row| row.get(0))
+    };
+    let herf = qrup("Hərf ölçüləri")?;
+    let bel = qrup("Şalvar beli")?;
+    let ayaqqabi = qrup("Ayaqqabı (EU)")?;
+
+    let kateqoriyalar: Vec<(i64, String)> = conn
+        .prepare("SELECT id, ad FROM categories")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+    for (id, ad) in kateqoriyalar {
+        let qrup_id = match ad.trim().to_lowercase().as_str() {
+            "köynək" | "kostyum" => herf,
+            "şalvar" => bel,
+            "ayaqabı" | "ayaqqabı" => ayaqqabi,
+            _ => continue,
+        };
+        conn.execute("UPDATE categories SET olcu_qrupu_id = ?1 WHERE id = ?2", [qrup_id, id])?;
+    }
+
+    let olculer: Vec<(i64, String)> = conn
+        .prepare("SELECT id, olcu FROM sizes")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+    for (id, olcu) in olculer {
+        let qrup_id = if defaults::reqemli_olcu(&olcu) {
+            let (bel_sayi, ayaqqabi_sayi): (i64, i64) = conn.query_row(
+                "SELECT COALESCE(SUM(c.olcu_qrupu_id = ?2), 0), COALESCE(SUM(c.olcu_qrupu_id = ?3), 0)
+                 FROM stock s
+                 JOIN products p ON p.id = s.mehsul_id
+                 JOIN categories c ON c.id = p.kateqoriya_id
+                 WHERE s.olcu_id = ?1",
+                [id, bel, ayaqqabi],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            if bel_sayi > ayaqqabi_sayi { bel } else { ayaqqabi }
+        } else {
+            herf
+        };
+        conn.execute(
+            "UPDATE sizes SET qrup_id = ?1, sira = ?2 WHERE id = ?3",
+            [qrup_id, defaults::ilkin_sira(&olcu), id],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn kateqoriya_agaci(conn: &Connection) -> Result<()> {
+    sutun_elave_et(conn, "categories", "parent_id", "INTEGER REFERENCES categories(id)")?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_categories_parent ON categories(parent_id)", [])?;
+
+    Ok(())
+}
+
+// Free-text products.marka becomes a brand. Spellings that differ only in
+// case or surrounding spaces are one brand, named after its most common
+// spelling, and every product is rewritten to that name.
+fn markalar(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS brands (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ad TEXT NOT NULL UNIQUE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );",
+    )?;
+    sutun_elave_et(conn, "products", "marka_id", "INTEGER REFERENCES brands(id)")?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_products_marka ON products(marka_id)", [])?;
+
+    let yazilislar: Vec<(String, i64)> = conn
+        .prepare(
+            "SELECT TRIM(marka), COUNT(*) FROM products
+             WHERE TRIM(IFNULL(marka, '')) <> ''
+             GROUP BY TRIM(marka)
+             ORDER BY COUNT(*) DESC, TRIM(marka)",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+
+    // The first spelling seen for a key is its most common one
+    let mut markalar: Vec<(String, String)> = Vec::new();
+    for (yazilis, _) in &yazilislar {
+        let acar = yazilis.to_lowercase();
+        if !markalar.iter().any(|(a, _)| *a == acar) {
+            markalar.push((acar, yazilis.clone()));
+        }
+    }
+
+    for (acar, ad) in &markalar {
+        conn.execute("INSERT OR IGNORE INTO brands (ad) VALUES (?1)", [ad])?;
+        let marka_id: i64 = conn.query_row("SELECT id FROM brands WHERE ad = ?1", [ad], |row| row.get(0))?;
+        for (yazilis, _) in yazilislar.iter().filter(|(y, _)| y.to_lowercase() == *acar) {
+            conn.execute(
+                "UPDATE products SET marka = ?1, marka_id = ?2 WHERE TRIM(marka) = ?3",
+                rusqlite::params![ad, marka_id, yazilis],
+            )?;
+        }
+    }
+    conn.execute("UPDATE products SET marka = NULL WHERE TRIM(marka) = ''", [])?;
+
+    Ok(())
+}
+
+// Promotions, and the discount each sale line got from one. A line's
+// toplam_qiymet is what was paid for it, after that discount.
+fn kampaniyalar(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS promotions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ad TEXT NOT NULL,
+            novu TEXT NOT NULL CHECK (novu IN ('faiz', 'sabit', 'bogo', 'uce_iki', 'deste')),
+            deyer REAL NOT NULL DEFAULT 0,
+            deste_miqdari INTEGER,
+            mehsul_id INTEGER REFERENCES products(id),
+            kateqoriya_id INTEGER REFERENCES categories(id),
+            marka_id INTEGER REFERENCES brands(id),
+            olcu_id INTEGER REFERENCES sizes(id),
+            baslangic_tarixi TEXT,
+            bitis_tarixi TEXT,
+            aktiv INTEGER NOT NULL DEFAULT 1,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );",
+    )?;
+    sutun_elave_et(conn, "sale_items", "endirim", "REAL NOT NULL DEFAULT 0")?;
+    sutun_elave_et(conn, "sale_items", "kampaniya_id", "INTEGER REFERENCES promotions(id)")?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_sale_items_kampaniya ON sale_items(kampaniya_id)", [])?;
+
+    Ok(())
+}
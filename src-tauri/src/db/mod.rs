@@ -1,2 +1,5 @@
+pub mod backup;
 pub mod connection;
+pub mod defaults;
+pub mod migrations;
 pub mod schema;
@@ -1,3 +1,5 @@
+/// Tables from the first release. Everything added later lives in
+/// `migrations.rs`, which runs this as migration 1.
 pub const SCHEMA: &str = r#"
 -- Müştərilər (Müşteriler)
 CREATE TABLE IF NOT EXISTS customers (
@@ -6,7 +8,6 @@ CREATE TABLE IF NOT EXISTS customers (
     soyad TEXT NOT NULL,
     telefon TEXT NOT NULL UNIQUE,
     qeyd TEXT,
-    baslangic_borcu REAL DEFAULT 0,
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
     updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
 );
@@ -108,80 +109,11 @@ CREATE TABLE IF NOT EXISTS stock_movements (
     FOREIGN KEY (olcu_id) REFERENCES sizes(id)
 );
 
--- Nisyə Ödəmələri
-CREATE TABLE IF NOT EXISTS debt_payments (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    musteri_id INTEGER NOT NULL,
-    mebleg REAL NOT NULL,
-    odenis_usulu TEXT DEFAULT 'Nağd',
-    qeyd TEXT,
-    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-    FOREIGN KEY (musteri_id) REFERENCES customers(id)
-);
-
--- Geri Qaytarmalər (Geri Qaytarmalar)
-CREATE TABLE IF NOT EXISTS returns (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    iade_nomresi TEXT UNIQUE NOT NULL,
-    satis_id INTEGER NOT NULL,
-    musteri_id INTEGER,
-    toplam_mebleg REAL NOT NULL,
-    sebebi TEXT,
-    qeyd TEXT,
-    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-    FOREIGN KEY (satis_id) REFERENCES sales(id),
-    FOREIGN KEY (musteri_id) REFERENCES customers(id)
-);
-
--- Geri Qaytarma Detalları
-CREATE TABLE IF NOT EXISTS return_items (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    iade_id INTEGER NOT NULL,
-    mehsul_id INTEGER NOT NULL,
-    olcu_id INTEGER NOT NULL,
-    miqdar INTEGER NOT NULL,
-    vahid_qiymeti REAL NOT NULL,
-    toplam_qiymet REAL NOT NULL,
-    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-    FOREIGN KEY (iade_id) REFERENCES returns(id) ON DELETE CASCADE,
-    FOREIGN KEY (mehsul_id) REFERENCES products(id),
-    FOREIGN KEY (olcu_id) REFERENCES sizes(id)
-);
-
 -- İndexlər (Performans üçün)
 CREATE INDEX IF NOT EXISTS idx_products_barkod ON products(barkod);
 CREATE INDEX IF NOT EXISTS idx_stock_mehsul ON stock(mehsul_id);
 CREATE INDEX IF NOT EXISTS idx_sales_date ON sales(created_at);
 CREATE INDEX IF NOT EXISTS idx_sale_items_satis ON sale_items(satis_id);
-CREATE INDEX IF NOT EXISTS idx_debt_payments_musteri ON debt_payments(musteri_id);
-CREATE INDEX IF NOT EXISTS idx_returns_satis ON returns(satis_id);
-
--- Mağaza Parametrləri
-CREATE TABLE IF NOT EXISTS settings (
-    id INTEGER PRIMARY KEY CHECK (id = 1),
-    magaza_adi TEXT DEFAULT 'Geyim',
-    logo_yolu TEXT,
-    telefon TEXT,
-    adres TEXT,
-    whatsapp TEXT,
-    instagram TEXT,
-    tiktok TEXT,
-    olculer_aktiv INTEGER DEFAULT 1,
-    qifil_sifresi TEXT,
-    barkod_capinda_magaza_adi INTEGER DEFAULT 0,
-    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
-);
-
--- Default settings insert
-INSERT OR IGNORE INTO settings (id, magaza_adi) VALUES (1, 'Geyim');
-
--- Default kateqoriyalar (UNIQUE constraint sayəsində dublikat əlavə olmayacaq)
-INSERT OR IGNORE INTO categories (ad) VALUES
-    ('Şalvar'),
-    ('Köynək'),
-    ('Ayaqabı'),
-    ('Kostyum'),
-    ('Aksesuar');
 
 -- Default rənglər
 INSERT OR IGNORE INTO colors (ad, kod) VALUES
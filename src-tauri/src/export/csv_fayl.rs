@@ -0,0 +1,51 @@
+use super::Senet;
+use std::path::Path;
+
+// The BOM makes Excel read the file as UTF-8 instead of the system code page
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// One block of records. Blocks are joined with an empty line, which the csv
+/// writer itself would print as `""`.
+fn blok(setirler: Vec<Vec<String>>) -> Result<Vec<u8>, String> {
+    let xeta = |e: String| format!("CSV faylı yazıla bilmədi: {}", e);
+
+    let mut yazici = csv::WriterBuilder::new().flexible(true).from_writer(Vec::new());
+    for setir in setirler {
+        yazici.write_record(&setir).map_err(|e| xeta(e.to_string()))?;
+    }
+    yazici.into_inner().map_err(|e| xeta(e.to_string()))
+}
+
+pub fn yaz(senet: &Senet, yol: &Path) -> Result<(), String> {
+    let mut bloklar = Vec::new();
+
+    let mut basliq: Vec<Vec<String>> = senet.magaza.iter().map(|s| vec![s.clone()]).collect();
+    basliq.push(vec![senet.basliq.clone()]);
+    basliq.push(vec![senet.yaradilma_tarixi.clone()]);
+    bloklar.push(blok(basliq)?);
+
+    if !senet.xulase.is_empty() {
+        bloklar.push(blok(
+            senet
+                .xulase
+                .iter()
+                .map(|(ad, deyer)| vec![ad.clone(), deyer.metn()])
+                .collect(),
+        )?);
+    }
+
+    for cedvel in &senet.cedveller {
+        let mut setirler = Vec::new();
+        if let Some(ad) = &cedvel.ad {
+            setirler.push(vec![ad.clone()]);
+        }
+        setirler.push(cedvel.sutunlar.clone());
+        setirler.extend(cedvel.setirler.iter().map(|s| s.iter().map(|x| x.metn()).collect()));
+        bloklar.push(blok(setirler)?);
+    }
+
+    let mut mezmun = BOM.to_vec();
+    mezmun.extend(bloklar.join(&b"\n"[..]));
+
+    std::fs::write(yol, mezmun).map_err(|e| format!("Fayl yazıla bilmədi: {}", e))
+}
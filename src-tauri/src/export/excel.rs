@@ -0,0 +1,92 @@
+use super::{Senet, Xana};
+use rust_xlsxwriter::{Format, FormatBorder, Workbook, Worksheet, XlsxError};
+use std::path::Path;
+
+fn xana_yaz(vereq: &mut Worksheet, setir: u32, sutun: u16, xana: &Xana, mebleg: &Format) -> Result<(), XlsxError> {
+    match xana {
+        Xana::Bos => {}
+        Xana::Metn(s) => {
+            vereq.write_string(setir, sutun, s)?;
+        }
+        Xana::Tam(i) => {
+            vereq.write_number(setir, sutun, *i as f64)?;
+        }
+        Xana::Reqem(f) => {
+            vereq.write_number_with_format(setir, sutun, *f, mebleg)?;
+        }
+    }
+    Ok(())
+}
+
+fn vereq_doldur(senet: &Senet, vereq: &mut Worksheet) -> Result<(), XlsxError> {
+    let qalin = Format::new().set_bold();
+    let basliq = Format::new().set_bold().set_font_size(14);
+    let mebleg = Format::new().set_num_format("#,##0.00");
+    let sutun_basligi = Format::new()
+        .set_bold()
+        .set_background_color("#E5E7EB")
+        .set_border_bottom(FormatBorder::Thin);
+
+    let mut setir: u32 = 0;
+
+    for s in &senet.magaza {
+        vereq.write_string_with_format(setir, 0, s, &qalin)?;
+        setir += 1;
+    }
+    vereq.write_string_with_format(setir, 0, &senet.basliq, &basliq)?;
+    setir += 1;
+    vereq.write_string(setir, 0, &senet.yaradilma_tarixi)?;
+    setir += 2;
+
+    for (ad, deyer) in &senet.xulase {
+        vereq.write_string_with_format(setir, 0, ad, &qalin)?;
+        xana_yaz(vereq, setir, 1, deyer, &mebleg)?;
+        setir += 1;
+    }
+    if !senet.xulase.is_empty() {
+        setir += 1;
+    }
+
+    for cedvel in &senet.cedveller {
+        if let Some(ad) = &cedvel.ad {
+            vereq.write_string_with_format(setir, 0, ad, &qalin)?;
+            setir += 1;
+        }
+        for (i, sutun) in cedvel.sutunlar.iter().enumerate() {
+            vereq.write_string_with_format(setir, i as u16, sutun, &sutun_basligi)?;
+        }
+        setir += 1;
+        for s in &cedvel.setirler {
+            for (i, xana) in s.iter().enumerate() {
+                xana_yaz(vereq, setir, i as u16, xana, &mebleg)?;
+            }
+            setir += 1;
+        }
+        setir += 1;
+    }
+
+    vereq.autofit();
+    Ok(())
+}
+
+pub fn yaz(senet: &Senet, yol: &Path) -> Result<(), String> {
+    let mut kitab = Workbook::new();
+    let vereq = kitab.add_worksheet();
+
+    // Sheet names are limited to 31 characters and a few symbols
+    let ad: String = senet
+        .basliq
+        .chars()
+        .filter(|c| !"[]:*?/\\".contains(*c))
+        .take(31)
+        .collect();
+
+    vereq
+        .set_name(ad)
+        .and_then(|v| vereq_doldur(senet, v))
+        .map_err(|e| format!("Excel faylı hazırlana bilmədi: {}", e))?;
+
+    kitab
+        .save(yol)
+        .map_err(|e| format!("Excel faylı yazıla bilmədi: {}", e))
+}
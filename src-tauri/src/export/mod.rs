@@ -0,0 +1,296 @@
+//! Writing reports to files. Any serialisable report is first flattened into a
+//! `Senet` (store header, summary lines and tables), which the CSV, Excel and
+//! PDF writers then lay out.
+
+mod csv_fayl;
+mod excel;
+mod pdf;
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+    Pdf,
+}
+
+impl ExportFormat {
+    fn uzanti(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Xana {
+    Bos,
+    Metn(String),
+    Tam(i64),
+    Reqem(f64),
+}
+
+impl Xana {
+    fn deyerden(deyer: &Value) -> Self {
+        match deyer {
+            Value::Null => Xana::Bos,
+            Value::Bool(b) => Xana::Metn(if *b { "Bəli" } else { "Xeyr" }.to_string()),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => Xana::Tam(i),
+                None => Xana::Reqem(n.as_f64().unwrap_or_default()),
+            },
+            Value::String(s) => Xana::Metn(s.clone()),
+            diger => Xana::Metn(diger.to_string()),
+        }
+    }
+
+    /// Text as printed in CSV and PDF; amounts get two decimals.
+    pub fn metn(&self) -> String {
+        match self {
+            Xana::Bos => String::new(),
+            Xana::Metn(s) => s.clone(),
+            Xana::Tam(i) => i.to_string(),
+            Xana::Reqem(f) => format!("{:.2}", f),
+        }
+    }
+
+    fn reqemdir(&self) -> bool {
+        matches!(self, Xana::Tam(_) | Xana::Reqem(_))
+    }
+}
+
+pub struct Cedvel {
+    pub ad: Option<String>,
+    pub sutunlar: Vec<String>,
+    pub setirler: Vec<Vec<Xana>>,
+}
+
+pub struct Senet {
+    pub basliq: String,
+    /// Store name, phone and address from settings, whichever are filled in
+    pub magaza: Vec<String>,
+    pub yaradilma_tarixi: String,
+    pub xulase: Vec<(String, Xana)>,
+    pub cedveller: Vec<Cedvel>,
+}
+
+// Internal keys mean nothing to the reader of a printed report
+fn gizlidir(acar: &str) -> bool {
+    acar == "id" || acar.ends_with("_id")
+}
+
+/// Column and field captions for the report structs.
+fn etiket(acar: &str) -> String {
+    let etiket = match acar {
+        "tarix" => "Tarix",
+        "ay" => "Ay",
+        "baslangic_tarix" => "Başlanğıc tarix",
+        "bitis_tarix" => "Bitiş tarixi",
+        "created_at" => "Tarix",
+        "satis_nomresi" => "Satış nömrəsi",
+        "satis_sayi" => "Satış sayı",
+        "mehsul_sayi" => "Məhsul sayı",
+        "mehsul_adi" => "Məhsul",
+        "barkod" => "Barkod",
+        "olcu" => "Ölçü",
+        "reng" => "Rəng",
+        "kateqoriya_adi" => "Kateqoriya",
+        "marka" => "Marka",
+        "kampaniya" => "Kampaniya",
+        "miqdar" => "Miqdar",
+        "minimum_miqdar" => "Minimum miqdar",
+        "hazirki_stok" => "Hazırkı stok",
+        "toplam_mehsul" => "Məhsul sayı",
+        "toplam_stok" => "Stok miqdarı",
+        "alis_qiymeti" => "Alış qiyməti",
+        "satis_qiymeti" => "Satış qiyməti",
+        "vahid_qiymet" => "Vahid qiymət",
+        "toplam_mebleg" => "Toplam məbləğ",
+        "son_mebleg" => "Son məbləğ",
+        "net_mebleg" => "Net məbləğ",
+        "endirim" => "Endirim",
+        "toplam_endirim" => "Toplam endirim",
+        "toplam_alis" => "Toplam alış",
+        "toplam_satis" => "Toplam satış",
+        "qazanc" => "Qazanc",
+        "toplam_qazanc" => "Toplam qazanc",
+        "net_qazanc" => "Net qazanc",
+        "potensial_qazanc" => "Potensial qazanc",
+        "alis_deyeri" => "Alış dəyəri",
+        "satis_deyeri" => "Satış dəyəri",
+        "toplam_deyeri" => "Toplam dəyər",
+        "deger" => "Dəyər",
+        "toplam_alis_miqdar" => "Alış miqdarı",
+        "toplam_satis_miqdar" => "Satış miqdarı",
+        "toplam_alis_deyeri" => "Alış dəyəri",
+        "toplam_satis_deyeri" => "Satış dəyəri",
+        "ortalama_qazanc_vahid" => "Vahid üzrə orta qazanc",
+        "umumi_alis_miqdar" => "Ümumi alış miqdarı",
+        "umumi_satis_miqdar" => "Ümumi satış miqdarı",
+        "umumi_alis_deyeri" => "Ümumi alış dəyəri",
+        "umumi_satis_deyeri" => "Ümumi satış dəyəri",
+        "umumi_qazanc" => "Ümumi qazanc",
+        "ortalama_qazanc_faizi" => "Orta qazanc faizi",
+        "satis_miqdar" => "Satış miqdarı",
+        "maya_deyeri" => "Maya dəyəri",
+        "qazanc_faizi" => "Qazanc faizi",
+        "brutto_satis" => "Endirimsiz satış",
+        "net_satis" => "Net satış",
+        "endirim_faizi" => "Endirim faizi",
+        "toplam_net_satis" => "Toplam net satış",
+        "umumi_satis" => "Ümumi satış",
+        "kampaniya_payi" => "Kampaniyaların payı (%)",
+        "stok_miqdari" => "Stok miqdarı",
+        "stok_alis_deyeri" => "Stokun alış dəyəri",
+        "stok_satis_deyeri" => "Stokun satış dəyəri",
+        "toplam_stok_alis_deyeri" => "Stokun ümumi alış dəyəri",
+        "toplam_stok_satis_deyeri" => "Stokun ümumi satış dəyəri",
+        "odenis_usulu" => "Ödəniş üsulu",
+        "iade_durumu" => "Qaytarma",
+        "novu" => "Növü",
+        "qeyd" => "Qeyd",
+        "items" => "Məhsullar",
+        "novbe" => "Növbə",
+        "istifadeci_adi" => "Kassir",
+        "status" => "Status",
+        "acilis_meblegi" => "Açılış məbləği",
+        "acilma_tarixi" => "Açılma tarixi",
+        "baglanma_tarixi" => "Bağlanma tarixi",
+        "iade_sayi" => "Qaytarma sayı",
+        "toplam_iade" => "Toplam qaytarma",
+        "nagd_qaytarma" => "Nağd qaytarma",
+        "odenisler" => "Ödənişlər",
+        "satis_meblegi" => "Satış məbləği",
+        "borc_odenisi" => "Borc ödənişi",
+        "cem" => "Cəm",
+        "gozlenilen_nagd" => "Gözlənilən nağd",
+        "sayilan_nagd" => "Sayılan nağd",
+        "ferq" => "Fərq",
+        diger => {
+            let mut metn = diger.replace('_', " ");
+            if let Some(ilk) = metn.get(..1) {
+                metn.replace_range(..1, &ilk.to_uppercase());
+            }
+            return metn;
+        }
+    };
+    etiket.to_string()
+}
+
+fn cedvel_qur(ad: Option<String>, elementler: &[Value]) -> Cedvel {
+    let mut acarlar: Vec<&str> = Vec::new();
+    for element in elementler {
+        if let Value::Object(obyekt) = element {
+            for acar in obyekt.keys() {
+                if !gizlidir(acar) && !acarlar.contains(&acar.as_str()) {
+                    acarlar.push(acar);
+                }
+            }
+        }
+    }
+
+    // A list of plain values becomes a single column
+    if acarlar.is_empty() {
+        return Cedvel {
+            ad,
+            sutunlar: vec!["Dəyər".to_string()],
+            setirler: elementler.iter().map(|e| vec![Xana::deyerden(e)]).collect(),
+        };
+    }
+
+    Cedvel {
+        ad,
+        sutunlar: acarlar.iter().map(|a| etiket(a)).collect(),
+        setirler: elementler
+            .iter()
+            .map(|e| {
+                acarlar
+                    .iter()
+                    .map(|a| e.get(*a).map(Xana::deyerden).unwrap_or(Xana::Bos))
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+// Scalars go to the summary, lists become tables, nested objects are walked
+// with their caption as a prefix.
+fn obyekti_ac(obyekt: &Map<String, Value>, prefiks: Option<&str>, senet: &mut Senet) {
+    for (acar, deyer) in obyekt {
+        if gizlidir(acar) {
+            continue;
+        }
+        let ad = match prefiks {
+            Some(p) => format!("{} / {}", p, etiket(acar)),
+            None => etiket(acar),
+        };
+        match deyer {
+            Value::Object(ic) => obyekti_ac(ic, Some(&ad), senet),
+            Value::Array(elementler) => senet.cedveller.push(cedvel_qur(Some(ad), elementler)),
+            diger => senet.xulase.push((ad, Xana::deyerden(diger))),
+        }
+    }
+}
+
+fn magaza_basligi(conn: &Connection) -> Result<Vec<String>, String> {
+    let setir: Option<[Option<String>; 3]> = conn
+        .query_row(
+            "SELECT magaza_adi, telefon, adres FROM settings WHERE id = 1",
+            [],
+            |row| Ok([row.get(0)?, row.get(1)?, row.get(2)?]),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    Ok(setir
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect())
+}
+
+impl Senet {
+    pub fn hazirla<T: Serialize>(conn: &Connection, basliq: &str, hesabat: &T) -> Result<Self, String> {
+        let mut senet = Senet {
+            basliq: basliq.to_string(),
+            magaza: magaza_basligi(conn)?,
+            yaradilma_tarixi: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            xulase: Vec::new(),
+            cedveller: Vec::new(),
+        };
+
+        match serde_json::to_value(hesabat).map_err(|e| e.to_string())? {
+            Value::Array(elementler) => senet.cedveller.push(cedvel_qur(None, &elementler)),
+            Value::Object(obyekt) => obyekti_ac(&obyekt, None, &mut senet),
+            diger => senet.xulase.push((basliq.to_string(), Xana::deyerden(&diger))),
+        }
+
+        Ok(senet)
+    }
+
+    /// Writes the document and returns the path used; the format's extension
+    /// is added when the chosen name has none.
+    pub fn yaz(&self, format: ExportFormat, yol: &Path) -> Result<PathBuf, String> {
+        let mut yol = yol.to_path_buf();
+        if yol.extension().is_none() {
+            yol.set_extension(format.uzanti());
+        }
+
+        match format {
+            ExportFormat::Csv => csv_fayl::yaz(self, &yol)?,
+            ExportFormat::Xlsx => excel::yaz(self, &yol)?,
+            ExportFormat::Pdf => pdf::yaz(self, &yol)?,
+        }
+
+        Ok(yol)
+    }
+}
@@ -0,0 +1,216 @@
+use super::{Cedvel, Senet};
+use printpdf::{IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point};
+use std::fs::File;
+use std::io::{BufWriter, Cursor};
+use std::path::Path;
+
+// Built-in PDF fonts have no 'ə', so a Unicode font is embedded
+const SRIFT: &[u8] = include_bytes!("../../fonts/DejaVuSansCondensed.ttf");
+
+const KENAR: f32 = 15.0;
+const SETIR_HUNDURLUYU: f32 = 5.0;
+const SRIFT_OLCUSU: f32 = 8.0;
+const SUTUN_ARALIGI: f32 = 2.0;
+const MAKS_SIMVOL: usize = 40;
+
+// Average glyph width of the condensed font, in mm per point of font size
+const SIMVOL_ENI: f32 = 0.52 * 0.3528;
+
+// Wide tables are printed on landscape pages
+const DIK_SEHIFE_MAKS_SUTUN: usize = 6;
+
+fn metn_eni(metn: &str, olcu: f32) -> f32 {
+    metn.chars().count() as f32 * olcu * SIMVOL_ENI
+}
+
+fn qisalt(metn: &str, en: f32, olcu: f32) -> String {
+    let maks = (en / (olcu * SIMVOL_ENI)).floor().max(1.0) as usize;
+    if metn.chars().count() <= maks {
+        return metn.to_string();
+    }
+    let mut qisa: String = metn.chars().take(maks.saturating_sub(1)).collect();
+    qisa.push('…');
+    qisa
+}
+
+struct Yazici {
+    doc: PdfDocumentReference,
+    srift: IndirectFontRef,
+    qat: PdfLayerReference,
+    en: f32,
+    hundurluk: f32,
+    y: f32,
+    sehife: usize,
+}
+
+impl Yazici {
+    fn yeni_sehife(&mut self) {
+        let (sehife, qat) = self.doc.add_page(Mm(self.en), Mm(self.hundurluk), "Qat");
+        self.qat = self.doc.get_page(sehife).get_layer(qat);
+        self.sehife += 1;
+        self.y = self.hundurluk - KENAR;
+        self.alt_bilgi();
+    }
+
+    fn alt_bilgi(&self) {
+        let metn = self.sehife.to_string();
+        let x = self.en - KENAR - metn_eni(&metn, SRIFT_OLCUSU);
+        self.qat.use_text(metn, SRIFT_OLCUSU, Mm(x), Mm(KENAR / 2.0), &self.srift);
+    }
+
+    /// Starts a new page when fewer than `setir` lines fit on this one.
+    fn yer_ayir(&mut self, setir: usize) {
+        if self.y - setir as f32 * SETIR_HUNDURLUYU < KENAR {
+            self.yeni_sehife();
+        }
+    }
+
+    fn yaz(&self, metn: &str, olcu: f32, x: f32) {
+        self.qat.use_text(metn, olcu, Mm(x), Mm(self.y), &self.srift);
+    }
+
+    fn xett(&self, y: f32) {
+        self.qat.add_line(Line {
+            points: vec![
+                (Point::new(Mm(KENAR), Mm(y)), false),
+                (Point::new(Mm(self.en - KENAR), Mm(y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    fn sutun_enleri(&self, cedvel: &Cedvel) -> Vec<f32> {
+        let simvollar: Vec<usize> = (0..cedvel.sutunlar.len())
+            .map(|i| {
+                cedvel
+                    .setirler
+                    .iter()
+                    .filter_map(|s| s.get(i))
+                    .map(|x| x.metn().chars().count())
+                    .chain(std::iter::once(cedvel.sutunlar[i].chars().count()))
+                    .max()
+                    .unwrap_or(1)
+                    .clamp(3, MAKS_SIMVOL)
+            })
+            .collect();
+
+        let cem: usize = simvollar.iter().sum();
+        let movcud = self.en - 2.0 * KENAR - SUTUN_ARALIGI * (simvollar.len().saturating_sub(1)) as f32;
+        simvollar
+            .iter()
+            .map(|s| movcud * *s as f32 / cem.max(1) as f32)
+            .collect()
+    }
+
+    fn setir_yaz(&mut self, xanalar: &[(String, bool)], enler: &[f32]) {
+        let mut x = KENAR;
+        for ((metn, saga), en) in xanalar.iter().zip(enler) {
+            let metn = qisalt(metn, *en, SRIFT_OLCUSU);
+            // Numbers line up on the right edge of their column
+            let sol = if *saga { x + en - metn_eni(&metn, SRIFT_OLCUSU) } else { x };
+            self.yaz(&metn, SRIFT_OLCUSU, sol);
+            x += en + SUTUN_ARALIGI;
+        }
+        self.y -= SETIR_HUNDURLUYU;
+    }
+
+    fn cedvel_yaz(&mut self, cedvel: &Cedvel) {
+        let enler = self.sutun_enleri(cedvel);
+        let basliqlar: Vec<(String, bool)> = cedvel.sutunlar.iter().map(|s| (s.clone(), false)).collect();
+
+        self.yer_ayir(3);
+        if let Some(ad) = &cedvel.ad {
+            self.yaz(ad, 10.0, KENAR);
+            self.y -= SETIR_HUNDURLUYU + 1.0;
+        }
+
+        let basliq_yaz = |y: &mut Self| {
+            y.setir_yaz(&basliqlar, &enler);
+            y.xett(y.y + SETIR_HUNDURLUYU - 1.5);
+        };
+        basliq_yaz(self);
+
+        if cedvel.setirler.is_empty() {
+            self.yaz("Məlumat yoxdur", SRIFT_OLCUSU, KENAR);
+            self.y -= SETIR_HUNDURLUYU;
+        }
+
+        for setir in &cedvel.setirler {
+            if self.y - SETIR_HUNDURLUYU < KENAR {
+                self.yeni_sehife();
+                basliq_yaz(self);
+            }
+            let xanalar: Vec<(String, bool)> = setir.iter().map(|x| (x.metn(), x.reqemdir())).collect();
+            self.setir_yaz(&xanalar, &enler);
+        }
+
+        self.y -= SETIR_HUNDURLUYU;
+    }
+}
+
+pub fn yaz(senet: &Senet, yol: &Path) -> Result<(), String> {
+    let genis = senet
+        .cedveller
+        .iter()
+        .any(|c| c.sutunlar.len() > DIK_SEHIFE_MAKS_SUTUN);
+    let (en, hundurluk) = if genis { (297.0, 210.0) } else { (210.0, 297.0) };
+
+    let (doc, sehife, qat) = PdfDocument::new(&senet.basliq, Mm(en), Mm(hundurluk), "Qat");
+    let srift = doc
+        .add_external_font(Cursor::new(SRIFT))
+        .map_err(|e| format!("PDF şrifti yüklənə bilmədi: {}", e))?;
+    let qat = doc.get_page(sehife).get_layer(qat);
+
+    let mut yazici = Yazici {
+        doc,
+        srift,
+        qat,
+        en,
+        hundurluk,
+        y: hundurluk - KENAR,
+        sehife: 1,
+    };
+    yazici.alt_bilgi();
+
+    // Store header
+    for (i, setir) in senet.magaza.iter().enumerate() {
+        let olcu = if i == 0 { 14.0 } else { 9.0 };
+        yazici.yaz(setir, olcu, KENAR);
+        yazici.y -= if i == 0 { 7.0 } else { SETIR_HUNDURLUYU };
+    }
+    yazici.y -= 3.0;
+    yazici.yaz(&senet.basliq, 12.0, KENAR);
+    let tarix_x = en - KENAR - metn_eni(&senet.yaradilma_tarixi, 9.0);
+    yazici.yaz(&senet.yaradilma_tarixi, 9.0, tarix_x);
+    yazici.y -= 3.0;
+    yazici.xett(yazici.y);
+    yazici.y -= SETIR_HUNDURLUYU + 2.0;
+
+    // Summary as label / value pairs
+    let etiket_eni = senet
+        .xulase
+        .iter()
+        .map(|(ad, _)| metn_eni(ad, 9.0))
+        .fold(0.0, f32::max)
+        + 6.0;
+    for (ad, deyer) in &senet.xulase {
+        yazici.yer_ayir(1);
+        yazici.yaz(ad, 9.0, KENAR);
+        yazici.yaz(&deyer.metn(), 9.0, KENAR + etiket_eni);
+        yazici.y -= SETIR_HUNDURLUYU;
+    }
+    if !senet.xulase.is_empty() {
+        yazici.y -= SETIR_HUNDURLUYU;
+    }
+
+    for cedvel in &senet.cedveller {
+        yazici.cedvel_yaz(cedvel);
+    }
+
+    let fayl = File::create(yol).map_err(|e| format!("Fayl yazıla bilmədi: {}", e))?;
+    yazici
+        .doc
+        .save(&mut BufWriter::new(fayl))
+        .map_err(|e| format!("PDF faylı yazıla bilmədi: {}", e))
+}
+
@@ -0,0 +1,95 @@
+//! Reading spreadsheets (CSV and Excel) into plain rows of text, so importers
+//! only deal with strings and row numbers.
+
+pub mod products;
+
+use calamine::{open_workbook_auto, Reader};
+use std::path::Path;
+
+pub struct Cedvel {
+    /// Line of the header row, the first non-blank one
+    pub basliq_setri: usize,
+    pub basliqlar: Vec<String>,
+    /// Data rows with their 1-based line in the file. Blank rows are left out.
+    pub setirler: Vec<(usize, Vec<String>)>,
+}
+
+pub fn oxu(yol: &Path) -> Result<Cedvel, String> {
+    let uzanti = yol
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+
+    let xam = match uzanti.as_str() {
+        "csv" | "txt" => csv_oxu(yol)?,
+        "xlsx" | "xlsm" | "xls" | "ods" => excel_oxu(yol)?,
+        _ => return Err("Yalnız CSV və Excel (xlsx, xls, ods) faylları dəstəklənir".to_string()),
+    };
+
+    let mut setirler = xam
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| (i + 1, s))
+        .filter(|(_, s)| s.iter().any(|x| !x.is_empty()));
+
+    let (basliq_setri, basliqlar) = setirler.next().ok_or_else(|| "Fayl boşdur".to_string())?;
+    let setirler = setirler.collect();
+
+    Ok(Cedvel { basliq_setri, basliqlar, setirler })
+}
+
+// Excel in Azerbaijani locale saves CSV with ';', so pick whichever separator
+// the header line uses most.
+fn ayirici(metn: &[u8]) -> u8 {
+    let basliq = metn.split(|b| *b == b'\n').next().unwrap_or_default();
+    [b';', b'\t', b',']
+        .into_iter()
+        .max_by_key(|a| basliq.iter().filter(|b| *b == a).count())
+        .filter(|a| basliq.contains(a))
+        .unwrap_or(b',')
+}
+
+fn csv_oxu(yol: &Path) -> Result<Vec<Vec<String>>, String> {
+    let mezmun = std::fs::read(yol).map_err(|e| format!("Fayl oxuna bilmədi: {}", e))?;
+    let mezmun = mezmun.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&mezmun);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(ayirici(mezmun))
+        .from_reader(mezmun);
+
+    reader
+        .byte_records()
+        .map(|r| {
+            r.map(|r| {
+                r.iter()
+                    .map(|x| String::from_utf8_lossy(x).trim().to_string())
+                    .collect()
+            })
+            .map_err(|e| format!("CSV faylı oxuna bilmədi: {}", e))
+        })
+        .collect()
+}
+
+fn excel_oxu(yol: &Path) -> Result<Vec<Vec<String>>, String> {
+    let mut kitab = open_workbook_auto(yol).map_err(|e| format!("Excel faylı açıla bilmədi: {}", e))?;
+
+    let vereq = kitab
+        .worksheet_range_at(0)
+        .ok_or_else(|| "Excel faylında vərəq yoxdur".to_string())?
+        .map_err(|e| format!("Excel vərəqi oxuna bilmədi: {}", e))?;
+
+    // The range starts at the first used cell; pad so line numbers match Excel's
+    let bos_setirler = vereq.start().map(|(setir, _)| setir as usize).unwrap_or(0);
+
+    Ok(std::iter::repeat_with(Vec::new)
+        .take(bos_setirler)
+        .chain(
+            vereq
+                .rows()
+                .map(|s| s.iter().map(|x| x.to_string().trim().to_string()).collect()),
+        )
+        .collect())
+}
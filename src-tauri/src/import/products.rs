@@ -0,0 +1,675 @@
+//! Product import. Each row is one product, matched by barcode, with the
+//! quantity to add for every size column.
+//!
+//! `planla` only reads the database and describes what the file would change;
+//! `tetbiq_et` writes a plan that has no errors.
+
+use super::Cedvel;
+use crate::audit;
+use crate::barcode;
+use crate::commands::stock::stok_daxil_et;
+use crate::commands::{brand, size, variant};
+use crate::auth::session::Session;
+use crate::models::CreateStock;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sahe {
+    Barkod,
+    Ad,
+    Kateqoriya,
+    Marka,
+    Reng,
+    AlisQiymeti,
+    SatisQiymeti,
+    Tesvir,
+}
+
+impl Sahe {
+    const HAMISI: [Sahe; 8] = [
+        Sahe::Barkod,
+        Sahe::Ad,
+        Sahe::Kateqoriya,
+        Sahe::Marka,
+        Sahe::Reng,
+        Sahe::AlisQiymeti,
+        Sahe::SatisQiymeti,
+        Sahe::Tesvir,
+    ];
+
+    fn ad(self) -> &'static str {
+        match self {
+            Sahe::Barkod => "barkod",
+            Sahe::Ad => "ad",
+            Sahe::Kateqoriya => "kateqoriya",
+            Sahe::Marka => "marka",
+            Sahe::Reng => "reng",
+            Sahe::AlisQiymeti => "alis_qiymeti",
+            Sahe::SatisQiymeti => "satis_qiymeti",
+            Sahe::Tesvir => "tesvir",
+        }
+    }
+
+    /// Header spellings accepted for the column, after `basliq_normallasdir`.
+    fn basliqlar(self) -> &'static [&'static str] {
+        match self {
+            Sahe::Barkod => &["barkod", "barcode"],
+            Sahe::Ad => &["ad", "mehsul", "mehsul_adi", "name"],
+            Sahe::Kateqoriya => &["kateqoriya", "category"],
+            Sahe::Marka => &["marka", "brand"],
+            Sahe::Reng => &["reng", "color", "colour"],
+            Sahe::AlisQiymeti => &["alis_qiymeti", "alis", "purchase_price", "cost"],
+            Sahe::SatisQiymeti => &["satis_qiymeti", "satis", "sale_price", "price"],
+            Sahe::Tesvir => &["tesvir", "description"],
+        }
+    }
+}
+
+enum Sutun {
+    Sahe(Sahe),
+    Olcu { id: i64, olcu: String },
+}
+
+/// "Alış qiyməti" -> "alis_qiymeti"
+fn basliq_normallasdir(basliq: &str) -> String {
+    basliq
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'ə' => 'e',
+            'ı' => 'i',
+            'ş' => 's',
+            'ç' => 'c',
+            'ğ' => 'g',
+            'ö' => 'o',
+            'ü' => 'u',
+            ' ' | '-' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportError {
+    pub setir: usize,
+    pub sutun: Option<String>,
+    pub xeta: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub sahe: String,
+    pub evvel: Option<String>,
+    pub sonra: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockChange {
+    pub olcu: String,
+    pub evvelki_miqdar: i32,
+    pub elave_miqdar: i32,
+    pub yeni_miqdar: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportChange {
+    pub setir: usize,
+    pub barkod: String,
+    pub ad: String,
+    /// 'Yeni', 'Yenilənir' or 'Dəyişiklik yoxdur'
+    pub emeliyyat: String,
+    pub sahe_deyisiklikleri: Vec<FieldChange>,
+    pub stok: Vec<StockChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub setir_sayi: usize,
+    pub yeni_mehsul: usize,
+    pub yenilenen_mehsul: usize,
+    pub elave_edilen_stok: i64,
+    pub deyisiklikler: Vec<ImportChange>,
+    pub xetalar: Vec<ImportError>,
+    /// False for a dry run, or when errors stopped the import
+    pub tetbiq_edildi: bool,
+}
+
+/// Product fields as they will be after the import.
+#[derive(Debug, Clone, PartialEq)]
+struct Deyerler {
+    ad: String,
+    kateqoriya: Option<(i64, String)>,
+    marka: Option<String>,
+    reng: Option<String>,
+    alis_qiymeti: f64,
+    satis_qiymeti: f64,
+    tesvir: Option<String>,
+}
+
+struct PlanSetri {
+    setir: usize,
+    barkod: String,
+    mehsul_id: Option<i64>,
+    evvel: Option<Deyerler>,
+    sonra: Deyerler,
+    // Stock is kept per colour, so quantities land on the product's colour
+    reng_id: Option<i64>,
+    // (olcu_id, olcu, current quantity, quantity to add)
+    stok: Vec<(i64, String, i32, i32)>,
+}
+
+pub struct Plan {
+    setir_sayi: usize,
+    setirler: Vec<PlanSetri>,
+    xetalar: Vec<ImportError>,
+}
+
+/// Lower-cased name -> (id, name as stored)
+fn ad_xeritesi(conn: &Connection, sql: &str) -> Result<HashMap<String, (i64, String)>, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let xerite = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(id, ad)| (ad.trim().to_lowercase(), (id, ad)))
+        .collect();
+    Ok(xerite)
+}
+
+fn movcud_mehsul(conn: &Connection, barkod: &str) -> Result<Option<(i64, Deyerler)>, String> {
+    conn.query_row(
+        "SELECT p.id, p.ad, p.kateqoriya_id, c.ad, p.marka, p.reng, p.alis_qiymeti, p.satis_qiymeti, p.tesvir
+         FROM products p
+         LEFT JOIN categories c ON p.kateqoriya_id = c.id
+         WHERE p.barkod = ?1",
+        [barkod],
+        |row| {
+            let kateqoriya_id: Option<i64> = row.get(2)?;
+            let kateqoriya_adi: Option<String> = row.get(3)?;
+            Ok((
+                row.get(0)?,
+                Deyerler {
+                    ad: row.get(1)?,
+                    kateqoriya: kateqoriya_id.zip(kateqoriya_adi),
+                    marka: row.get(4)?,
+                    reng: row.get(5)?,
+                    alis_qiymeti: row.get(6)?,
+                    satis_qiymeti: row.get(7)?,
+                    tesvir: row.get(8)?,
+                },
+            ))
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn qiymet_oxu(deyer: &str) -> Result<f64, String> {
+    match deyer.replace(',', ".").parse::<f64>() {
+        Ok(q) if q.is_finite() && q >= 0.0 => Ok(q),
+        _ => Err(format!("Yanlış qiymət: {}", deyer)),
+    }
+}
+
+// Excel may hand back whole numbers as "3.0"
+fn miqdar_oxu(deyer: &str) -> Result<i32, String> {
+    match deyer.replace(',', ".").parse::<f64>() {
+        Ok(m) if m >= 0.0 && m.fract() == 0.0 && m <= i32::MAX as f64 => Ok(m as i32),
+        _ => Err(format!("Miqdar mənfi olmayan tam ədəd olmalıdır: {}", deyer)),
+    }
+}
+
+fn xeta_yaz(xetalar: &mut Vec<ImportError>, setir: usize, sutun: &str, xeta: String) {
+    xetalar.push(ImportError { setir, sutun: Some(sutun.to_string()), xeta });
+}
+
+fn bos_deyilse(deyer: &str) -> Option<String> {
+    (!deyer.is_empty()).then(|| deyer.to_string())
+}
+
+/// Checks every row against the database and works out what the import
+/// would do, without writing anything.
+pub fn planla(conn: &Connection, cedvel: &Cedvel) -> Result<Plan, String> {
+    let olculer = ad_xeritesi(conn, "SELECT id, olcu FROM sizes")?;
+    let kateqoriyalar = ad_xeritesi(conn, "SELECT id, ad FROM categories")?;
+    let rengler = ad_xeritesi(conn, "SELECT id, ad FROM colors")?;
+    let markalar = ad_xeritesi(conn, "SELECT id, ad FROM brands")?;
+    let olcu_qruplari: HashMap<i64, Option<i64>> = conn
+        .prepare("SELECT id, qrup_id FROM sizes")
+        .and_then(|mut stmt| stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect())
+        .map_err(|e| e.to_string())?;
+
+    let mut xetalar = Vec::new();
+    let mut sutunlar = Vec::new();
+
+    for basliq in &cedvel.basliqlar {
+        let normal = basliq_normallasdir(basliq);
+        let sutun = if normal.is_empty() {
+            None
+        } else if let Some(sahe) = Sahe::HAMISI.into_iter().find(|s| s.basliqlar().contains(&normal.as_str())) {
+            Some(Sutun::Sahe(sahe))
+        } else if let Some((id, olcu)) = olculer.get(&basliq.trim().to_lowercase()) {
+            Some(Sutun::Olcu { id: *id, olcu: olcu.clone() })
+        } else {
+            xeta_yaz(&mut xetalar, cedvel.basliq_setri, basliq, format!("Naməlum sütun və ya ölçü: {}", basliq));
+            None
+        };
+        sutunlar.push(sutun);
+    }
+
+    for sahe in [Sahe::Barkod, Sahe::Ad, Sahe::AlisQiymeti, Sahe::SatisQiymeti] {
+        if !sutunlar.iter().any(|s| matches!(s, Some(Sutun::Sahe(x)) if *x == sahe)) {
+            xeta_yaz(&mut xetalar, cedvel.basliq_setri, sahe.ad(), format!("Məcburi sütun tapılmadı: {}", sahe.ad()));
+        }
+    }
+
+    if !xetalar.is_empty() {
+        return Ok(Plan { setir_sayi: cedvel.setirler.len(), setirler: Vec::new(), xetalar });
+    }
+
+    let mut setirler = Vec::new();
+    let mut gorulen_barkodlar: HashMap<String, usize> = HashMap::new();
+
+    for (setir, xanalar) in &cedvel.setirler {
+        let setir = *setir;
+        let xeta_sayi = xetalar.len();
+
+        let mut saheler: HashMap<&'static str, &str> = HashMap::new();
+        let mut miqdarlar = Vec::new();
+        for (i, sutun) in sutunlar.iter().enumerate() {
+            let deyer = xanalar.get(i).map(String::as_str).unwrap_or("");
+            match sutun {
+                Some(Sutun::Sahe(sahe)) => {
+                    saheler.insert(sahe.ad(), deyer);
+                }
+                Some(Sutun::Olcu { id, olcu }) if !deyer.is_empty() => match miqdar_oxu(deyer) {
+                    Ok(0) => {}
+                    Ok(m) => miqdarlar.push((*id, olcu.clone(), m)),
+                    Err(e) => xeta_yaz(&mut xetalar, setir, olcu, e),
+                },
+                _ => {}
+            }
+        }
+        let sahe = |s: Sahe| saheler.get(s.ad()).copied().unwrap_or("");
+
+        let barkod = sahe(Sahe::Barkod).to_string();
+        if barkod.is_empty() {
+            xeta_yaz(&mut xetalar, setir, "barkod", "Barkod boş ola bilməz".to_string());
+            continue;
+        }
+        if let Some(evvelki) = gorulen_barkodlar.insert(barkod.clone(), setir) {
+            xeta_yaz(&mut xetalar, setir, "barkod", format!("Bu barkod faylda təkrarlanır ({}. sətirdə də var)", evvelki));
+            continue;
+        }
+
+        let movcud = movcud_mehsul(conn, &barkod)?;
+        let evvel = movcud.as_ref().map(|(_, d)| d);
+
+        // Rows matching an existing product keep its code as it is
+        if movcud.is_none() {
+            if let Err(xeta) = barcode::yoxla(&barkod) {
+                xeta_yaz(&mut xetalar, setir, "barkod", xeta);
+                continue;
+            }
+            if variant::barkodla_tap(conn, &barkod)?.is_some() {
+                xeta_yaz(&mut xetalar, setir, "barkod", "Bu barkod artıq bir varianta verilib".to_string());
+                continue;
+            }
+        }
+
+        // Empty cells keep what an existing product already has
+        let ad = match (bos_deyilse(sahe(Sahe::Ad)), evvel) {
+            (Some(ad), _) => ad,
+            (None, Some(e)) => e.ad.clone(),
+            (None, None) => {
+                xeta_yaz(&mut xetalar, setir, "ad", "Məhsul adı boş ola bilməz".to_string());
+                String::new()
+            }
+        };
+
+        let mut qiymet = |s: Sahe, movcud: Option<f64>| -> f64 {
+            let deyer = sahe(s);
+            match (deyer.is_empty(), movcud) {
+                (true, Some(q)) => q,
+                (true, None) => {
+                    xeta_yaz(&mut xetalar, setir, s.ad(), "Qiymət boş ola bilməz".to_string());
+                    0.0
+                }
+                (false, _) => qiymet_oxu(deyer).unwrap_or_else(|e| {
+                    xeta_yaz(&mut xetalar, setir, s.ad(), e);
+                    0.0
+                }),
+            }
+        };
+        let alis_qiymeti = qiymet(Sahe::AlisQiymeti, evvel.map(|e| e.alis_qiymeti));
+        let satis_qiymeti = qiymet(Sahe::SatisQiymeti, evvel.map(|e| e.satis_qiymeti));
+
+        let kateqoriya = match bos_deyilse(sahe(Sahe::Kateqoriya)) {
+            Some(k) => kateqoriyalar.get(&k.to_lowercase()).cloned().or_else(|| {
+                xeta_yaz(&mut xetalar, setir, "kateqoriya", format!("Kateqoriya tapılmadı: {}", k));
+                None
+            }),
+            None => evvel.and_then(|e| e.kateqoriya.clone()),
+        };
+
+        // Quantities go into stock, where the category's size group applies
+        if let Some(qrup_id) = size::kateqoriya_olcu_qrupu(conn, kateqoriya.as_ref().map(|(id, _)| *id))? {
+            for (olcu_id, olcu, _) in &miqdarlar {
+                if olcu_qruplari.get(olcu_id).copied().flatten() != Some(qrup_id) {
+                    xeta_yaz(&mut xetalar, setir, olcu, "Bu ölçü kateqoriyanın ölçü qrupuna aid deyil".to_string());
+                }
+            }
+        }
+
+        let reng = match bos_deyilse(sahe(Sahe::Reng)) {
+            Some(r) => match rengler.get(&r.to_lowercase()) {
+                Some((_, ad)) => Some(ad.clone()),
+                None => {
+                    xeta_yaz(&mut xetalar, setir, "reng", format!("Rəng tapılmadı: {}", r));
+                    None
+                }
+            },
+            None => evvel.and_then(|e| e.reng.clone()),
+        };
+
+        // A known brand keeps its stored spelling; a new one is created on import
+        let marka = match bos_deyilse(sahe(Sahe::Marka)) {
+            Some(m) => Some(markalar.get(&m.to_lowercase()).map(|(_, ad)| ad.clone()).unwrap_or(m)),
+            None => evvel.and_then(|e| e.marka.clone()),
+        };
+        let tesvir = bos_deyilse(sahe(Sahe::Tesvir)).or_else(|| evvel.and_then(|e| e.tesvir.clone()));
+
+        if xetalar.len() > xeta_sayi {
+            continue;
+        }
+
+        let reng_id = reng.as_ref().and_then(|r| rengler.get(&r.to_lowercase())).map(|(id, _)| *id);
+        let mehsul_id = movcud.as_ref().map(|(id, _)| *id);
+        let mut stok = Vec::new();
+        for (olcu_id, olcu, elave) in miqdarlar {
+            let cari: i32 = match mehsul_id {
+                Some(id) => conn
+                    .query_row(
+                        "SELECT miqdar FROM stock WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3",
+                        rusqlite::params![id, olcu_id, reng_id],
+                        |row| row.get(0),
+                    )
+                    .optional()
+                    .map_err(|e| e.to_string())?
+                    .unwrap_or(0),
+                None => 0,
+            };
+            stok.push((olcu_id, olcu, cari, elave));
+        }
+
+        setirler.push(PlanSetri {
+            setir,
+            barkod,
+            mehsul_id,
+            evvel: movcud.map(|(_, d)| d),
+            sonra: Deyerler { ad, kateqoriya, marka, reng, alis_qiymeti, satis_qiymeti, tesvir },
+            reng_id,
+            stok,
+        });
+    }
+
+    Ok(Plan { setir_sayi: cedvel.setirler.len(), setirler, xetalar })
+}
+
+fn sahe_deyisiklikleri(evvel: Option<&Deyerler>, sonra: &Deyerler) -> Vec<FieldChange> {
+    let metn = |d: &Deyerler| -> [(&'static str, Option<String>); 7] {
+        [
+            ("ad", Some(d.ad.clone())),
+            ("kateqoriya", d.kateqoriya.as_ref().map(|(_, ad)| ad.clone())),
+            ("marka", d.marka.clone()),
+            ("reng", d.reng.clone()),
+            ("alis_qiymeti", Some(format!("{:.2}", d.alis_qiymeti))),
+            ("satis_qiymeti", Some(format!("{:.2}", d.satis_qiymeti))),
+            ("tesvir", d.tesvir.clone()),
+        ]
+    };
+
+    let evveller = evvel.map(metn);
+    metn(sonra)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, (sahe, yeni))| {
+            let kohne = evveller.as_ref().and_then(|e| e[i].1.clone());
+            (kohne != yeni).then(|| FieldChange { sahe: sahe.to_string(), evvel: kohne, sonra: yeni })
+        })
+        .collect()
+}
+
+impl Plan {
+    pub fn hesabat(&self, tetbiq_edildi: bool) -> ImportReport {
+        let deyisiklikler: Vec<ImportChange> = self
+            .setirler
+            .iter()
+            .map(|s| {
+                let sahe_deyisiklikleri = sahe_deyisiklikleri(s.evvel.as_ref(), &s.sonra);
+                let emeliyyat = if s.mehsul_id.is_none() {
+                    "Yeni"
+                } else if sahe_deyisiklikleri.is_empty() && s.stok.is_empty() {
+                    "Dəyişiklik yoxdur"
+                } else {
+                    "Yenilənir"
+                };
+                ImportChange {
+                    setir: s.setir,
+                    barkod: s.barkod.clone(),
+                    ad: s.sonra.ad.clone(),
+                    emeliyyat: emeliyyat.to_string(),
+                    sahe_deyisiklikleri,
+                    stok: s
+                        .stok
+                        .iter()
+                        .map(|(_, olcu, cari, elave)| StockChange {
+                            olcu: olcu.clone(),
+                            evvelki_miqdar: *cari,
+                            elave_miqdar: *elave,
+                            yeni_miqdar: cari + elave,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        ImportReport {
+            setir_sayi: self.setir_sayi,
+            yeni_mehsul: deyisiklikler.iter().filter(|d| d.emeliyyat == "Yeni").count(),
+            yenilenen_mehsul: deyisiklikler.iter().filter(|d| d.emeliyyat == "Yenilənir").count(),
+            elave_edilen_stok: self
+                .setirler
+                .iter()
+                .flat_map(|s| s.stok.iter().map(|(_, _, _, elave)| *elave as i64))
+                .sum(),
+            deyisiklikler,
+            xetalar: self.xetalar.clone(),
+            tetbiq_edildi,
+        }
+    }
+
+    pub fn xetasizdir(&self) -> bool {
+        self.xetalar.is_empty()
+    }
+
+    /// Writes the plan. Call inside a transaction; stops at the first database error.
+    pub fn tetbiq_et(&self, conn: &Connection, session: &Session) -> Result<(), String> {
+        for s in &self.setirler {
+            let d = &s.sonra;
+            let kateqoriya_id = d.kateqoriya.as_ref().map(|(id, _)| *id);
+            let (marka_id, marka) = brand::marka_hell_et(conn, session, "mehsul_idxal_et", None, d.marka.as_deref())
+                .map_err(|e| format!("{}. sətir: {}", s.setir, e))?;
+
+            let mehsul_id = match s.mehsul_id {
+                Some(id) => {
+                    if s.evvel.as_ref() != Some(d) {
+                        let evvel = audit::setir(conn, "products", id)?;
+                        conn.execute(
+                            "UPDATE products
+                             SET ad = ?1, kateqoriya_id = ?2, reng = ?3, marka = ?4, marka_id = ?5,
+                                 alis_qiymeti = ?6, satis_qiymeti = ?7, tesvir = ?8,
+                                 updated_at = CURRENT_TIMESTAMP
+                             WHERE id = ?9",
+                            rusqlite::params![d.ad, kateqoriya_id, d.reng, marka, marka_id, d.alis_qiymeti, d.satis_qiymeti, d.tesvir, id],
+                        )
+                        .map_err(|e| format!("{}. sətir: məhsul yenilənə bilmədi: {}", s.setir, e))?;
+
+                        audit::qeyde_al(
+                            conn,
+                            session,
+                            "mehsul_idxal_et",
+                            "products",
+                            Some(id),
+                            evvel,
+                            audit::setir(conn, "products", id)?,
+                        )?;
+                    }
+                    id
+                }
+                None => {
+                    conn.execute(
+                        "INSERT INTO products (barkod, ad, kateqoriya_id, reng, marka, marka_id, alis_qiymeti, satis_qiymeti, tesvir)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                        rusqlite::params![s.barkod, d.ad, kateqoriya_id, d.reng, marka, marka_id, d.alis_qiymeti, d.satis_qiymeti, d.tesvir],
+                    )
+                    .map_err(|e| format!("{}. sətir: məhsul əlavə edilə bilmədi: {}", s.setir, e))?;
+                    let id = conn.last_insert_rowid();
+
+                    audit::qeyde_al(
+                        conn,
+                        session,
+                        "mehsul_idxal_et",
+                        "products",
+                        Some(id),
+                        None,
+                        audit::setir(conn, "products", id)?,
+                    )?;
+                    id
+                }
+            };
+
+            for (olcu_id, _, _, miqdar) in &s.stok {
+                let stok = CreateStock {
+                    mehsul_id,
+                    olcu_id: *olcu_id,
+                    reng_id: s.reng_id,
+                    miqdar: *miqdar,
+                    minimum_miqdar: None,
+                };
+                stok_daxil_et(conn, session, "mehsul_idxal_et", &stok)
+                    .map_err(|e| format!("{}. sətir: {}", s.setir, e))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::test_bazasi;
+
+    fn cedvel(basliqlar: &[&str], setirler: &[&[&str]]) -> Cedvel {
+        Cedvel {
+            basliq_setri: 1,
+            basliqlar: basliqlar.iter().map(|b| b.to_string()).collect(),
+            setirler: setirler
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (i + 2, s.iter().map(|x| x.to_string()).collect()))
+                .collect(),
+        }
+    }
+
+    fn xetalar(plan: &Plan) -> Vec<(usize, String)> {
+        plan.xetalar
+            .iter()
+            .map(|x| (x.setir, x.sutun.clone().unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn bilinmeyen_ve_catismayan_sutunlar_basliq_setrinde_gosterilir() {
+        let conn = test_bazasi();
+        let plan = planla(&conn, &cedvel(&["Barkod", "Ad", "Rəf"], &[&["KG-1", "Köynək", "A3"]])).unwrap();
+
+        assert_eq!(
+            xetalar(&plan),
+            vec![
+                (1, "Rəf".to_string()),
+                (1, "alis_qiymeti".to_string()),
+                (1, "satis_qiymeti".to_string()),
+            ]
+        );
+        assert!(plan.setirler.is_empty());
+        assert!(!plan.xetasizdir());
+    }
+
+    #[test]
+    fn xetali_setirler_sutunu_ile_yazilir_duzgunler_planlanir() {
+        let conn = test_bazasi();
+        conn.execute("INSERT INTO sizes (olcu) VALUES ('M')", []).unwrap();
+        let plan = planla(
+            &conn,
+            &cedvel(
+                &["Barkod", "Ad", "Kateqoriya", "Alış qiyməti", "Satış qiyməti", "M"],
+                &[
+                    &["KG-1", "Köynək", "", "10", "20", "3"],
+                    &["", "Adsız", "", "10", "20", ""],
+                    &["KG-1", "Təkrar", "", "10", "20", ""],
+                    &["KG-2", "", "", "abc", "20", ""],
+                    &["KG-3", "Şalvar", "Yoxdur", "10", "20", "-1"],
+                ],
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(
+            xetalar(&plan),
+            vec![
+                (3, "barkod".to_string()),
+                (4, "barkod".to_string()),
+                (5, "ad".to_string()),
+                (5, "alis_qiymeti".to_string()),
+                (6, "M".to_string()),
+                (6, "kateqoriya".to_string()),
+            ]
+        );
+        assert_eq!(plan.setirler.len(), 1);
+        assert_eq!(plan.setirler[0].barkod, "KG-1");
+        assert_eq!(plan.setirler[0].stok.len(), 1);
+    }
+
+    #[test]
+    fn olcu_qrupundan_kenar_olcu_xeta_verir() {
+        let conn = test_bazasi();
+        conn.execute_batch(
+            "INSERT INTO size_groups (id, ad) VALUES (100, 'Hərf'), (101, 'Bel');
+             INSERT INTO sizes (olcu, qrup_id) VALUES ('M', 100), ('32', 101);
+             INSERT INTO categories (ad, olcu_qrupu_id) VALUES ('Köynəklər', 100);",
+        )
+        .unwrap();
+
+        let plan = planla(
+            &conn,
+            &cedvel(
+                &["Barkod", "Ad", "Kateqoriya", "Alış qiyməti", "Satış qiyməti", "M", "32"],
+                &[
+                    &["KG-1", "Köynək", "Köynəklər", "10", "20", "2", ""],
+                    &["KG-2", "Köynək", "Köynəklər", "10", "20", "", "1"],
+                ],
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(xetalar(&plan), vec![(3, "32".to_string())]);
+        assert_eq!(plan.setirler.len(), 1);
+    }
+}
@@ -0,0 +1,153 @@
+//! Cost layers: every receipt of stock opens a layer with its unit cost and
+//! every outgoing unit consumes layers, so a sale's cost is fixed at the
+//! moment it happens instead of following today's `products.alis_qiymeti`.
+
+use rusqlite::{Connection, OptionalExtension};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MayaUsulu {
+    /// Oldest receipts are sold first
+    Fifo,
+    /// Every unit costs the weighted average of what is on hand
+    Orta,
+}
+
+impl FromStr for MayaUsulu {
+    type Err = String;
+
+    fn from_str(deyer: &str) -> Result<Self, Self::Err> {
+        match deyer {
+            "FIFO" => Ok(MayaUsulu::Fifo),
+            "Orta" => Ok(MayaUsulu::Orta),
+            _ => Err("Maya dəyəri üsulu 'FIFO' və ya 'Orta' olmalıdır".to_string()),
+        }
+    }
+}
+
+impl MayaUsulu {
+    /// Reads the costing method chosen in settings (FIFO by default).
+    pub fn oxu(conn: &Connection) -> Result<Self, String> {
+        let deyer: Option<String> = conn
+            .query_row(
+                "SELECT maya_deyeri_usulu FROM settings WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .flatten();
+
+        Ok(deyer.and_then(|d| d.parse().ok()).unwrap_or(MayaUsulu::Fifo))
+    }
+}
+
+/// Opens a new cost layer for stock that has just come in.
+pub fn lay_elave_et(
+    conn: &Connection,
+    mehsul_id: i64,
+    olcu_id: i64,
+    reng_id: Option<i64>,
+    miqdar: i32,
+    vahid_maya: f64,
+    hereket_id: Option<i64>,
+) -> Result<(), String> {
+    if miqdar <= 0 {
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO cost_layers (mehsul_id, olcu_id, reng_id, hereket_id, ilkin_miqdar, qalan_miqdar, vahid_maya)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6)",
+        rusqlite::params![mehsul_id, olcu_id, reng_id, hereket_id, miqdar, vahid_maya],
+    )
+    .map_err(|e| format!("Maya dəyəri qeydə alına bilmədi: {}", e))?;
+
+    Ok(())
+}
+
+/// Takes `miqdar` units out of the layers and returns their unit cost.
+///
+/// Units not covered by any layer (legacy or oversold stock) are costed at the
+/// most recent known unit cost, falling back to the product's purchase price.
+pub fn istehlak_et(
+    conn: &Connection,
+    mehsul_id: i64,
+    olcu_id: i64,
+    reng_id: Option<i64>,
+    miqdar: i32,
+    usul: MayaUsulu,
+) -> Result<f64, String> {
+    if miqdar <= 0 {
+        return son_maya(conn, mehsul_id, olcu_id, reng_id);
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, qalan_miqdar, vahid_maya FROM cost_layers
+             WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3 AND qalan_miqdar > 0
+             ORDER BY created_at, id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let laylar: Vec<(i64, i32, f64)> = stmt
+        .query_map(rusqlite::params![mehsul_id, olcu_id, reng_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let movcud: i32 = laylar.iter().map(|(_, qalan, _)| qalan).sum();
+    let movcud_deyer: f64 = laylar.iter().map(|(_, qalan, maya)| *qalan as f64 * maya).sum();
+
+    let mut qalan_teleb = miqdar;
+    let mut toplam_maya = 0.0;
+
+    for (id, qalan, maya) in &laylar {
+        if qalan_teleb == 0 {
+            break;
+        }
+        let goturulen = qalan_teleb.min(*qalan);
+        toplam_maya += goturulen as f64 * maya;
+        qalan_teleb -= goturulen;
+
+        conn.execute(
+            "UPDATE cost_layers SET qalan_miqdar = qalan_miqdar - ?1 WHERE id = ?2",
+            rusqlite::params![goturulen, id],
+        )
+        .map_err(|e| format!("Maya dəyəri yenilənə bilmədi: {}", e))?;
+    }
+
+    if usul == MayaUsulu::Orta && movcud > 0 {
+        // Everything on hand is worth the same average, before and after this sale
+        let orta = movcud_deyer / movcud as f64;
+        conn.execute(
+            "UPDATE cost_layers SET vahid_maya = ?1
+             WHERE mehsul_id = ?2 AND olcu_id = ?3 AND reng_id IS ?4 AND qalan_miqdar > 0",
+            rusqlite::params![orta, mehsul_id, olcu_id, reng_id],
+        )
+        .map_err(|e| format!("Maya dəyəri yenilənə bilmədi: {}", e))?;
+
+        toplam_maya = (miqdar - qalan_teleb) as f64 * orta;
+    }
+
+    if qalan_teleb > 0 {
+        toplam_maya += qalan_teleb as f64 * son_maya(conn, mehsul_id, olcu_id, reng_id)?;
+    }
+
+    Ok(toplam_maya / miqdar as f64)
+}
+
+// Latest unit cost we know of for a product/size/colour
+fn son_maya(conn: &Connection, mehsul_id: i64, olcu_id: i64, reng_id: Option<i64>) -> Result<f64, String> {
+    conn.query_row(
+        "SELECT COALESCE(
+            (SELECT vahid_maya FROM cost_layers
+             WHERE mehsul_id = ?1 AND olcu_id = ?2 AND reng_id IS ?3
+             ORDER BY created_at DESC, id DESC LIMIT 1),
+            (SELECT alis_qiymeti FROM products WHERE id = ?1),
+            0)",
+        rusqlite::params![mehsul_id, olcu_id, reng_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,4 @@
+pub mod cost;
+
+/// One stock row: product, size and (optional) colour.
+pub type StokAcari = (i64, i64, Option<i64>);
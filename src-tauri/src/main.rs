@@ -1,15 +1,27 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod audit;
+mod auth;
+mod barcode;
 mod db;
+mod export;
+mod import;
+mod inventory;
+mod numbering;
+mod promotions;
 mod models;
 mod commands;
 
 use std::sync::{Arc, Mutex};
+use auth::session::SessionStore;
+use db::backup::AutoBackupStatus;
 use db::connection::Database;
 
 pub struct AppState {
     pub db: Arc<Mutex<Database>>,
+    pub sessions: SessionStore,
+    pub ehtiyat_veziyyeti: Arc<Mutex<AutoBackupStatus>>,
 }
 
 fn main() {
@@ -21,33 +33,71 @@ fn main() {
 
     let state = AppState {
         db: Arc::new(Mutex::new(db)),
+        sessions: SessionStore::default(),
+        ehtiyat_veziyyeti: Arc::default(),
     };
 
+    // Daily rotating backups in the background
+    db::backup::avtomatik_ehtiyat_baslat(Arc::clone(&state.db), Arc::clone(&state.ehtiyat_veziyyeti));
+
     tauri::Builder::default()
         .manage(state)
         .invoke_handler(tauri::generate_handler![
             // Product commands
             commands::product::mehsul_elave_et,
+            commands::product::mehsul_matris_elave_et,
             commands::product::mehsul_siyahisi,
             commands::product::mehsul_axtar,
             commands::product::mehsul_yenile,
             commands::product::mehsul_sil,
             commands::product::mehsul_barkod_ile_axtar,
+            commands::variant::variant_elave_et,
+            commands::variant::variant_siyahisi,
+            commands::variant::variant_yenile,
+            commands::variant::variant_sil,
+            commands::import::mehsul_idxal_yoxla,
+            commands::import::mehsul_idxal_et,
+            // Barcode commands
+            commands::barcode::barkod_yarat,
+            commands::barcode::barkod_yoxla,
+            commands::barcode::barkod_sekli,
             // Category commands
             commands::category::kateqoriya_elave_et,
             commands::category::kateqoriya_siyahisi,
             commands::category::kateqoriya_yenile,
+            commands::category::kateqoriya_olcu_qrupu_teyin_et,
+            commands::category::kateqoriya_kocur,
+            commands::category::kateqoriya_birlesdir,
             commands::category::kateqoriya_sil,
             // Size commands
             commands::size::olcu_elave_et,
             commands::size::olcu_siyahisi,
             commands::size::olcu_yenile,
             commands::size::olcu_sil,
+            commands::size::kateqoriya_olculeri,
+            // Size group commands
+            commands::size_group::olcu_qrupu_elave_et,
+            commands::size_group::olcu_qrupu_siyahisi,
+            commands::size_group::olcu_qrupu_yenile,
+            commands::size_group::olcu_qrupu_sirala,
+            commands::size_group::olcu_qrupu_sil,
             // Color commands
             commands::color::reng_elave_et,
             commands::color::reng_siyahisi,
             commands::color::reng_yenile,
             commands::color::reng_sil,
+            // Brand commands
+            commands::brand::marka_elave_et,
+            commands::brand::marka_siyahisi,
+            commands::brand::marka_yenile,
+            commands::brand::marka_sil,
+            // Promotion commands
+            commands::promotion::kampaniya_elave_et,
+            commands::promotion::kampaniya_siyahisi,
+            commands::promotion::kampaniya_yenile,
+            commands::promotion::kampaniya_aktiv_et,
+            commands::promotion::kampaniya_sil,
+            commands::promotion::kampaniyalari_hesabla,
             // Stock commands
             commands::stock::stok_elave_et,
             commands::stock::stok_yenile,
@@ -66,7 +116,10 @@ fn main() {
             commands::report::qazanc_hesabati,
             commands::report::stok_deyeri_hesabati,
             commands::report::mehsul_statistikasi,
+            commands::report::marka_hesabati,
+            commands::report::kampaniya_hesabati,
             commands::report::mehsul_hereketleri,
+            commands::export::hesabat_ixrac_et,
             // Customer commands
             commands::customer::musteri_elave_et,
             commands::customer::musteri_siyahisi,
@@ -89,13 +142,42 @@ fn main() {
             commands::settings::parametrleri_yenile,
             // User commands
             commands::user::giris_yap,
+            commands::user::cixis_et,
             commands::user::istifadeci_elave_et,
             commands::user::istifadeci_siyahisi,
             commands::user::istifadeci_yenile,
             commands::user::istifadeci_sil,
             commands::user::sifre_deyis,
+            // Supplier commands
+            commands::supplier::tedarukcu_elave_et,
+            commands::supplier::tedarukcu_siyahisi,
+            commands::supplier::tedarukcu_yenile,
+            commands::supplier::tedarukcu_sil,
+            // Purchase order commands
+            commands::purchase_order::sifaris_yarat,
+            commands::purchase_order::sifaris_siyahisi,
+            commands::purchase_order::sifaris_detallari,
+            commands::purchase_order::sifaris_qebul_et,
+            commands::purchase_order::sifaris_bagla,
+            // Supplier payment commands
+            commands::supplier_payment::tedarukcu_odeme_yarat,
+            commands::supplier_payment::tedarukcu_odeme_siyahisi,
+            commands::supplier_payment::tedarukcu_borc_xulasesi,
+            commands::supplier_payment::tedarukcu_hesab_cixarisi,
+            // Shift commands
+            commands::shift::novbe_ac,
+            commands::shift::novbe_bagla,
+            commands::shift::aktiv_novbe,
+            commands::shift::novbe_siyahisi,
+            commands::shift::novbe_z_hesabati,
+            commands::audit_log::audit_jurnali,
             // Database commands
             commands::database::databazi_sifirla,
+            commands::database::sxem_versiyasi,
+            commands::backup::ehtiyat_yarat,
+            commands::backup::ehtiyat_siyahisi,
+            commands::backup::ehtiyat_berpa_et,
+            commands::backup::ehtiyat_veziyyeti,
         ])
         .run(tauri::generate_context!())
         .expect("Tətbiq işə salına bilmədi");
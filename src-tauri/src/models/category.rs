@@ -4,10 +4,16 @@ use serde::{Deserialize, Serialize};
 pub struct Category {
     pub id: i64,
     pub ad: String,
+    pub parent_id: Option<i64>,
+    pub olcu_qrupu_id: Option<i64>,
     pub created_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateCategory {
     pub ad: String,
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+    #[serde(default)]
+    pub olcu_qrupu_id: Option<i64>,
 }
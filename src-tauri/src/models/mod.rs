@@ -2,16 +2,22 @@ pub mod product;
 pub mod category;
 pub mod size;
 pub mod color;
+pub mod brand;
 pub mod sale;
 pub mod stock;
 pub mod customer;
 pub mod user;
+pub mod variant;
+pub mod promotion;
 
 pub use product::*;
 pub use category::*;
 pub use size::*;
 pub use color::*;
+pub use brand::*;
 pub use sale::*;
 pub use stock::*;
 pub use customer::*;
 pub use user::*;
+pub use variant::*;
+pub use promotion::*;
@@ -9,6 +9,7 @@ pub struct Product {
     pub kateqoriya_adi: Option<String>,
     pub reng: Option<String>,
     pub marka: Option<String>,
+    pub marka_id: Option<i64>,
     pub alis_qiymeti: f64,
     pub satis_qiymeti: f64,
     pub tesvir: Option<String>,
@@ -24,6 +25,8 @@ pub struct CreateProduct {
     pub kateqoriya_id: Option<i64>,
     pub reng: Option<String>,
     pub marka: Option<String>,
+    #[serde(default)]
+    pub marka_id: Option<i64>,
     pub alis_qiymeti: f64,
     pub satis_qiymeti: f64,
     pub tesvir: Option<String>,
@@ -37,12 +40,37 @@ pub struct UpdateProduct {
     pub kateqoriya_id: Option<i64>,
     pub reng: Option<String>,
     pub marka: Option<String>,
+    #[serde(default)]
+    pub marka_id: Option<i64>,
     pub alis_qiymeti: Option<f64>,
     pub satis_qiymeti: Option<f64>,
     pub tesvir: Option<String>,
     pub sekil_yolu: Option<String>,
 }
 
+/// A new style in every chosen size and colour. Each cell starts with
+/// `miqdar` and `minimum_miqdar` unless it has its own line in `hucreler`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateProductMatrix {
+    pub mehsul: CreateProduct,
+    pub olcu_idler: Vec<i64>,
+    #[serde(default)]
+    pub reng_idler: Vec<i64>,
+    #[serde(default)]
+    pub miqdar: i32,
+    pub minimum_miqdar: Option<i32>,
+    #[serde(default)]
+    pub hucreler: Vec<MatrixCell>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MatrixCell {
+    pub olcu_id: i64,
+    pub reng_id: Option<i64>,
+    pub miqdar: i32,
+    pub minimum_miqdar: Option<i32>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProductWithStock {
     #[serde(flatten)]
@@ -54,6 +82,8 @@ pub struct ProductWithStock {
 pub struct StockInfo {
     pub olcu_id: i64,
     pub olcu: String,
+    pub reng_id: Option<i64>,
+    pub reng: Option<String>,
     pub miqdar: i32,
     pub minimum_miqdar: i32,
 }
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// How a promotion takes money off the lines it covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromotionType {
+    /// `deyer` per cent off every unit
+    Faiz,
+    /// `deyer` manat off every unit, never below zero
+    Sabit,
+    /// Buy one, get one free: of every two units the cheaper is free
+    Bogo,
+    /// Three for the price of two: of every three units the cheapest is free
+    UceIki,
+    /// Every `deste_miqdari` units together cost `deyer`
+    Deste,
+}
+
+impl PromotionType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PromotionType::Faiz => "faiz",
+            PromotionType::Sabit => "sabit",
+            PromotionType::Bogo => "bogo",
+            PromotionType::UceIki => "uce_iki",
+            PromotionType::Deste => "deste",
+        }
+    }
+}
+
+impl FromStr for PromotionType {
+    type Err = String;
+
+    fn from_str(deyer: &str) -> Result<Self, Self::Err> {
+        match deyer {
+            "faiz" => Ok(PromotionType::Faiz),
+            "sabit" => Ok(PromotionType::Sabit),
+            "bogo" => Ok(PromotionType::Bogo),
+            "uce_iki" => Ok(PromotionType::UceIki),
+            "deste" => Ok(PromotionType::Deste),
+            _ => Err(format!("Naməlum kampaniya növü: {}", deyer)),
+        }
+    }
+}
+
+/// A promotion covers the products matching every scope it sets; one with
+/// no scope covers the whole shop. A category takes in its sub-categories.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Promotion {
+    pub id: i64,
+    pub ad: String,
+    pub novu: PromotionType,
+    pub deyer: f64,
+    pub deste_miqdari: Option<i32>,
+    pub mehsul_id: Option<i64>,
+    pub kateqoriya_id: Option<i64>,
+    pub marka_id: Option<i64>,
+    pub olcu_id: Option<i64>,
+    pub baslangic_tarixi: Option<String>,
+    pub bitis_tarixi: Option<String>,
+    pub aktiv: bool,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePromotion {
+    pub ad: String,
+    pub novu: PromotionType,
+    #[serde(default)]
+    pub deyer: f64,
+    #[serde(default)]
+    pub deste_miqdari: Option<i32>,
+    #[serde(default)]
+    pub mehsul_id: Option<i64>,
+    #[serde(default)]
+    pub kateqoriya_id: Option<i64>,
+    #[serde(default)]
+    pub marka_id: Option<i64>,
+    #[serde(default)]
+    pub olcu_id: Option<i64>,
+    #[serde(default)]
+    pub baslangic_tarixi: Option<String>,
+    #[serde(default)]
+    pub bitis_tarixi: Option<String>,
+}
+
+/// The promotion applied to one sale line, in the order the lines were given.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SaleLinePromotion {
+    pub kampaniya_id: Option<i64>,
+    pub kampaniya_adi: Option<String>,
+    pub endirim: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SalePromotions {
+    pub setirler: Vec<SaleLinePromotion>,
+    pub endirim: f64,
+}
@@ -18,15 +18,40 @@ pub struct Sale {
 pub struct CreateSale {
     pub items: Vec<CreateSaleItem>,
     pub endirim: f64,
-    pub odenis_usulu: String,
+    /// Single tender, used when `odenisler` is empty
+    pub odenis_usulu: Option<String>,
+    /// Split payment; amounts must add up to the final total
+    #[serde(default)]
+    pub odenisler: Vec<CreateSalePayment>,
     pub musteri_id: Option<i64>,
     pub qeyd: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateSalePayment {
+    pub odenis_usulu: String,
+    pub mebleg: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SalePayment {
+    pub id: i64,
+    pub satis_id: i64,
+    pub odenis_usulu: String,
+    pub mebleg: f64,
+}
+
+/// A sale line names the product, size and colour directly, or gives a
+/// scanned variant barcode from which `satis_yarat` fills them in.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateSaleItem {
+    #[serde(default)]
     pub mehsul_id: i64,
+    #[serde(default)]
     pub olcu_id: i64,
+    pub reng_id: Option<i64>,
+    #[serde(default)]
+    pub variant_barkod: Option<String>,
     pub miqdar: i32,
     pub vahid_qiymeti: f64,
 }
@@ -40,9 +65,15 @@ pub struct SaleItem {
     pub mehsul_barkod: Option<String>,
     pub olcu_id: i64,
     pub olcu: Option<String>,
+    pub reng_id: Option<i64>,
+    pub reng: Option<String>,
     pub miqdar: i32,
     pub vahid_qiymeti: f64,
+    /// Line total after the promotion's discount, i.e. what was paid
     pub toplam_qiymet: f64,
+    pub endirim: f64,
+    pub kampaniya_id: Option<i64>,
+    pub kampaniya_adi: Option<String>,
     pub created_at: Option<String>,
     pub iade_miqdar: i32,  // Qaytarılmış miqdar
 }
@@ -52,4 +83,5 @@ pub struct SaleWithItems {
     #[serde(flatten)]
     pub sale: Sale,
     pub items: Vec<SaleItem>,
+    pub odenisler: Vec<SalePayment>,
 }
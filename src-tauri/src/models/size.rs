@@ -4,10 +4,28 @@ use serde::{Deserialize, Serialize};
 pub struct Size {
     pub id: i64,
     pub olcu: String,
+    pub qrup_id: Option<i64>,
+    pub sira: i64,
     pub created_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateSize {
     pub olcu: String,
+    #[serde(default)]
+    pub qrup_id: Option<i64>,
+}
+
+/// A size scale such as letter sizes, trouser waists or EU shoe sizes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SizeGroup {
+    pub id: i64,
+    pub ad: String,
+    pub olculer: Vec<Size>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSizeGroup {
+    pub ad: String,
 }
@@ -10,6 +10,8 @@ pub struct Stock {
     pub kateqoriya_adi: Option<String>,
     pub olcu_id: i64,
     pub olcu: Option<String>,
+    pub reng_id: Option<i64>,
+    pub reng: Option<String>,
     pub miqdar: i32,
     pub minimum_miqdar: i32,
     pub created_at: Option<String>,
@@ -20,6 +22,7 @@ pub struct Stock {
 pub struct CreateStock {
     pub mehsul_id: i64,
     pub olcu_id: i64,
+    pub reng_id: Option<i64>,
     pub miqdar: i32,
     pub minimum_miqdar: Option<i32>,
 }
@@ -30,27 +33,13 @@ pub struct UpdateStock {
     pub minimum_miqdar: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct StockMovement {
-    pub id: i64,
-    pub mehsul_id: i64,
-    pub mehsul_adi: Option<String>,
-    pub olcu_id: i64,
-    pub olcu: Option<String>,
-    pub novu: String,
-    pub miqdar: i32,
-    pub evvelki_miqdar: Option<i32>,
-    pub yeni_miqdar: Option<i32>,
-    pub qeyd: Option<String>,
-    pub created_at: Option<String>,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LowStockAlert {
     pub mehsul_id: i64,
     pub mehsul_adi: String,
     pub barkod: String,
     pub olcu: String,
+    pub reng: Option<String>,
     pub miqdar: i32,
     pub minimum_miqdar: i32,
 }
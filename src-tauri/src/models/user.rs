@@ -1,19 +1,5 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct User {
-    pub id: i64,
-    pub ad: String,
-    pub soyad: String,
-    pub istifadeci_adi: String,
-    #[serde(skip_serializing)]
-    pub sifre: String,
-    pub rol: String, // "admin" or "isci"
-    pub aktiv: bool,
-    pub created_at: Option<String>,
-    pub updated_at: Option<String>,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserPublic {
     pub id: i64,
@@ -53,5 +39,6 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginResponse {
     pub user: UserPublic,
+    pub token: String,
     pub message: String,
 }
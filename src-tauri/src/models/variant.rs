@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+use super::Product;
+
+/// One sellable SKU of a product: a size, optionally a colour, and its own
+/// barcode.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Variant {
+    pub id: i64,
+    pub mehsul_id: i64,
+    pub olcu_id: i64,
+    pub olcu: Option<String>,
+    pub reng_id: Option<i64>,
+    pub reng: Option<String>,
+    pub barkod: String,
+    /// Stock of the product in this size and colour
+    pub miqdar: i32,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateVariant {
+    pub mehsul_id: i64,
+    pub olcu_id: i64,
+    pub reng_id: Option<i64>,
+    /// Left empty, an internal barcode is issued
+    pub barkod: Option<String>,
+}
+
+/// Result of a barcode scan. `variant` is set when the code belongs to a
+/// variant rather than to the product itself.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScannedProduct {
+    #[serde(flatten)]
+    pub product: Product,
+    pub variant: Option<Variant>,
+}
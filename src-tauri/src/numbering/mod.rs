@@ -0,0 +1,254 @@
+//! Sequential document numbers for sales, returns and purchase orders, built
+//! from a format and a counter kept in the `sequences` table.
+
+use chrono::Datelike;
+use rusqlite::{Connection, OptionalExtension};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sened {
+    Satis,
+    Iade,
+    Sifaris,
+}
+
+impl Sened {
+    fn ad(self) -> &'static str {
+        match self {
+            Sened::Satis => "satis",
+            Sened::Iade => "iade",
+            Sened::Sifaris => "sifaris",
+        }
+    }
+
+    /// Settings column holding the format; purchase orders always use the standard one.
+    fn format_sutunu(self) -> Option<&'static str> {
+        match self {
+            Sened::Satis => Some("satis_nomre_formati"),
+            Sened::Iade => Some("iade_nomre_formati"),
+            Sened::Sifaris => None,
+        }
+    }
+
+    /// Table and column the numbers are stored in.
+    fn nomre_sutunu(self) -> (&'static str, &'static str) {
+        match self {
+            Sened::Satis => ("sales", "satis_nomresi"),
+            Sened::Iade => ("returns", "iade_nomresi"),
+            Sened::Sifaris => ("purchase_orders", "sifaris_nomresi"),
+        }
+    }
+
+    pub fn standart_format(self) -> &'static str {
+        match self {
+            Sened::Satis => "S-{IL}-{NOMRE:6}",
+            Sened::Iade => "I-{IL}-{NOMRE:6}",
+            Sened::Sifaris => "PO-{IL}-{NOMRE:6}",
+        }
+    }
+}
+
+enum Hisse<'a> {
+    Metn(&'a str),
+    Il,
+    Nomre(usize),
+}
+
+const MAKS_FORMAT_UZUNLUGU: usize = 40;
+const MAKS_DOLDURMA: usize = 12;
+
+fn hisselere_ayir(format: &str) -> Result<Vec<Hisse<'_>>, String> {
+    if format.trim().is_empty() {
+        return Err("Nömrə formatı boş ola bilməz".to_string());
+    }
+    if format.chars().count() > MAKS_FORMAT_UZUNLUGU {
+        return Err(format!("Nömrə formatı {} simvoldan uzun ola bilməz", MAKS_FORMAT_UZUNLUGU));
+    }
+
+    let mut hisseler = Vec::new();
+    let mut qalan = format;
+
+    while let Some(basla) = qalan.find('{') {
+        if basla > 0 {
+            hisseler.push(Hisse::Metn(&qalan[..basla]));
+        }
+        let son = qalan[basla..]
+            .find('}')
+            .map(|i| basla + i)
+            .ok_or_else(|| format!("Nömrə formatında bağlanmamış '{{' var: {}", format))?;
+
+        let hisse = match &qalan[basla + 1..son] {
+            "IL" => Hisse::Il,
+            "NOMRE" => Hisse::Nomre(0),
+            diger => match diger.strip_prefix("NOMRE:").map(str::parse::<usize>) {
+                Some(Ok(n)) if (1..=MAKS_DOLDURMA).contains(&n) => Hisse::Nomre(n),
+                Some(_) => {
+                    return Err(format!("{{NOMRE:n}} üçün n 1 ilə {} arasında olmalıdır", MAKS_DOLDURMA))
+                }
+                None => return Err(format!("Naməlum nömrə formatı hissəsi: {{{}}}", diger)),
+            },
+        };
+        hisseler.push(hisse);
+        qalan = &qalan[son + 1..];
+    }
+
+    if !qalan.is_empty() {
+        hisseler.push(Hisse::Metn(qalan));
+    }
+
+    let nomre_sayi = hisseler.iter().filter(|h| matches!(h, Hisse::Nomre(_))).count();
+    if nomre_sayi != 1 {
+        return Err("Nömrə formatında bir dəfə {NOMRE} və ya {NOMRE:n} olmalıdır".to_string());
+    }
+
+    Ok(hisseler)
+}
+
+/// Checks a format such as `S-{IL}-{NOMRE:6}` before it is saved to settings.
+pub fn format_yoxla(format: &str) -> Result<(), String> {
+    hisselere_ayir(format).map(|_| ())
+}
+
+fn formatla(hisseler: &[Hisse], il: i32, nomre: i64) -> String {
+    let mut netice = String::new();
+    for hisse in hisseler {
+        match hisse {
+            Hisse::Metn(m) => netice.push_str(m),
+            Hisse::Il => netice.push_str(&il.to_string()),
+            Hisse::Nomre(n) => netice.push_str(&format!("{:0width$}", nomre, width = *n)),
+        }
+    }
+    netice
+}
+
+/// Allocates the next number for `sened`. Call it inside the transaction that
+/// inserts the document, so a rollback hands the number back.
+///
+/// Every format keeps its own counter, which restarts at 1 each calendar year
+/// when the format contains `{IL}`. Numbers already taken, e.g. by another
+/// format that produces the same text, are skipped.
+pub fn novbeti_nomre(conn: &Connection, sened: Sened) -> Result<String, String> {
+    nomre_ayir(conn, sened, chrono::Local::now().year())
+}
+
+fn nomre_ayir(conn: &Connection, sened: Sened, il: i32) -> Result<String, String> {
+    let format = match sened.format_sutunu() {
+        Some(sutun) => conn
+            .query_row(&format!("SELECT {} FROM settings WHERE id = 1", sutun), [], |row| {
+                row.get::<_, Option<String>>(0)
+            })
+            .optional()
+            .map_err(|e| format!("Nömrə formatı oxuna bilmədi: {}", e))?
+            .flatten(),
+        None => None,
+    }
+    .unwrap_or_else(|| sened.standart_format().to_string());
+
+    let hisseler = hisselere_ayir(&format)?;
+    let sayac_ili = if hisseler.iter().any(|h| matches!(h, Hisse::Il)) { il } else { 0 };
+    let (cedvel, sutun) = sened.nomre_sutunu();
+
+    loop {
+        let nomre: i64 = conn
+            .query_row(
+                "INSERT INTO sequences (ad, il, son_nomre) VALUES (?1, ?2, 1)
+                 ON CONFLICT(ad) DO UPDATE SET
+                    son_nomre = CASE WHEN il = excluded.il THEN son_nomre + 1 ELSE 1 END,
+                    il = excluded.il,
+                    updated_at = CURRENT_TIMESTAMP
+                 RETURNING son_nomre",
+                rusqlite::params![format!("{}-{}", sened.ad(), format), sayac_ili],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Sənəd nömrəsi ayrıla bilmədi: {}", e))?;
+
+        let nomre = formatla(&hisseler, il, nomre);
+        let istifade_olunub: bool = conn
+            .query_row(
+                &format!("SELECT EXISTS(SELECT 1 FROM {} WHERE {} = ?1)", cedvel, sutun),
+                [&nomre],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if !istifade_olunub {
+            return Ok(nomre);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baza() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE settings (id INTEGER PRIMARY KEY, satis_nomre_formati TEXT, iade_nomre_formati TEXT);
+             INSERT INTO settings (id, satis_nomre_formati) VALUES (1, 'S-{IL}-{NOMRE:4}');
+             CREATE TABLE sequences (
+                ad TEXT PRIMARY KEY,
+                il INTEGER NOT NULL DEFAULT 0,
+                son_nomre INTEGER NOT NULL DEFAULT 0,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+             );
+             CREATE TABLE sales (id INTEGER PRIMARY KEY, satis_nomresi TEXT UNIQUE);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn satis(conn: &Connection, il: i32) -> String {
+        let nomre = nomre_ayir(conn, Sened::Satis, il).unwrap();
+        conn.execute("INSERT INTO sales (satis_nomresi) VALUES (?1)", [&nomre]).unwrap();
+        nomre
+    }
+
+    fn format_qoy(conn: &Connection, format: &str) {
+        conn.execute("UPDATE settings SET satis_nomre_formati = ?1", [format]).unwrap();
+    }
+
+    #[test]
+    fn il_deyisende_sayac_yeniden_baslayir() {
+        let conn = baza();
+        assert_eq!(satis(&conn, 2026), "S-2026-0001");
+        assert_eq!(satis(&conn, 2026), "S-2026-0002");
+        assert_eq!(satis(&conn, 2027), "S-2027-0001");
+        assert_eq!(satis(&conn, 2027), "S-2027-0002");
+    }
+
+    #[test]
+    fn ilsiz_format_ilden_asili_deyil() {
+        let conn = baza();
+        format_qoy(&conn, "S{NOMRE}");
+        assert_eq!(satis(&conn, 2026), "S1");
+        assert_eq!(satis(&conn, 2027), "S2");
+    }
+
+    #[test]
+    fn format_deyisende_nomre_tekrarlanmir() {
+        let conn = baza();
+        assert_eq!(satis(&conn, 2026), "S-2026-0001");
+        format_qoy(&conn, "S-{NOMRE:3}");
+        assert_eq!(satis(&conn, 2026), "S-001");
+        format_qoy(&conn, "S-{IL}-{NOMRE:4}");
+        assert_eq!(satis(&conn, 2026), "S-2026-0002");
+    }
+
+    #[test]
+    fn istifade_olunmus_nomre_oturulur() {
+        let conn = baza();
+        conn.execute("INSERT INTO sales (satis_nomresi) VALUES ('S-2026-0001')", []).unwrap();
+        assert_eq!(satis(&conn, 2026), "S-2026-0002");
+
+        // Another format spelling the same text does not reissue it
+        format_qoy(&conn, "S-2026-{NOMRE:4}");
+        assert_eq!(satis(&conn, 2026), "S-2026-0003");
+    }
+
+    #[test]
+    fn sifaris_standart_formatla_nomrelenir() {
+        let conn = baza();
+        conn.execute_batch("CREATE TABLE purchase_orders (id INTEGER PRIMARY KEY, sifaris_nomresi TEXT UNIQUE);")
+            .unwrap();
+        assert_eq!(nomre_ayir(&conn, Sened::Sifaris, 2026).unwrap(), "PO-2026-000001");
+    }
+}
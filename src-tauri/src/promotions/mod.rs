@@ -0,0 +1,328 @@
+//! Promotions: rules that take money off sale lines on their own, without
+//! the cashier typing a discount. `satis_yarat` asks `hesabla` what each line
+//! gets and stores the discount and the promotion on the line itself.
+
+use crate::commands::category::alt_kateqoriyalar_cte;
+use crate::models::{CreateSaleItem, Promotion, PromotionType, SaleLinePromotion, SalePromotions};
+use rusqlite::{Connection, OptionalExtension};
+
+pub const KAMPANIYA_SELECT: &str = "SELECT id, ad, novu, deyer, deste_miqdari, mehsul_id, kateqoriya_id, marka_id,
+            olcu_id, baslangic_tarixi, bitis_tarixi, aktiv, created_at
+     FROM promotions";
+
+pub fn kampaniya_setri(row: &rusqlite::Row) -> rusqlite::Result<Promotion> {
+    let novu: String = row.get(2)?;
+    Ok(Promotion {
+        id: row.get(0)?,
+        ad: row.get(1)?,
+        novu: novu
+            .parse()
+            .map_err(|e: String| rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, e.into()))?,
+        deyer: row.get(3)?,
+        deste_miqdari: row.get(4)?,
+        mehsul_id: row.get(5)?,
+        kateqoriya_id: row.get(6)?,
+        marka_id: row.get(7)?,
+        olcu_id: row.get(8)?,
+        baslangic_tarixi: row.get(9)?,
+        bitis_tarixi: row.get(10)?,
+        aktiv: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+/// Refuses to delete a product, category, brand or size a promotion is
+/// scoped to. `sutun` is the promotions column pointing at it.
+pub fn aid_kampaniya_yoxla(conn: &Connection, sutun: &str, id: i64) -> Result<(), String> {
+    let kampaniya: Option<String> = conn
+        .query_row(
+            &format!("SELECT ad FROM promotions WHERE {} = ?1 ORDER BY id LIMIT 1", sutun),
+            [id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match kampaniya {
+        Some(ad) => Err(format!(
+            "\"{}\" kampaniyası buna aiddir, əvvəlcə kampaniyanın əhatəsini dəyişin və ya onu silin",
+            ad
+        )),
+        None => Ok(()),
+    }
+}
+
+// (line index, discount) for the lines a promotion covers
+type Endirimler = Vec<(usize, f64)>;
+
+// A sale line as the promotions see it
+struct Setir {
+    mehsul_id: i64,
+    kateqoriya_id: Option<i64>,
+    marka_id: Option<i64>,
+    olcu_id: i64,
+    miqdar: i32,
+    vahid_qiymeti: f64,
+}
+
+// An active promotion with its category already expanded to the sub-tree
+struct Kampaniya {
+    kampaniya: Promotion,
+    kateqoriyalar: Vec<i64>,
+}
+
+impl Kampaniya {
+    fn aiddir(&self, s: &Setir) -> bool {
+        let k = &self.kampaniya;
+        k.mehsul_id.is_none_or(|id| id == s.mehsul_id)
+            && k.olcu_id.is_none_or(|id| id == s.olcu_id)
+            && k.marka_id.is_none_or(|id| Some(id) == s.marka_id)
+            && (k.kateqoriya_id.is_none()
+                || s.kateqoriya_id.is_some_and(|id| self.kateqoriyalar.contains(&id)))
+    }
+
+    /// The lines out of `setirler` the promotion takes, with their discount.
+    /// Promotions over several units pool every unit of the given lines and
+    /// group the dearest together, so the free unit in each group is worth
+    /// the most; every line that went into a group is taken, so it cannot
+    /// count towards a second promotion.
+    fn endirimler(&self, setirler: &[(usize, &Setir)]) -> Endirimler {
+        let k = &self.kampaniya;
+        let mut endirim: Endirimler = setirler.iter().map(|(i, _)| (*i, 0.0)).collect();
+
+        let qrup = match k.novu {
+            PromotionType::Faiz => {
+                for ((_, s), (_, e)) in setirler.iter().zip(endirim.iter_mut()) {
+                    *e = s.vahid_qiymeti * s.miqdar as f64 * k.deyer / 100.0;
+                }
+                return endirim;
+            }
+            PromotionType::Sabit => {
+                for ((_, s), (_, e)) in setirler.iter().zip(endirim.iter_mut()) {
+                    *e = k.deyer.min(s.vahid_qiymeti) * s.miqdar as f64;
+                }
+                return endirim;
+            }
+            PromotionType::Bogo => 2,
+            PromotionType::UceIki => 3,
+            PromotionType::Deste => k.deste_miqdari.unwrap_or(0).max(0) as usize,
+        };
+        if qrup == 0 {
+            return Vec::new();
+        }
+
+        // (price, position in `endirim`) for every unit
+        let mut vahidler: Vec<(f64, usize)> = setirler
+            .iter()
+            .enumerate()
+            .flat_map(|(yer, (_, s))| std::iter::repeat_n((s.vahid_qiymeti, yer), s.miqdar.max(0) as usize))
+            .collect();
+        vahidler.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut istirak = vec![false; setirler.len()];
+        for qrup_vahidleri in vahidler.chunks_exact(qrup) {
+            let cem: f64 = qrup_vahidleri.iter().map(|(qiymet, _)| qiymet).sum();
+            let qenaet = match k.novu {
+                PromotionType::Deste => cem - k.deyer,
+                // The cheapest unit of the group is the free one
+                _ => qrup_vahidleri[qrup - 1].0,
+            };
+            if qenaet <= 0.0 || cem <= 0.0 {
+                continue;
+            }
+            // The saving is shared out by each unit's part of the price, so
+            // no line is left free and each one refunds what was paid for it
+            for (qiymet, yer) in qrup_vahidleri {
+                endirim[*yer].1 += qenaet * qiymet / cem;
+            }
+            for (_, yer) in qrup_vahidleri {
+                istirak[*yer] = true;
+            }
+        }
+
+        endirim
+            .into_iter()
+            .zip(istirak)
+            .filter_map(|(e, var)| var.then_some(e))
+            .collect()
+    }
+}
+
+fn qepike_yuvarlaqla(mebleg: f64) -> f64 {
+    (mebleg * 100.0).round() / 100.0
+}
+
+fn aktiv_kampaniyalar(conn: &Connection, tarix: &str) -> Result<Vec<Kampaniya>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE aktiv = 1
+               AND (baslangic_tarixi IS NULL OR baslangic_tarixi <= ?1)
+               AND (bitis_tarixi IS NULL OR bitis_tarixi >= ?1)
+             ORDER BY id",
+            KAMPANIYA_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+    let kampaniyalar = stmt
+        .query_map([tarix], kampaniya_setri)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut netice = Vec::new();
+    for kampaniya in kampaniyalar {
+        let kateqoriyalar = match kampaniya.kateqoriya_id {
+            Some(id) => conn
+                .prepare(&format!("WITH RECURSIVE {} SELECT id FROM alt_kateqoriyalar", alt_kateqoriyalar_cte("?1")))
+                .map_err(|e| e.to_string())?
+                .query_map([id], |row| row.get(0))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<i64>, _>>()
+                .map_err(|e| e.to_string())?,
+            None => Vec::new(),
+        };
+        netice.push(Kampaniya { kampaniya, kateqoriyalar });
+    }
+
+    Ok(netice)
+}
+
+/// Works out what the promotions running today take off each line. A line
+/// gets at most one promotion: the one saving the most is given its lines
+/// first, then the next among the lines still left, and so on.
+pub fn hesabla(conn: &Connection, items: &[CreateSaleItem]) -> Result<SalePromotions, String> {
+    let bu_gun = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let kampaniyalar = aktiv_kampaniyalar(conn, &bu_gun)?;
+
+    let mut setirler = Vec::new();
+    for item in items {
+        let (kateqoriya_id, marka_id) = conn
+            .query_row(
+                "SELECT kateqoriya_id, marka_id FROM products WHERE id = ?1",
+                [item.mehsul_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|_| format!("Məhsul tapılmadı: {}", item.mehsul_id))?;
+        setirler.push(Setir {
+            mehsul_id: item.mehsul_id,
+            kateqoriya_id,
+            marka_id,
+            olcu_id: item.olcu_id,
+            miqdar: item.miqdar,
+            vahid_qiymeti: item.vahid_qiymeti,
+        });
+    }
+
+    let mut netice: Vec<SaleLinePromotion> = setirler
+        .iter()
+        .map(|_| SaleLinePromotion { kampaniya_id: None, kampaniya_adi: None, endirim: 0.0 })
+        .collect();
+    let mut istifade_olunub = vec![false; kampaniyalar.len()];
+
+    loop {
+        let mut en_yaxsi: Option<(usize, Endirimler, f64)> = None;
+        for (k_yer, k) in kampaniyalar.iter().enumerate() {
+            if istifade_olunub[k_yer] {
+                continue;
+            }
+            let bos: Vec<(usize, &Setir)> = setirler
+                .iter()
+                .enumerate()
+                .filter(|(i, s)| netice[*i].kampaniya_id.is_none() && k.aiddir(s))
+                .collect();
+            let endirimler: Endirimler = k
+                .endirimler(&bos)
+                .into_iter()
+                .map(|(i, e)| (i, qepike_yuvarlaqla(e)))
+                .collect();
+            let cem: f64 = endirimler.iter().map(|(_, e)| e).sum();
+            if cem > en_yaxsi.as_ref().map_or(0.0, |(_, _, c)| *c) {
+                en_yaxsi = Some((k_yer, endirimler, cem));
+            }
+        }
+
+        let Some((k_yer, endirimler, _)) = en_yaxsi else {
+            break;
+        };
+        istifade_olunub[k_yer] = true;
+        let k = &kampaniyalar[k_yer].kampaniya;
+        for (i, endirim) in endirimler {
+            let setir = &setirler[i];
+            netice[i] = SaleLinePromotion {
+                kampaniya_id: Some(k.id),
+                kampaniya_adi: Some(k.ad.clone()),
+                endirim: endirim.min(setir.vahid_qiymeti * setir.miqdar as f64),
+            };
+        }
+    }
+
+    let endirim = qepike_yuvarlaqla(netice.iter().map(|s| s.endirim).sum());
+    Ok(SalePromotions { setirler: netice, endirim })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::test_bazasi;
+
+    fn baza(kampaniyalar: &str) -> Connection {
+        let conn = test_bazasi();
+        conn.execute_batch(
+            "INSERT INTO sizes (id, olcu) VALUES (100, 'M');
+             INSERT INTO products (id, barkod, ad, alis_qiymeti, satis_qiymeti) VALUES
+                (100, 'KG-1', 'Köynək', 10, 30), (101, 'KG-2', 'Corab', 2, 10);",
+        )
+        .unwrap();
+        conn.execute_batch(kampaniyalar).unwrap();
+        conn
+    }
+
+    fn setir(mehsul_id: i64, miqdar: i32, vahid_qiymeti: f64) -> CreateSaleItem {
+        CreateSaleItem { mehsul_id, olcu_id: 100, reng_id: None, variant_barkod: None, miqdar, vahid_qiymeti }
+    }
+
+    fn endirimler(netice: &SalePromotions) -> Vec<(Option<i64>, f64)> {
+        netice.setirler.iter().map(|s| (s.kampaniya_id, s.endirim)).collect()
+    }
+
+    #[test]
+    fn biri_alana_biri_pulsuz_faizden_cox_qenaet_edende_secilir() {
+        let conn = baza(
+            "INSERT INTO promotions (id, ad, novu, deyer) VALUES (1, '10%', 'faiz', 10), (2, '1+1', 'bogo', 0);",
+        );
+        let netice = hesabla(&conn, &[setir(100, 1, 30.0), setir(101, 1, 10.0)]).unwrap();
+
+        // The free unit is the 10 manat one, shared out 30:10 over the two lines
+        assert_eq!(endirimler(&netice), vec![(Some(2), 7.5), (Some(2), 2.5)]);
+        assert_eq!(netice.endirim, 10.0);
+    }
+
+    #[test]
+    fn deste_en_cox_qenaet_edende_secilir() {
+        let conn = baza(
+            "INSERT INTO promotions (id, ad, novu, deyer) VALUES (1, '20%', 'faiz', 20);
+             INSERT INTO promotions (id, ad, novu, deyer, deste_miqdari) VALUES (2, '3-ü 50', 'deste', 50, 3);",
+        );
+        // 70 for three units against 50: the bundle saves 20, the percentage 14
+        let netice = hesabla(&conn, &[setir(100, 2, 30.0), setir(101, 1, 10.0)]).unwrap();
+        assert_eq!(netice.setirler.iter().map(|s| s.kampaniya_id).collect::<Vec<_>>(), vec![Some(2), Some(2)]);
+        assert_eq!(netice.endirim, 20.0);
+
+        // At 50% the percentage saves more and takes the lines instead
+        conn.execute("UPDATE promotions SET deyer = 50 WHERE id = 1", []).unwrap();
+        let netice = hesabla(&conn, &[setir(100, 2, 30.0), setir(101, 1, 10.0)]).unwrap();
+        assert_eq!(endirimler(&netice), vec![(Some(1), 30.0), (Some(1), 5.0)]);
+    }
+
+    #[test]
+    fn setir_yalniz_bir_kampaniya_alir() {
+        // The bundle only covers the shirt, so the socks are left to the percentage
+        let conn = baza(
+            "INSERT INTO promotions (id, ad, novu, deyer) VALUES (1, '10%', 'faiz', 10);
+             INSERT INTO promotions (id, ad, novu, deyer, deste_miqdari, mehsul_id) VALUES (2, '2-si 40', 'deste', 40, 2, 100);",
+        );
+        let netice = hesabla(&conn, &[setir(100, 2, 30.0), setir(101, 1, 10.0)]).unwrap();
+
+        assert_eq!(endirimler(&netice), vec![(Some(2), 20.0), (Some(1), 1.0)]);
+        assert_eq!(netice.endirim, 21.0);
+    }
+}
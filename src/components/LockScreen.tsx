@@ -5,7 +5,7 @@ import { Lock, Unlock, AlertTriangle, User } from 'lucide-react';
 import { Button } from './ui';
 
 export const LockScreen: React.FC = () => {
-  const { isScreenLocked, unlockScreen, currentUser } = useAppStore();
+  const { isScreenLocked, unlockScreen, currentUser, setSession } = useAppStore();
   const [lockPassword, setLockPassword] = useState('');
   const [userPassword, setUserPassword] = useState('');
   const [error, setError] = useState('');
@@ -40,10 +40,11 @@ export const LockScreen: React.FC = () => {
 
       setLoading(true);
       try {
-        await userApi.girisYap({
+        const response = await userApi.girisYap({
           istifadeci_adi: currentUser?.istifadeci_adi || '',
           sifre: userPassword,
         });
+        setSession(response.user, response.token);
         unlockScreen();
         setUserPassword('');
         setError('');
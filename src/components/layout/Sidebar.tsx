@@ -1,6 +1,7 @@
 import React from 'react';
 import { cn } from '@/lib/utils';
 import { useAppStore } from '@/store/appStore';
+import { userApi } from '@/lib/tauri';
 import {
   LayoutDashboard,
   Package,
@@ -55,6 +56,7 @@ export const Sidebar: React.FC<SidebarProps> = ({ activePage, onNavigate }) => {
   const menuItems = isAdmin() ? [...baseMenuItems, ...adminMenuItems] : baseMenuItems;
 
   const handleLogout = () => {
+    userApi.cixisEt().catch(console.error);
     logout();
   };
 
@@ -105,6 +105,11 @@ export const Receipt = forwardRef<HTMLDivElement, ReceiptProps>(({ sale }, ref)
                     {item.mehsul_adi}
                     <span className="text-gray-500 ml-1">({item.olcu})</span>
                   </div>
+                  {item.endirim > 0 && (
+                    <div className="text-[9px] text-gray-500">
+                      {item.kampaniya_adi}: -{item.endirim.toFixed(2)}
+                    </div>
+                  )}
                 </td>
                 <td className="text-right py-1 font-bold">{item.miqdar}</td>
                 <td className="text-right py-1 font-bold">{item.vahid_qiymeti.toFixed(2)}</td>
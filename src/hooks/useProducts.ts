@@ -1,7 +1,7 @@
 import { useEffect, useCallback } from 'react';
 import { productApi, categoryApi, sizeApi, colorApi } from '@/lib/tauri';
 import { useAppStore } from '@/store/appStore';
-import type { CreateProduct, UpdateProduct } from '@/types';
+import type { CreateProduct, CreateProductMatrix, UpdateProduct } from '@/types';
 
 export const useProducts = () => {
   const {
@@ -59,6 +59,19 @@ export const useProducts = () => {
     }
   };
 
+  // Add product with its opening stock in one go
+  const createProductMatrix = async (data: CreateProductMatrix) => {
+    try {
+      const product = await productApi.mehsulMatrisElaveEt(data);
+      addProduct(product);
+      addToast('success', 'Məhsul uğurla əlavə edildi!');
+      return product;
+    } catch (error) {
+      addToast('error', 'Məhsul əlavə edilə bilmədi');
+      throw error;
+    }
+  };
+
   // Update product
   const editProduct = async (id: number, data: UpdateProduct) => {
     try {
@@ -117,6 +130,7 @@ export const useProducts = () => {
     colors,
     loadData,
     createProduct,
+    createProductMatrix,
     editProduct,
     deleteProduct,
     searchProducts,
@@ -1,13 +1,37 @@
-import { useState, useCallback } from 'react';
-import { saleApi, stockApi } from '@/lib/tauri';
+import { useState, useCallback, useEffect } from 'react';
+import { promotionApi, saleApi, stockApi } from '@/lib/tauri';
 import { useAppStore } from '@/store/appStore';
 import type { Sale, CreateSale, Stock } from '@/types';
 
 export const useSales = () => {
   const [sales, setSales] = useState<Sale[]>([]);
   const [stocks, setStocks] = useState<Stock[]>([]);
+  const [promotionDiscount, setPromotionDiscount] = useState(0);
   const { cart, clearCart, addToast, setLoading } = useAppStore();
 
+  // Running promotions for the cart; the sale itself applies the same ones
+  useEffect(() => {
+    if (cart.length === 0) {
+      setPromotionDiscount(0);
+      return;
+    }
+    promotionApi
+      .kampaniyalariHesabla(
+        cart.map((item) => ({
+          mehsul_id: item.mehsul.id,
+          olcu_id: item.olcu_id,
+          reng_id: item.reng_id,
+          miqdar: item.miqdar,
+          vahid_qiymeti: item.vahid_qiymeti,
+        }))
+      )
+      .then((result) => setPromotionDiscount(result.endirim))
+      .catch((error) => {
+        setPromotionDiscount(0);
+        console.error(error);
+      });
+  }, [cart]);
+
   // Load sales
   const loadSales = useCallback(async () => {
     setLoading(true);
@@ -48,6 +72,7 @@ export const useSales = () => {
       items: cart.map((item) => ({
         mehsul_id: item.mehsul.id,
         olcu_id: item.olcu_id,
+        reng_id: item.reng_id,
         miqdar: item.miqdar,
         vahid_qiymeti: item.vahid_qiymeti,
       })),
@@ -105,6 +130,7 @@ export const useSales = () => {
     stocks,
     cart,
     cartTotal,
+    promotionDiscount,
     loadSales,
     loadStocks,
     createSale,
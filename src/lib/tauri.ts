@@ -1,14 +1,25 @@
-import { invoke } from '@tauri-apps/api/tauri';
+import { invoke as tauriInvoke } from '@tauri-apps/api/tauri';
+import { useAppStore } from '@/store/appStore';
 import type {
   Product,
   CreateProduct,
+  CreateProductMatrix,
+  ProductWithStock,
   UpdateProduct,
   Category,
   CreateCategory,
   Size,
   CreateSize,
+  SizeGroup,
+  CreateSizeGroup,
   Color,
   CreateColor,
+  Brand,
+  CreateBrand,
+  Promotion,
+  CreatePromotion,
+  SalePromotions,
+  CreateSaleItem,
   Stock,
   CreateStock,
   UpdateStock,
@@ -21,6 +32,8 @@ import type {
   LowStockAlert,
   SaleListItem,
   ProfitReport,
+  BrandReport,
+  PromotionReport,
   ProductStatisticsReport,
   ProductMovementDetail,
   Customer,
@@ -40,20 +53,68 @@ import type {
   UpdateUser,
   LoginRequest,
   LoginResponse,
+  Supplier,
+  CreateSupplier,
+  UpdateSupplier,
+  PurchaseOrder,
+  PurchaseOrderStatus,
+  PurchaseOrderWithItems,
+  CreatePurchaseOrder,
+  ReceivePurchaseOrder,
+  SupplierPayment,
+  CreateSupplierPayment,
+  SupplierDebtSummary,
+  SupplierStatement,
+  Shift,
+  OpenShift,
+  CloseShift,
+  ZReport,
+  AuditEntry,
+  AuditFilter,
+  SchemaVersion,
+  BackupFile,
+  AutoBackupStatus,
+  ResetScope,
+  ImportReport,
+  ExportRequest,
+  BarcodeSymbology,
+  BarcodeImageFormat,
+  Variant,
+  CreateVariant,
+  ScannedProduct,
+  ExportFormat,
 } from '@/types';
 
+const SESSION_ERROR = 'Sessiya etibarsızdır, yenidən daxil olun';
+
+// Every command except login needs the session token issued by giris_yap
+const invoke = async <T>(cmd: string, args: Record<string, unknown> = {}): Promise<T> => {
+  try {
+    return await tauriInvoke<T>(cmd, { ...args, token: useAppStore.getState().sessionToken });
+  } catch (err) {
+    if (err === SESSION_ERROR) {
+      useAppStore.getState().logout();
+    }
+    throw err;
+  }
+};
+
 // Product API
 export const productApi = {
   mehsulElaveEt: (mehsul: CreateProduct): Promise<Product> =>
     invoke('mehsul_elave_et', { mehsul }),
 
+  // Product plus a stock row per size/colour, created together
+  mehsulMatrisElaveEt: (matris: CreateProductMatrix): Promise<ProductWithStock> =>
+    invoke('mehsul_matris_elave_et', { matris }),
+
   mehsulSiyahisi: (): Promise<Product[]> =>
     invoke('mehsul_siyahisi'),
 
   mehsulAxtar: (axtaris: string): Promise<Product[]> =>
     invoke('mehsul_axtar', { axtaris }),
 
-  mehsulBarkodIleAxtar: (barkod: string): Promise<Product | null> =>
+  mehsulBarkodIleAxtar: (barkod: string): Promise<ScannedProduct | null> =>
     invoke('mehsul_barkod_ile_axtar', { barkod }),
 
   mehsulYenile: (id: number, mehsul: UpdateProduct): Promise<Product> =>
@@ -63,7 +124,45 @@ export const productApi = {
     invoke('mehsul_sil', { id }),
 };
 
+// Import API (CSV / Excel)
+export const variantApi = {
+  variantElaveEt: (variant: CreateVariant): Promise<Variant> =>
+    invoke('variant_elave_et', { variant }),
+
+  variantSiyahisi: (mehsulId: number): Promise<Variant[]> =>
+    invoke('variant_siyahisi', { mehsulId }),
+
+  // Only the barcode can change
+  variantYenile: (id: number, barkod: string): Promise<Variant> =>
+    invoke('variant_yenile', { id, barkod }),
+
+  variantSil: (id: number): Promise<void> => invoke('variant_sil', { id }),
+};
+
+export const importApi = {
+  // Dry run, nothing is written
+  mehsulIdxalYoxla: (faylYolu: string): Promise<ImportReport> =>
+    invoke('mehsul_idxal_yoxla', { faylYolu }),
+
+  // Writes nothing if any row has an error; check tetbiq_edildi
+  mehsulIdxalEt: (faylYolu: string): Promise<ImportReport> =>
+    invoke('mehsul_idxal_et', { faylYolu }),
+};
+
 // Category API
+export const barcodeApi = {
+  // Next internal EAN-13 from the store prefix in settings
+  barkodYarat: (): Promise<string> => invoke('barkod_yarat'),
+
+  // Rejects codes with a wrong check digit
+  barkodYoxla: (barkod: string): Promise<BarcodeSymbology> =>
+    invoke('barkod_yoxla', { barkod }),
+
+  // SVG markup, or a PNG data URL
+  barkodSekli: (barkod: string, format: BarcodeImageFormat): Promise<string> =>
+    invoke('barkod_sekli', { barkod, format }),
+};
+
 export const categoryApi = {
   kateqoriyaElaveEt: (kateqoriya: CreateCategory): Promise<Category> =>
     invoke('kateqoriya_elave_et', { kateqoriya }),
@@ -74,6 +173,18 @@ export const categoryApi = {
   kateqoriyaYenile: (id: number, ad: string): Promise<Category> =>
     invoke('kateqoriya_yenile', { id, ad }),
 
+  // null lets the category use every size
+  kateqoriyaOlcuQrupuTeyinEt: (id: number, olcuQrupuId: number | null): Promise<void> =>
+    invoke('kateqoriya_olcu_qrupu_teyin_et', { id, olcuQrupuId }),
+
+  // null makes it a top-level category
+  kateqoriyaKocur: (id: number, parentId: number | null): Promise<void> =>
+    invoke('kateqoriya_kocur', { id, parentId }),
+
+  // Products and sub-categories of the source move to the target
+  kateqoriyaBirlesdir: (menbeId: number, hedefId: number): Promise<void> =>
+    invoke('kateqoriya_birlesdir', { menbeId, hedefId }),
+
   kateqoriyaSil: (id: number): Promise<void> =>
     invoke('kateqoriya_sil', { id }),
 };
@@ -91,6 +202,29 @@ export const sizeApi = {
 
   olcuSil: (id: number): Promise<void> =>
     invoke('olcu_sil', { id }),
+
+  // Sizes of the category's size group, or all sizes without one
+  kateqoriyaOlculeri: (kateqoriyaId: number | null): Promise<Size[]> =>
+    invoke('kateqoriya_olculeri', { kateqoriyaId }),
+};
+
+// Size group API
+export const sizeGroupApi = {
+  olcuQrupuElaveEt: (qrup: CreateSizeGroup): Promise<SizeGroup> =>
+    invoke('olcu_qrupu_elave_et', { qrup }),
+
+  olcuQrupuSiyahisi: (): Promise<SizeGroup[]> =>
+    invoke('olcu_qrupu_siyahisi'),
+
+  olcuQrupuYenile: (id: number, ad: string): Promise<SizeGroup> =>
+    invoke('olcu_qrupu_yenile', { id, ad }),
+
+  // The listed sizes, in this order, become the group
+  olcuQrupuSirala: (id: number, olcuIdler: number[]): Promise<SizeGroup> =>
+    invoke('olcu_qrupu_sirala', { id, olcuIdler }),
+
+  olcuQrupuSil: (id: number): Promise<void> =>
+    invoke('olcu_qrupu_sil', { id }),
 };
 
 // Stock API
@@ -98,8 +232,8 @@ export const stockApi = {
   stokElaveEt: (stok: CreateStock): Promise<Stock> =>
     invoke('stok_elave_et', { stok }),
 
-  stokYenile: (mehsulId: number, olcuId: number, stok: UpdateStock): Promise<Stock> =>
-    invoke('stok_yenile', { mehsulId, olcuId, stok }),
+  stokYenile: (mehsulId: number, olcuId: number, stok: UpdateStock, rengId: number | null = null): Promise<Stock> =>
+    invoke('stok_yenile', { mehsulId, olcuId, rengId, stok }),
 
   stokSiyahisi: (): Promise<Stock[]> =>
     invoke('stok_siyahisi'),
@@ -107,8 +241,8 @@ export const stockApi = {
   stokMehsulUcun: (mehsulId: number): Promise<Stock[]> =>
     invoke('stok_mehsul_ucun', { mehsulId }),
 
-  stokSil: (mehsulId: number, olcuId: number): Promise<void> =>
-    invoke('stok_sil', { mehsulId, olcuId }),
+  stokSil: (mehsulId: number, olcuId: number, rengId: number | null = null): Promise<void> =>
+    invoke('stok_sil', { mehsulId, olcuId, rengId }),
 };
 
 // Sale API
@@ -161,19 +295,32 @@ export const reportApi = {
   satisSiyahisiTarixeGore: (baslangicTarix?: string, bitisTarix?: string): Promise<SaleListItem[]> =>
     invoke('satis_siyahisi_tarixe_gore', { baslangicTarix, bitisTarix }),
 
-  qazancHesabati: (baslangicTarix?: string, bitisTarix?: string): Promise<ProfitReport> =>
-    invoke('qazanc_hesabati', { baslangicTarix, bitisTarix }),
+  // A category filter also covers every sub-category below it
+  qazancHesabati: (baslangicTarix?: string, bitisTarix?: string, kateqoriyaId?: number): Promise<ProfitReport> =>
+    invoke('qazanc_hesabati', { baslangicTarix, bitisTarix, kateqoriyaId }),
 
-  stokDeyeriHesabati: (): Promise<StockValueReport> =>
-    invoke('stok_deyeri_hesabati'),
+  stokDeyeriHesabati: (kateqoriyaId?: number): Promise<StockValueReport> =>
+    invoke('stok_deyeri_hesabati', { kateqoriyaId }),
 
   mehsulStatistikasi: (baslangicTarix?: string, bitisTarix?: string, kateqoriyaId?: number): Promise<ProductStatisticsReport> =>
     invoke('mehsul_statistikasi', { baslangicTarix, bitisTarix, kateqoriyaId }),
 
+  markaHesabati: (baslangicTarix?: string, bitisTarix?: string): Promise<BrandReport> =>
+    invoke('marka_hesabati', { baslangicTarix, bitisTarix }),
+
+  kampaniyaHesabati: (baslangicTarix?: string, bitisTarix?: string): Promise<PromotionReport> =>
+    invoke('kampaniya_hesabati', { baslangicTarix, bitisTarix }),
+
   mehsulHereketleri: (mehsulId: number, baslangicTarix?: string, bitisTarix?: string): Promise<ProductMovementDetail[]> =>
     invoke('mehsul_hereketleri', { mehsulId, baslangicTarix, bitisTarix }),
 };
 
+// Export API: faylYolu comes from the save dialog; resolves to the path written
+export const exportApi = {
+  hesabatIxracEt: (sorgu: ExportRequest, format: ExportFormat, faylYolu: string): Promise<string> =>
+    invoke('hesabat_ixrac_et', { sorgu, format, faylYolu }),
+};
+
 // Payment API
 export const paymentApi = {
   borcOdemeYarat: (odeme: CreateDebtPayment): Promise<DebtPayment> =>
@@ -213,7 +360,10 @@ export const settingsApi = {
 // User API
 export const userApi = {
   girisYap: (login: LoginRequest): Promise<LoginResponse> =>
-    invoke('giris_yap', { login }),
+    tauriInvoke('giris_yap', { login }),
+
+  cixisEt: (): Promise<void> =>
+    invoke('cixis_et'),
 
   istifadeciElaveEt: (user: CreateUser): Promise<User> =>
     invoke('istifadeci_elave_et', { user }),
@@ -233,8 +383,28 @@ export const userApi = {
 
 // Database API
 export const databaseApi = {
-  databaziSifirla: (): Promise<void> =>
-    invoke('databazi_sifirla'),
+  // Takes a snapshot first and returns it
+  databaziSifirla: (sifre: string, hisseler: ResetScope[]): Promise<BackupFile> =>
+    invoke('databazi_sifirla', { sifre, hisseler }),
+
+  sxemVersiyasi: (): Promise<SchemaVersion> =>
+    invoke('sxem_versiyasi'),
+};
+
+// Backup API
+export const backupApi = {
+  ehtiyatYarat: (): Promise<BackupFile> =>
+    invoke('ehtiyat_yarat'),
+
+  ehtiyatSiyahisi: (): Promise<BackupFile[]> =>
+    invoke('ehtiyat_siyahisi'),
+
+  // Logs everyone out on success
+  ehtiyatBerpaEt: (ad: string): Promise<void> =>
+    invoke('ehtiyat_berpa_et', { ad }),
+
+  ehtiyatVeziyyeti: (): Promise<AutoBackupStatus> =>
+    invoke('ehtiyat_veziyyeti'),
 };
 
 // Printer API
@@ -257,3 +427,113 @@ export const colorApi = {
   rengSil: (id: number): Promise<void> =>
     invoke('reng_sil', { id }),
 };
+
+// Brand API
+export const brandApi = {
+  markaElaveEt: (marka: CreateBrand): Promise<Brand> =>
+    invoke('marka_elave_et', { marka }),
+
+  markaSiyahisi: (): Promise<Brand[]> =>
+    invoke('marka_siyahisi'),
+
+  // Renames the brand on its products too
+  markaYenile: (id: number, ad: string): Promise<Brand> =>
+    invoke('marka_yenile', { id, ad }),
+
+  markaSil: (id: number): Promise<void> =>
+    invoke('marka_sil', { id }),
+};
+
+// Promotion API
+export const promotionApi = {
+  kampaniyaElaveEt: (kampaniya: CreatePromotion): Promise<Promotion> =>
+    invoke('kampaniya_elave_et', { kampaniya }),
+
+  kampaniyaSiyahisi: (): Promise<Promotion[]> =>
+    invoke('kampaniya_siyahisi'),
+
+  kampaniyaYenile: (id: number, kampaniya: CreatePromotion): Promise<Promotion> =>
+    invoke('kampaniya_yenile', { id, kampaniya }),
+
+  kampaniyaAktivEt: (id: number, aktiv: boolean): Promise<Promotion> =>
+    invoke('kampaniya_aktiv_et', { id, aktiv }),
+
+  // Refused once a sale has used it; switch it off instead
+  kampaniyaSil: (id: number): Promise<void> =>
+    invoke('kampaniya_sil', { id }),
+
+  // What the running promotions take off a cart, before the sale is made
+  kampaniyalariHesabla: (items: CreateSaleItem[]): Promise<SalePromotions> =>
+    invoke('kampaniyalari_hesabla', { items }),
+};
+
+// Supplier API
+export const supplierApi = {
+  tedarukcuElaveEt: (tedarukcu: CreateSupplier): Promise<Supplier> =>
+    invoke('tedarukcu_elave_et', { tedarukcu }),
+
+  tedarukcuSiyahisi: (): Promise<Supplier[]> =>
+    invoke('tedarukcu_siyahisi'),
+
+  tedarukcuYenile: (id: number, tedarukcu: UpdateSupplier): Promise<Supplier> =>
+    invoke('tedarukcu_yenile', { id, tedarukcu }),
+
+  tedarukcuSil: (id: number): Promise<void> =>
+    invoke('tedarukcu_sil', { id }),
+};
+
+// Purchase Order API
+export const purchaseOrderApi = {
+  sifarisYarat: (sifaris: CreatePurchaseOrder): Promise<PurchaseOrderWithItems> =>
+    invoke('sifaris_yarat', { sifaris }),
+
+  sifarisSiyahisi: (status?: PurchaseOrderStatus): Promise<PurchaseOrder[]> =>
+    invoke('sifaris_siyahisi', { status: status ?? null }),
+
+  sifarisDetallari: (sifarisId: number): Promise<PurchaseOrderWithItems> =>
+    invoke('sifaris_detallari', { sifarisId }),
+
+  sifarisQebulEt: (sifarisId: number, qebul: ReceivePurchaseOrder): Promise<PurchaseOrderWithItems> =>
+    invoke('sifaris_qebul_et', { sifarisId, qebul }),
+
+  sifarisBagla: (sifarisId: number): Promise<PurchaseOrderWithItems> =>
+    invoke('sifaris_bagla', { sifarisId }),
+};
+
+// Supplier Payment API
+export const supplierPaymentApi = {
+  tedarukcuOdemeYarat: (odeme: CreateSupplierPayment): Promise<SupplierPayment> =>
+    invoke('tedarukcu_odeme_yarat', { odeme }),
+
+  tedarukcuOdemeSiyahisi: (tedarukcuId?: number): Promise<SupplierPayment[]> =>
+    invoke('tedarukcu_odeme_siyahisi', { tedarukcuId: tedarukcuId ?? null }),
+
+  tedarukcuBorcXulasesi: (): Promise<SupplierDebtSummary[]> =>
+    invoke('tedarukcu_borc_xulasesi'),
+
+  tedarukcuHesabCixarisi: (tedarukcuId: number): Promise<SupplierStatement> =>
+    invoke('tedarukcu_hesab_cixarisi', { tedarukcuId }),
+};
+
+// Shift API
+export const shiftApi = {
+  novbeAc: (novbe: OpenShift): Promise<Shift> =>
+    invoke('novbe_ac', { novbe }),
+
+  novbeBagla: (novbeId: number, baglama: CloseShift): Promise<ZReport> =>
+    invoke('novbe_bagla', { novbeId, baglama }),
+
+  aktivNovbe: (): Promise<Shift | null> =>
+    invoke('aktiv_novbe'),
+
+  novbeSiyahisi: (): Promise<Shift[]> =>
+    invoke('novbe_siyahisi'),
+
+  novbeZHesabati: (novbeId: number): Promise<ZReport> =>
+    invoke('novbe_z_hesabati', { novbeId }),
+};
+
+export const auditApi = {
+  auditJurnali: (filter?: AuditFilter): Promise<AuditEntry[]> =>
+    invoke('audit_jurnali', { filter }),
+};
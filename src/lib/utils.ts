@@ -32,12 +32,6 @@ export function formatDateShort(date: string | null): string {
   }).format(new Date(date));
 }
 
-export function generateBarcode(): string {
-  const timestamp = Date.now().toString().slice(-8);
-  const random = Math.floor(Math.random() * 10000).toString().padStart(4, '0');
-  return `${timestamp}${random}`;
-}
-
 export function debounce<T extends (...args: unknown[]) => unknown>(
   func: T,
   wait: number
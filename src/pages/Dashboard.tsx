@@ -77,7 +77,7 @@ export const Dashboard: React.FC = () => {
         const [daily, alerts, stockVal] = await Promise.all([
           reportApi.gunlukSatisHesabati(),
           reportApi.stokHesabati(),
-          reportApi.stokDeyeriHesabati(),
+          isAdmin() ? reportApi.stokDeyeriHesabati() : Promise.resolve(null),
         ]);
         setDailyReport(daily);
         setLowStockAlerts(alerts);
@@ -4,7 +4,7 @@ import { userApi } from '@/lib/tauri';
 import { Lock, User, LogIn, Loader2 } from 'lucide-react';
 
 export const Login: React.FC = () => {
-  const { setCurrentUser, setActivePage, addToast } = useAppStore();
+  const { setSession, setActivePage, addToast } = useAppStore();
   const [username, setUsername] = useState('');
   const [password, setPassword] = useState('');
   const [loading, setLoading] = useState(false);
@@ -26,7 +26,7 @@ export const Login: React.FC = () => {
         sifre: password,
       });
       
-      setCurrentUser(response.user);
+      setSession(response.user, response.token);
       setActivePage('dashboard');
       addToast('success', `Xoş gəlmisiniz, ${response.user.ad}!`);
     } catch (err: any) {
@@ -4,8 +4,8 @@ import { Button, Input, Select, Modal, Table } from '@/components/ui';
 import { BarcodePrintModal } from '@/components/barcode';
 import { useProducts, useBarkodOxucu } from '@/hooks';
 import { useAppStore } from '@/store/appStore';
-import { stockApi } from '@/lib/tauri';
-import { formatCurrency, generateBarcode, sortSizes } from '@/lib/utils';
+import { barcodeApi, stockApi } from '@/lib/tauri';
+import { formatCurrency, sortSizes } from '@/lib/utils';
 import { useForm } from 'react-hook-form';
 import { zodResolver } from '@hookform/resolvers/zod';
 import { z } from 'zod';
@@ -25,7 +25,11 @@ import {
 import type { Product, CreateProduct, Stock } from '@/types';
 
 const productSchema = z.object({
-  barkod: z.string().min(6, 'Barkod ən azı 6 simvol olmalıdır'),
+  // Left empty, the backend issues an internal barcode on save
+  barkod: z
+    .string()
+    .trim()
+    .refine((v) => v === '' || v.length >= 6, 'Barkod ən azı 6 simvol olmalıdır'),
   ad: z.string().min(2, 'Məhsulun adı ən azı 2 simvol olmalıdır'),
   kateqoriya_id: z.number().optional().nullable(),
   reng: z.string().optional().nullable(),
@@ -45,7 +49,7 @@ interface StockEntry {
 }
 
 export const Products: React.FC = () => {
-  const { products, categories, sizes, colors, createProduct, editProduct, deleteProduct, searchProducts, loadData } = useProducts();
+  const { products, categories, sizes, colors, createProduct, createProductMatrix, editProduct, deleteProduct, searchProducts, loadData } = useProducts();
   const { loading, addToast, isAdmin } = useAppStore();
   const [isModalOpen, setIsModalOpen] = useState(false);
   const [editingProduct, setEditingProduct] = useState<Product | null>(null);
@@ -82,23 +86,36 @@ export const Products: React.FC = () => {
     },
   });
 
-  // Initialize stock entries when sizes change or modal opens
+  const formKateqoriyaId = watch('kateqoriya_id');
+
+  // Initialize stock entries when sizes or the category change, or modal opens
   useEffect(() => {
     if (sizes.length > 0 && isModalOpen) {
-      initializeStockEntries();
+      initializeStockEntries(true);
     }
-  }, [sizes, isModalOpen]);
-
-  const initializeStockEntries = () => {
-    // Sort sizes: letters first (XS, S, M, L, XL, XXL, XXXL), then numbers (38, 40, 42...)
-    const sortedSizes = sortSizes(sizes);
+  }, [sizes, isModalOpen, formKateqoriyaId]);
+
+  const initializeStockEntries = (keepTyped = false) => {
+    // A category with a size group (its own or the nearest parent's) only
+    // offers that group's sizes, in its order
+    let kateqoriya = categories.find((c) => c.id === watch('kateqoriya_id'));
+    while (kateqoriya && kateqoriya.olcu_qrupu_id === null && kateqoriya.parent_id !== null) {
+      const parentId = kateqoriya.parent_id;
+      kateqoriya = categories.find((c) => c.id === parentId);
+    }
+    const qrupId = kateqoriya?.olcu_qrupu_id ?? null;
+    const sortedSizes = qrupId === null
+      ? sortSizes(sizes)
+      : sizes.filter((size) => size.qrup_id === qrupId).sort((a, b) => a.sira - b.sira);
     
-    const entries: StockEntry[] = sortedSizes.map((size) => ({
-      olcu_id: size.id,
-      olcu: size.olcu,
-      miqdar: 0,
-    }));
-    setStockEntries(entries);
+    // Quantities already typed in survive a category change
+    setStockEntries((prev) =>
+      sortedSizes.map((size) => ({
+        olcu_id: size.id,
+        olcu: size.olcu,
+        miqdar: (keepTyped && prev.find((entry) => entry.olcu_id === size.id)?.miqdar) || 0,
+      }))
+    );
   };
 
   // Load existing stock when editing
@@ -189,10 +206,18 @@ export const Products: React.FC = () => {
     return () => clearTimeout(timer);
   }, [searchQuery]);
 
+  const generateBarcode = async () => {
+    try {
+      setValue('barkod', await barcodeApi.barkodYarat());
+    } catch (error) {
+      addToast('error', `Barkod yaradıla bilmədi: ${error}`);
+    }
+  };
+
   const openAddModal = () => {
     setEditingProduct(null);
     reset({
-      barkod: generateBarcode(),
+      barkod: '',
       ad: '',
       kateqoriya_id: null,
       reng: '',
@@ -232,7 +257,7 @@ export const Products: React.FC = () => {
     );
   };
 
-  const saveStockEntries = async (productId: number) => {
+  const saveStockEntries = async (productId: number, rengId: number | null) => {
     // Get existing stocks for this product
     let existingStocks: Stock[] = [];
     try {
@@ -243,7 +268,7 @@ export const Products: React.FC = () => {
 
     for (const entry of stockEntries) {
       // Check if this size already has stock entry
-      const existingStock = existingStocks.find(s => s.olcu_id === entry.olcu_id);
+      const existingStock = existingStocks.find(s => s.olcu_id === entry.olcu_id && s.reng_id === rengId);
       
       // If stock exists, update it (even if 0)
       // If stock doesn't exist, only add if quantity > 0
@@ -252,7 +277,7 @@ export const Products: React.FC = () => {
           await stockApi.stokYenile(productId, entry.olcu_id, {
             miqdar: entry.miqdar,
             minimum_miqdar: 1,
-          });
+          }, rengId);
         } catch (error) {
           console.error(`Error saving stock for size ${entry.olcu}:`, error);
           addToast('error', `Stok xətası: ${entry.olcu} - ${error}`);
@@ -273,10 +298,27 @@ export const Products: React.FC = () => {
 
       if (editingProduct) {
         await editProduct(editingProduct.id, productData);
-        await saveStockEntries(editingProduct.id);
+        const reng = colors.find((c) => c.ad === productData.reng);
+        await saveStockEntries(editingProduct.id, reng?.id ?? null);
       } else {
-        const newProduct = await createProduct(productData);
-        await saveStockEntries(newProduct.id);
+        // Sizes with an opening quantity are created together with the product
+        const filled = stockEntries.filter((entry) => entry.miqdar > 0);
+        if (filled.length > 0) {
+          const reng = colors.find((c) => c.ad === productData.reng);
+          await createProductMatrix({
+            mehsul: productData,
+            olcu_idler: filled.map((entry) => entry.olcu_id),
+            reng_idler: reng ? [reng.id] : [],
+            minimum_miqdar: 1,
+            hucreler: filled.map((entry) => ({
+              olcu_id: entry.olcu_id,
+              reng_id: reng?.id ?? null,
+              miqdar: entry.miqdar,
+            })),
+          });
+        } else {
+          await createProduct(productData);
+        }
       }
       setIsModalOpen(false);
     } catch (error) {
@@ -632,7 +674,7 @@ export const Products: React.FC = () => {
                   type="button"
                   variant="secondary"
                   className="mt-7"
-                  onClick={() => setValue('barkod', generateBarcode())}
+                  onClick={generateBarcode}
                 >
                   Yarat
                 </Button>
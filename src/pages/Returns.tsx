@@ -94,7 +94,7 @@ export const Returns: React.FC = () => {
         for (const ret of saleReturns) {
           const retDetails = await returnApi.iadeDetallari(ret.id);
           retDetails.items.forEach(item => {
-            alreadyReturned.add(`${item.mehsul_id}-${item.olcu_id}`);
+            alreadyReturned.add(`${item.mehsul_id}-${item.olcu_id}-${item.reng_id ?? 0}`);
           });
         }
         setReturnedItems(alreadyReturned);
@@ -102,7 +102,7 @@ export const Returns: React.FC = () => {
         // Initialize return items with 0 quantities
         const items: { [key: string]: number } = {};
         details.items.forEach(item => {
-          items[`${item.mehsul_id}-${item.olcu_id}`] = 0;
+          items[`${item.mehsul_id}-${item.olcu_id}-${item.reng_id ?? 0}`] = 0;
         });
         setReturnItems(items);
       } else {
@@ -127,9 +127,10 @@ export const Returns: React.FC = () => {
   const getReturnTotal = () => {
     if (!foundSale) return 0;
     return foundSale.items.reduce((sum, item) => {
-      const key = `${item.mehsul_id}-${item.olcu_id}`;
+      const key = `${item.mehsul_id}-${item.olcu_id}-${item.reng_id ?? 0}`;
       const qty = returnItems[key] || 0;
-      return sum + (item.vahid_qiymeti * qty);
+      // Refunds go at what was paid, after any promotion
+      return sum + (item.toplam_qiymet / item.miqdar) * qty;
     }, 0);
   };
 
@@ -146,16 +147,17 @@ export const Returns: React.FC = () => {
     try {
       const items: CreateReturnItem[] = foundSale.items
         .filter(item => {
-          const key = `${item.mehsul_id}-${item.olcu_id}`;
+          const key = `${item.mehsul_id}-${item.olcu_id}-${item.reng_id ?? 0}`;
           return (returnItems[key] || 0) > 0;
         })
         .map(item => {
-          const key = `${item.mehsul_id}-${item.olcu_id}`;
+          const key = `${item.mehsul_id}-${item.olcu_id}-${item.reng_id ?? 0}`;
           return {
             mehsul_id: item.mehsul_id,
             olcu_id: item.olcu_id,
+            reng_id: item.reng_id,
             miqdar: returnItems[key],
-            vahid_qiymeti: item.vahid_qiymeti,
+            vahid_qiymeti: item.toplam_qiymet / item.miqdar,
           };
         });
 
@@ -389,7 +391,7 @@ export const Returns: React.FC = () => {
                 </h4>
                 <div className="border border-gray-200 rounded-lg divide-y divide-gray-100 max-h-64 overflow-auto">
                   {foundSale.items.map((item) => {
-                    const key = `${item.mehsul_id}-${item.olcu_id}`;
+                    const key = `${item.mehsul_id}-${item.olcu_id}-${item.reng_id ?? 0}`;
                     const qty = returnItems[key] || 0;
                     const isReturned = returnedItems.has(key);
                     return (
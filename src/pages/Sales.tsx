@@ -22,11 +22,20 @@ import {
   Clock,
   Printer,
 } from 'lucide-react';
-import type { Product, Stock, Sale, CartItem, Customer, CreateCustomer, SaleWithItems } from '@/types';
+import type {
+  Product,
+  Stock,
+  Sale,
+  CartItem,
+  Customer,
+  CreateCustomer,
+  SaleWithItems,
+  Variant,
+} from '@/types';
 
 export const Sales: React.FC = () => {
-  const { products, sizes, findByBarcode } = useProducts();
-  const { cartTotal, createSale, getProductStock, loadSales } = useSales();
+  const { products, findByBarcode } = useProducts();
+  const { cartTotal, promotionDiscount, createSale, getProductStock, loadSales } = useSales();
   const { cart, addToCart, removeFromCart, addToast } = useAppStore();
 
   const barkodInputRef = React.useRef<HTMLInputElement>(null);
@@ -35,7 +44,7 @@ export const Sales: React.FC = () => {
   const [productSearchQuery, setProductSearchQuery] = useState(''); // For product sidebar search
   const [selectedProduct, setSelectedProduct] = useState<Product | null>(null);
   const [productStocks, setProductStocks] = useState<Stock[]>([]);
-  const [selectedSize, setSelectedSize] = useState<number | null>(null);
+  const [selectedStockId, setSelectedStockId] = useState<number | null>(null);
   const [quantity, setQuantity] = useState(1);
   const [discount, setDiscount] = useState(0);
   const [paymentMethod, setPaymentMethod] = useState('Nağd');
@@ -90,7 +99,7 @@ export const Sales: React.FC = () => {
     onScan: async (barkod) => {
       const product = await findByBarcode(barkod);
       if (product) {
-        handleSelectProduct(product);
+        handleSelectProduct(product, product.variant);
         addToast('success', `Məhsul tapıldı: ${product.ad}`);
         // Re-focus input after scan
         setTimeout(() => barkodInputRef.current?.focus(), 100);
@@ -109,7 +118,7 @@ export const Sales: React.FC = () => {
       if (barkodSearch.length >= 6) {
         const product = await findByBarcode(barkodSearch);
         if (product) {
-          handleSelectProduct(product);
+          handleSelectProduct(product, product.variant);
           setBarkodSearch('');
           addToast('success', `Məhsul tapıldı: ${product.ad}`);
           // Re-focus input after search
@@ -153,32 +162,41 @@ export const Sales: React.FC = () => {
     }
   }, [paymentMethod]);
 
-  const handleSelectProduct = async (product: Product) => {
+  // A scanned variant barcode already tells the size and colour
+  const handleSelectProduct = async (product: Product, variant: Variant | null = null) => {
     setSelectedProduct(product);
     setQuantity(1);
-    setSelectedSize(null);
+    setSelectedStockId(null);
 
     const stocks = await getProductStock(product.id);
     setProductStocks(stocks);
+    if (variant) {
+      const stock = stocks.find(
+        (s) => s.olcu_id === variant.olcu_id && s.reng_id === variant.reng_id
+      );
+      setSelectedStockId(stock?.id ?? null);
+    }
   };
 
   const handleAddToCart = () => {
-    if (!selectedProduct || !selectedSize) {
+    if (!selectedProduct || !selectedStockId) {
       addToast('warning', 'Zəhmət olmasa ölçü seçin');
       return;
     }
 
-    const stock = productStocks.find((s) => s.olcu_id === selectedSize);
+    // Stock is kept per size and colour, so the line carries both
+    const stock = productStocks.find((s) => s.id === selectedStockId);
     if (!stock || stock.miqdar < quantity) {
       addToast('error', 'Stokda kifayət qədər məhsul yoxdur');
       return;
     }
 
-    const sizeInfo = sizes.find((s) => s.id === selectedSize);
     const cartItem: CartItem = {
       mehsul: selectedProduct,
-      olcu_id: selectedSize,
-      olcu: sizeInfo?.olcu || '',
+      olcu_id: stock.olcu_id,
+      olcu: stock.olcu || '',
+      reng_id: stock.reng_id,
+      reng: stock.reng,
       miqdar: quantity,
       vahid_qiymeti: selectedProduct.satis_qiymeti,
     };
@@ -269,7 +287,7 @@ export const Sales: React.FC = () => {
     return true; // Show all if no search
   }).slice(0, 20); // Limit to 20 products
 
-  const finalTotal = cartTotal - discount;
+  const finalTotal = cartTotal - promotionDiscount - discount;
 
   return (
     <div className="h-screen flex flex-col">
@@ -323,19 +341,20 @@ export const Sales: React.FC = () => {
                 <div className="flex flex-wrap gap-2">
                   {productStocks.map((stock) => (
                     <button
-                      key={stock.olcu_id}
-                      onClick={() => setSelectedSize(stock.olcu_id)}
+                      key={stock.id}
+                      onClick={() => setSelectedStockId(stock.id)}
                       disabled={stock.miqdar === 0}
                       className={`px-4 py-2 rounded-lg border text-sm font-medium transition-all
                         ${
-                          selectedSize === stock.olcu_id
+                          selectedStockId === stock.id
                             ? 'bg-primary-500 text-white border-primary-500'
                             : stock.miqdar > 0
                             ? 'bg-white text-gray-700 border-gray-300 hover:border-primary-500'
                             : 'bg-gray-100 text-gray-400 border-gray-200 cursor-not-allowed'
                         }`}
                     >
-                      {stock.olcu} ({stock.miqdar})
+                      {stock.olcu}
+                      {stock.reng ? ` / ${stock.reng}` : ''} ({stock.miqdar})
                     </button>
                   ))}
                 </div>
@@ -399,7 +418,9 @@ export const Sales: React.FC = () => {
                           {item.mehsul.ad}
                         </p>
                         <p className="text-sm text-gray-500">
-                          Ölçü: {item.olcu} • {formatCurrency(item.vahid_qiymeti)} x{' '}
+                          Ölçü: {item.olcu}
+                          {item.reng ? ` • Rəng: ${item.reng}` : ''} •{' '}
+                          {formatCurrency(item.vahid_qiymeti)} x{' '}
                           {item.miqdar}
                         </p>
                       </div>
@@ -499,6 +520,12 @@ export const Sales: React.FC = () => {
               <span>Cəmi:</span>
               <span>{formatCurrency(cartTotal)}</span>
             </div>
+            {promotionDiscount > 0 && (
+              <div className="flex justify-between text-gray-600">
+                <span>Kampaniya endirimi:</span>
+                <span>-{formatCurrency(promotionDiscount)}</span>
+              </div>
+            )}
             <div className="flex justify-between text-gray-600">
               <span>Endirim:</span>
               <span>-{formatCurrency(discount)}</span>
@@ -37,6 +37,7 @@ export const SalesList: React.FC = () => {
   const [returnItem, setReturnItem] = useState<{
     mehsul_id: number;
     olcu_id: number;
+    reng_id: number | null;
     mehsul_adi: string;
     olcu: string;
     miqdar: number;
@@ -98,6 +99,7 @@ export const SalesList: React.FC = () => {
   const openReturnModal = (item: {
     mehsul_id: number;
     olcu_id: number;
+    reng_id: number | null;
     mehsul_adi: string;
     olcu: string;
     miqdar: number;
@@ -123,6 +125,7 @@ export const SalesList: React.FC = () => {
       const items: CreateReturnItem[] = [{
         mehsul_id: returnItem.mehsul_id,
         olcu_id: returnItem.olcu_id,
+        reng_id: returnItem.reng_id,
         miqdar: returnQuantity,
         vahid_qiymeti: returnItem.vahid_qiymeti,
       }];
@@ -489,10 +492,11 @@ export const SalesList: React.FC = () => {
                               onClick={() => openReturnModal({
                                 mehsul_id: item.mehsul_id,
                                 olcu_id: item.olcu_id,
+                                reng_id: item.reng_id,
                                 mehsul_adi: item.mehsul_adi || '',
                                 olcu: item.olcu || '',
                                 miqdar: item.miqdar,
-                                vahid_qiymeti: item.vahid_qiymeti,
+                                vahid_qiymeti: item.toplam_qiymet / item.miqdar,
                                 maxMiqdar: item.miqdar - (item.iade_miqdar || 0),
                               })}
                               disabled={hasReturn ? (item.miqdar - item.iade_miqdar <= 0) : false}
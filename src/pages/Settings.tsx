@@ -1,7 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { Header } from '@/components/layout';
 import { Button, Modal } from '@/components/ui';
-import { settingsApi, databaseApi } from '@/lib/tauri';
+import { settingsApi, databaseApi, backupApi } from '@/lib/tauri';
 import { useAppStore } from '@/store/appStore';
 import { open } from '@tauri-apps/api/dialog';
 import { convertFileSrc } from '@tauri-apps/api/tauri';
@@ -29,7 +29,7 @@ import {
   CheckCircle,
   Info,
 } from 'lucide-react';
-import type { UpdateSettings } from '@/types';
+import type { UpdateSettings, ResetScope, AutoBackupStatus } from '@/types';
 
 type TabType = 'magaza' | 'proqram' | 'yenileme' | 'tehluke';
 
@@ -49,6 +49,9 @@ export const SettingsPage: React.FC = () => {
   const [resetModalOpen, setResetModalOpen] = useState(false);
   const [resetting, setResetting] = useState(false);
   const [confirmText, setConfirmText] = useState('');
+  const [resetPassword, setResetPassword] = useState('');
+  const [resetScopes, setResetScopes] = useState<ResetScope[]>(['emeliyyatlar']);
+  const [backupStatus, setBackupStatus] = useState<AutoBackupStatus | null>(null);
 
   // Update states
   const [updateInfo, setUpdateInfo] = useState<UpdateInfo | null>(null);
@@ -95,8 +98,19 @@ export const SettingsPage: React.FC = () => {
   useEffect(() => {
     loadSettings();
     loadCurrentVersion();
+    if (currentUser?.rol?.toLowerCase() === 'admin') {
+      loadBackupStatus();
+    }
   }, []);
 
+  const loadBackupStatus = async () => {
+    try {
+      setBackupStatus(await backupApi.ehtiyatVeziyyeti());
+    } catch (error) {
+      console.error('Error loading backup status:', error);
+    }
+  };
+
   const loadCurrentVersion = async () => {
     try {
       const version = await getVersion();
@@ -214,6 +228,24 @@ export const SettingsPage: React.FC = () => {
     }
   };
 
+  const closeResetModal = () => {
+    setResetModalOpen(false);
+    setConfirmText('');
+    setResetPassword('');
+    setResetScopes(['emeliyyatlar']);
+  };
+
+  // Catalog and customers are referenced by sales, so they need operations too
+  const toggleResetScope = (scope: ResetScope) => {
+    setResetScopes((prev) => {
+      if (prev.includes(scope)) {
+        return scope === 'emeliyyatlar' ? [] : prev.filter((s) => s !== scope);
+      }
+      const next = [...prev, scope];
+      return next.includes('emeliyyatlar') ? next : ['emeliyyatlar', ...next];
+    });
+  };
+
   const handleResetDatabase = async () => {
     if (confirmText !== 'SIFIRLA') {
       addToast('error', 'Təsdiq mətni düzgün deyil!');
@@ -222,10 +254,9 @@ export const SettingsPage: React.FC = () => {
 
     setResetting(true);
     try {
-      await databaseApi.databaziSifirla();
-      addToast('success', 'Databaza uğurla sıfırlandı!');
-      setResetModalOpen(false);
-      setConfirmText('');
+      const ehtiyat = await databaseApi.databaziSifirla(resetPassword, resetScopes);
+      addToast('success', `Databaza uğurla sıfırlandı! Ehtiyat nüsxəsi: ${ehtiyat.ad}`);
+      closeResetModal();
       // Reload the app
       setTimeout(() => {
         window.location.reload();
@@ -643,6 +674,36 @@ export const SettingsPage: React.FC = () => {
               </div>
             </div>
 
+            {/* Avtomatik ehtiyat nüsxəsi */}
+            {backupStatus && (
+              <div className="border-t pt-6">
+                <h3 className="text-sm font-semibold text-gray-900 mb-4 flex items-center gap-2">
+                  <Database className="w-4 h-4" />
+                  Avtomatik Ehtiyat Nüsxəsi
+                </h3>
+
+                {backupStatus.xeta ? (
+                  <div className="flex items-start gap-3 p-4 bg-red-50 border border-red-200 rounded-xl">
+                    <AlertTriangle className="w-5 h-5 text-red-600 flex-shrink-0" />
+                    <div>
+                      <p className="font-medium text-red-800">Son avtomatik ehtiyat nüsxəsi alına bilmədi</p>
+                      <p className="text-sm text-red-700">{backupStatus.xeta}</p>
+                      <p className="text-xs text-red-500 mt-1">Yoxlama: {backupStatus.son_yoxlama}</p>
+                    </div>
+                  </div>
+                ) : (
+                  <div className="flex items-center gap-3 p-4 bg-gray-50 rounded-xl">
+                    <CheckCircle className="w-5 h-5 text-green-600" />
+                    <p className="text-sm text-gray-700">
+                      {backupStatus.son_yoxlama
+                        ? `Son yoxlama uğurlu oldu: ${backupStatus.son_yoxlama}`
+                        : 'Hələ yoxlanılmayıb'}
+                    </p>
+                  </div>
+                )}
+              </div>
+            )}
+
             {/* Saxla düyməsi */}
             <div className="flex justify-end pt-4 border-t">
               <Button
@@ -799,32 +860,28 @@ export const SettingsPage: React.FC = () => {
                     </h3>
                   </div>
                   <p className="text-gray-600 text-sm mb-2">
-                    Bütün məhsullar, satışlar, müştərilər və digər məlumatlar silinəcək.
+                    Seçilmiş məlumatlar silinəcək. Sıfırlamadan əvvəl avtomatik ehtiyat nüsxəsi alınır.
                   </p>
                   <ul className="text-sm text-gray-500 space-y-1">
                     <li className="flex items-center gap-2">
                       <span className="text-red-500">✗</span>
-                      Məhsullar və stok
+                      Satışlar, geri qaytarmalar, ödənişlər və növbələr
                     </li>
                     <li className="flex items-center gap-2">
                       <span className="text-red-500">✗</span>
-                      Satışlar və geri qaytarmalar
+                      Məhsullar, stok, kateqoriyalar və tədarükçülər (seçilsə)
                     </li>
                     <li className="flex items-center gap-2">
                       <span className="text-red-500">✗</span>
-                      Müştərilər və borc məlumatları
-                    </li>
-                    <li className="flex items-center gap-2">
-                      <span className="text-red-500">✗</span>
-                      Parametrlər (logo, ünvan və s.)
+                      Müştərilər (seçilsə)
                     </li>
                     <li className="flex items-center gap-2">
                       <span className="text-green-500">✓</span>
-                      İstifadəçilər qalacaq
+                      İstifadəçilər və parametrlər qalacaq
                     </li>
                     <li className="flex items-center gap-2">
                       <span className="text-green-500">✓</span>
-                      Ölçülər qalacaq
+                      Ölçülər, rənglər və audit jurnalı qalacaq
                     </li>
                   </ul>
                 </div>
@@ -845,10 +902,7 @@ export const SettingsPage: React.FC = () => {
       {/* Reset Confirmation Modal */}
       <Modal
         isOpen={resetModalOpen}
-        onClose={() => {
-          setResetModalOpen(false);
-          setConfirmText('');
-        }}
+        onClose={closeResetModal}
         title="Databazanı Sıfırla"
         size="md"
       >
@@ -861,13 +915,45 @@ export const SettingsPage: React.FC = () => {
                   Bu əməliyyat geri qaytarıla bilməz!
                 </h4>
                 <p className="text-sm text-red-700">
-                  Bütün məhsullar, satışlar, müştərilər və digər məlumatlar həmişəlik silinəcək.
-                  Yalnız istifadəçilər və ölçülər qalacaq.
+                  Seçilmiş məlumatlar silinəcək. Əvvəlcə ehtiyat nüsxəsi alınır və lazım olsa
+                  oradan bərpa edə bilərsiniz.
                 </p>
               </div>
             </div>
           </div>
 
+          <div className="space-y-2">
+            <p className="text-sm font-medium text-gray-700">Nələr silinsin:</p>
+            {([
+              ['emeliyyatlar', 'Əməliyyatlar (satışlar, qaytarmalar, ödənişlər, növbələr, sifarişlər)'],
+              ['kataloq', 'Kataloq (məhsullar, stok, kateqoriyalar, tədarükçülər)'],
+              ['musteriler', 'Müştərilər'],
+            ] as [ResetScope, string][]).map(([scope, label]) => (
+              <label key={scope} className="flex items-center gap-2 text-sm text-gray-700">
+                <input
+                  type="checkbox"
+                  checked={resetScopes.includes(scope)}
+                  onChange={() => toggleResetScope(scope)}
+                  className="rounded border-gray-300 text-red-600 focus:ring-red-500"
+                />
+                {label}
+              </label>
+            ))}
+          </div>
+
+          <div>
+            <label className="block text-sm font-medium text-gray-700 mb-2">
+              Şifrənizi daxil edin:
+            </label>
+            <input
+              type="password"
+              value={resetPassword}
+              onChange={(e) => setResetPassword(e.target.value)}
+              className="w-full px-4 py-3 border border-gray-300 rounded-xl focus:outline-none focus:ring-2 focus:ring-red-500 focus:border-transparent"
+              autoComplete="current-password"
+            />
+          </div>
+
           <div>
             <label className="block text-sm font-medium text-gray-700 mb-2">
               Təsdiq etmək üçün <span className="font-bold text-red-600">SIFIRLA</span> yazın:
@@ -885,10 +971,7 @@ export const SettingsPage: React.FC = () => {
           <div className="flex justify-end gap-3 pt-4 border-t">
             <Button
               variant="secondary"
-              onClick={() => {
-                setResetModalOpen(false);
-                setConfirmText('');
-              }}
+              onClick={closeResetModal}
               disabled={resetting}
             >
               Ləğv et
@@ -896,7 +979,7 @@ export const SettingsPage: React.FC = () => {
             <Button
               variant="danger"
               onClick={handleResetDatabase}
-              disabled={confirmText !== 'SIFIRLA' || resetting}
+              disabled={confirmText !== 'SIFIRLA' || !resetPassword || resetScopes.length === 0 || resetting}
               icon={resetting ? <Loader2 className="w-4 h-4 animate-spin" /> : <Database className="w-4 h-4" />}
             >
               {resetting ? 'Sıfırlanır...' : 'Databazanı Sıfırla'}
@@ -119,7 +119,8 @@ export const StockPage: React.FC = () => {
       await stockApi.stokYenile(
         editingStock.mehsul_id,
         editingStock.olcu_id,
-        { miqdar: quantity, minimum_miqdar: minQuantity }
+        { miqdar: quantity, minimum_miqdar: minQuantity },
+        editingStock.reng_id
       );
       addToast('success', 'Stok uğurla yeniləndi');
       setIsEditModalOpen(false);
@@ -241,7 +242,7 @@ export const StockPage: React.FC = () => {
     try {
       await stockApi.stokYenile(stock.mehsul_id, stock.olcu_id, {
         miqdar: stock.miqdar + change,
-      });
+      }, stock.reng_id);
       loadStocks();
     } catch (error) {
       addToast('error', 'Stok yenilənə bilmədi');
@@ -252,7 +253,7 @@ export const StockPage: React.FC = () => {
     if (!deleteConfirm) return;
 
     try {
-      await stockApi.stokSil(deleteConfirm.mehsul_id, deleteConfirm.olcu_id);
+      await stockApi.stokSil(deleteConfirm.mehsul_id, deleteConfirm.olcu_id, deleteConfirm.reng_id);
       addToast('success', 'Stok uğurla silindi!');
       setDeleteConfirm(null);
       loadStocks();
@@ -11,7 +11,9 @@ interface Toast {
 interface AppState {
   // Auth
   currentUser: User | null;
+  sessionToken: string | null;
   setCurrentUser: (user: User | null) => void;
+  setSession: (user: User, token: string) => void;
   isAdmin: () => boolean;
   logout: () => void;
 
@@ -65,9 +67,11 @@ export const useAppStore = create<AppState>()(
     (set, get) => ({
       // Auth
       currentUser: null,
+      sessionToken: null,
       setCurrentUser: (user) => set({ currentUser: user }),
+      setSession: (user, token) => set({ currentUser: user, sessionToken: token }),
       isAdmin: () => get().currentUser?.rol === 'admin',
-      logout: () => set({ currentUser: null, activePage: 'login' }),
+      logout: () => set({ currentUser: null, sessionToken: null, activePage: 'login' }),
 
       // Navigation
       activePage: 'login',
@@ -106,7 +110,10 @@ export const useAppStore = create<AppState>()(
   addToCart: (item) =>
     set((state) => {
       const existingIndex = state.cart.findIndex(
-        (c) => c.mehsul.id === item.mehsul.id && c.olcu_id === item.olcu_id
+        (c) =>
+          c.mehsul.id === item.mehsul.id &&
+          c.olcu_id === item.olcu_id &&
+          c.reng_id === item.reng_id
       );
       if (existingIndex >= 0) {
         const newCart = [...state.cart];
@@ -152,7 +159,7 @@ export const useAppStore = create<AppState>()(
     }),
     {
       name: 'app-storage',
-      partialize: (state) => ({ currentUser: state.currentUser }),
+      partialize: (state) => ({ currentUser: state.currentUser, sessionToken: state.sessionToken }),
     }
   )
 );
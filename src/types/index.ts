@@ -7,6 +7,7 @@ export interface Product {
   kateqoriya_adi: string | null;
   reng: string | null;
   marka: string | null;
+  marka_id: number | null;
   alis_qiymeti: number;
   satis_qiymeti: number;
   tesvir: string | null;
@@ -21,6 +22,7 @@ export interface CreateProduct {
   kateqoriya_id?: number | null;
   reng?: string | null;
   marka?: string | null;
+  marka_id?: number | null;
   alis_qiymeti: number;
   satis_qiymeti: number;
   tesvir?: string | null;
@@ -33,6 +35,7 @@ export interface UpdateProduct {
   kateqoriya_id?: number | null;
   reng?: string | null;
   marka?: string | null;
+  marka_id?: number | null;
   alis_qiymeti?: number;
   satis_qiymeti?: number;
   tesvir?: string | null;
@@ -43,22 +46,41 @@ export interface UpdateProduct {
 export interface Category {
   id: number;
   ad: string;
+  parent_id: number | null;
+  olcu_qrupu_id: number | null;
   created_at: string | null;
 }
 
 export interface CreateCategory {
   ad: string;
+  parent_id?: number | null;
+  olcu_qrupu_id?: number | null;
 }
 
 // Size Types
 export interface Size {
   id: number;
   olcu: string;
+  qrup_id: number | null;
+  sira: number;
   created_at: string | null;
 }
 
 export interface CreateSize {
   olcu: string;
+  qrup_id?: number | null;
+}
+
+// A size scale (letter, trouser waist, EU shoe...) with its sizes in order
+export interface SizeGroup {
+  id: number;
+  ad: string;
+  olculer: Size[];
+  created_at: string | null;
+}
+
+export interface CreateSizeGroup {
+  ad: string;
 }
 
 // Color Types
@@ -74,6 +96,62 @@ export interface CreateColor {
   kod?: string | null;
 }
 
+// Promotion Types
+export type PromotionType = 'faiz' | 'sabit' | 'bogo' | 'uce_iki' | 'deste';
+
+// No scope set means the promotion covers the whole shop
+export interface Promotion {
+  id: number;
+  ad: string;
+  novu: PromotionType;
+  deyer: number;
+  deste_miqdari: number | null;
+  mehsul_id: number | null;
+  kateqoriya_id: number | null;
+  marka_id: number | null;
+  olcu_id: number | null;
+  baslangic_tarixi: string | null;
+  bitis_tarixi: string | null;
+  aktiv: boolean;
+  created_at: string | null;
+}
+
+export interface CreatePromotion {
+  ad: string;
+  novu: PromotionType;
+  deyer?: number;
+  deste_miqdari?: number | null;
+  mehsul_id?: number | null;
+  kateqoriya_id?: number | null;
+  marka_id?: number | null;
+  olcu_id?: number | null;
+  baslangic_tarixi?: string | null;
+  bitis_tarixi?: string | null;
+}
+
+export interface SaleLinePromotion {
+  kampaniya_id: number | null;
+  kampaniya_adi: string | null;
+  endirim: number;
+}
+
+// setirler follows the order of the cart lines
+export interface SalePromotions {
+  setirler: SaleLinePromotion[];
+  endirim: number;
+}
+
+// Brand Types
+export interface Brand {
+  id: number;
+  ad: string;
+  created_at: string | null;
+}
+
+export interface CreateBrand {
+  ad: string;
+}
+
 // Stock Types
 export interface Stock {
   id: number;
@@ -84,6 +162,8 @@ export interface Stock {
   kateqoriya_adi: string | null;
   olcu_id: number;
   olcu: string | null;
+  reng_id: number | null;
+  reng: string | null;
   miqdar: number;
   minimum_miqdar: number;
   created_at: string | null;
@@ -93,6 +173,7 @@ export interface Stock {
 export interface CreateStock {
   mehsul_id: number;
   olcu_id: number;
+  reng_id?: number | null;
   miqdar: number;
   minimum_miqdar?: number;
 }
@@ -105,6 +186,8 @@ export interface UpdateStock {
 export interface StockInfo {
   olcu_id: number;
   olcu: string;
+  reng_id: number | null;
+  reng: string | null;
   miqdar: number;
   minimum_miqdar: number;
 }
@@ -114,6 +197,7 @@ export interface LowStockAlert {
   mehsul_adi: string;
   barkod: string;
   olcu: string;
+  reng: string | null;
   miqdar: number;
   minimum_miqdar: number;
 }
@@ -152,6 +236,7 @@ export interface CustomerSaleItem {
   endirim: number;
   son_mebleg: number;
   odenis_usulu: string;
+  nisye_mebleg: number;
   created_at: string;
 }
 
@@ -172,14 +257,30 @@ export interface Sale {
 export interface CreateSale {
   items: CreateSaleItem[];
   endirim: number;
-  odenis_usulu: string;
+  odenis_usulu?: string | null;
+  odenisler?: CreateSalePayment[];
   musteri_id?: number | null;
   qeyd?: string | null;
 }
 
+export interface CreateSalePayment {
+  odenis_usulu: string;
+  mebleg: number;
+}
+
+export interface SalePayment {
+  id: number;
+  satis_id: number;
+  odenis_usulu: string;
+  mebleg: number;
+}
+
+// Either mehsul_id + olcu_id, or a scanned variant barcode
 export interface CreateSaleItem {
-  mehsul_id: number;
-  olcu_id: number;
+  mehsul_id?: number;
+  olcu_id?: number;
+  reng_id?: number | null;
+  variant_barkod?: string | null;
   miqdar: number;
   vahid_qiymeti: number;
 }
@@ -192,9 +293,14 @@ export interface SaleItem {
   mehsul_barkod: string | null;
   olcu_id: number;
   olcu: string | null;
+  reng_id: number | null;
+  reng: string | null;
   miqdar: number;
   vahid_qiymeti: number;
-  toplam_qiymet: number;
+  toplam_qiymet: number;  // Kampaniya endirimi çıxıldıqdan sonra
+  endirim: number;
+  kampaniya_id: number | null;
+  kampaniya_adi: string | null;
   created_at: string | null;
   iade_miqdar: number;  // Qaytarılmış miqdar
 }
@@ -202,6 +308,7 @@ export interface SaleItem {
 // SaleWithItems - serde flatten olduğu üçün Sale fieldləri birbaşa root-dadır
 export interface SaleWithItems extends Sale {
   items: SaleItem[];
+  odenisler: SalePayment[];
 }
 
 // Report Types
@@ -266,6 +373,55 @@ export interface ProfitReport {
   net_qazanc: number;
 }
 
+// Products without a brand come as marka_id null, marka "Markasız"
+export interface BrandReportItem {
+  marka_id: number | null;
+  marka: string;
+  satis_miqdar: number;
+  satis_deyeri: number;
+  maya_deyeri: number;
+  endirim: number;
+  qazanc: number;
+  qazanc_faizi: number;
+  stok_miqdari: number;
+  stok_alis_deyeri: number;
+  stok_satis_deyeri: number;
+}
+
+export interface PromotionReportItem {
+  kampaniya_id: number;
+  kampaniya: string;
+  novu: PromotionType;
+  satis_sayi: number;
+  miqdar: number;
+  brutto_satis: number;
+  endirim: number;
+  net_satis: number;
+  maya_deyeri: number;
+  qazanc: number;
+  endirim_faizi: number;
+}
+
+export interface PromotionReport {
+  baslangic_tarix: string;
+  bitis_tarix: string;
+  items: PromotionReportItem[];
+  toplam_endirim: number;
+  toplam_net_satis: number;
+  umumi_satis: number;
+  kampaniya_payi: number;
+}
+
+export interface BrandReport {
+  baslangic_tarix: string;
+  bitis_tarix: string;
+  items: BrandReportItem[];
+  toplam_satis: number;
+  toplam_qazanc: number;
+  toplam_stok_alis_deyeri: number;
+  toplam_stok_satis_deyeri: number;
+}
+
 // Product Statistics Types
 export interface ProductStatistics {
   mehsul_id: number;
@@ -309,6 +465,8 @@ export interface CartItem {
   mehsul: Product;
   olcu_id: number;
   olcu: string;
+  reng_id: number | null;
+  reng: string | null;
   miqdar: number;
   vahid_qiymeti: number;
 }
@@ -362,6 +520,8 @@ export interface ReturnItem {
   mehsul_barkod: string | null;
   olcu_id: number;
   olcu: string | null;
+  reng_id: number | null;
+  reng: string | null;
   miqdar: number;
   vahid_qiymeti: number;
   toplam_qiymet: number;
@@ -378,6 +538,7 @@ export interface CreateReturn {
 export interface CreateReturnItem {
   mehsul_id: number;
   olcu_id: number;
+  reng_id?: number | null;
   miqdar: number;
   vahid_qiymeti: number;
 }
@@ -399,6 +560,13 @@ export interface Settings {
   olculer_aktiv: boolean;
   qifil_sifresi: string | null;
   barkod_capinda_magaza_adi: boolean;
+  menfi_stok_icazesi: boolean;
+  maya_deyeri_usulu: 'FIFO' | 'Orta';
+  // Tokens: {IL} year, {NOMRE} or {NOMRE:6} zero-padded counter
+  satis_nomre_formati: string;
+  iade_nomre_formati: string;
+  // Start of internal EAN-13 codes, 2–6 digits beginning with 2
+  barkod_prefiksi: string;
   updated_at: string | null;
 }
 
@@ -413,6 +581,11 @@ export interface UpdateSettings {
   olculer_aktiv?: boolean | null;
   qifil_sifresi?: string | null;
   barkod_capinda_magaza_adi?: boolean | null;
+  menfi_stok_icazesi?: boolean | null;
+  maya_deyeri_usulu?: 'FIFO' | 'Orta' | null;
+  satis_nomre_formati?: string | null;
+  iade_nomre_formati?: string | null;
+  barkod_prefiksi?: string | null;
 }
 
 // Toast Types
@@ -455,5 +628,361 @@ export interface LoginRequest {
 
 export interface LoginResponse {
   user: User;
+  token: string;
   message: string;
 }
+
+// Supplier Types
+export interface Supplier {
+  id: number;
+  ad: string;
+  elaqe_sexsi: string | null;
+  telefon: string | null;
+  email: string | null;
+  unvan: string | null;
+  qeyd: string | null;
+  aktiv: boolean;
+  baslangic_borcu: number;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface CreateSupplier {
+  ad: string;
+  elaqe_sexsi?: string | null;
+  telefon?: string | null;
+  email?: string | null;
+  unvan?: string | null;
+  qeyd?: string | null;
+  baslangic_borcu?: number | null;
+}
+
+export interface UpdateSupplier {
+  ad?: string | null;
+  elaqe_sexsi?: string | null;
+  telefon?: string | null;
+  email?: string | null;
+  unvan?: string | null;
+  qeyd?: string | null;
+  aktiv?: boolean | null;
+}
+
+// Purchase Order Types
+export type PurchaseOrderStatus = 'Açıq' | 'Qismən qəbul edilib' | 'Qəbul edilib' | 'Bağlanıb';
+
+export interface PurchaseOrder {
+  id: number;
+  sifaris_nomresi: string;
+  tedarukcu_id: number;
+  tedarukcu_adi: string | null;
+  status: PurchaseOrderStatus;
+  odenis_usulu: string;
+  gozlenilen_tarix: string | null;
+  qeyd: string | null;
+  toplam_mebleg: number;
+  qebul_mebleg: number;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface PurchaseOrderItem {
+  id: number;
+  sifaris_id: number;
+  mehsul_id: number;
+  mehsul_adi: string | null;
+  mehsul_barkod: string | null;
+  olcu_id: number;
+  olcu: string | null;
+  reng_id: number | null;
+  reng: string | null;
+  sifaris_miqdari: number;
+  qebul_miqdari: number;
+  vahid_alis_qiymeti: number;
+}
+
+export interface PurchaseOrderWithItems extends PurchaseOrder {
+  items: PurchaseOrderItem[];
+}
+
+export interface CreatePurchaseOrder {
+  tedarukcu_id: number;
+  odenis_usulu?: string | null;
+  gozlenilen_tarix?: string | null;
+  qeyd?: string | null;
+  items: CreatePurchaseOrderItem[];
+}
+
+export interface CreatePurchaseOrderItem {
+  mehsul_id: number;
+  olcu_id: number;
+  reng_id?: number | null;
+  miqdar: number;
+  vahid_alis_qiymeti: number;
+}
+
+export interface ReceivePurchaseOrder {
+  items: ReceivePurchaseOrderItem[];
+  qeyd?: string | null;
+}
+
+export interface ReceivePurchaseOrderItem {
+  setir_id: number;
+  miqdar: number;
+  vahid_alis_qiymeti?: number | null;
+}
+
+// Supplier Payment Types
+export interface SupplierPayment {
+  id: number;
+  tedarukcu_id: number;
+  tedarukcu_adi: string | null;
+  sifaris_id: number | null;
+  sifaris_nomresi: string | null;
+  mebleg: number;
+  odenis_usulu: string;
+  qeyd: string | null;
+  created_at: string;
+}
+
+export interface CreateSupplierPayment {
+  tedarukcu_id: number;
+  sifaris_id?: number | null;
+  mebleg: number;
+  odenis_usulu: string;
+  qeyd?: string | null;
+}
+
+export interface SupplierDebtSummary {
+  tedarukcu_id: number;
+  tedarukcu_adi: string;
+  telefon: string | null;
+  toplam_borc: number;
+  toplam_odenis: number;
+  qalan_borc: number;
+}
+
+export interface SupplierStatementEntry {
+  tarix: string;
+  novu: 'Başlanğıc borc' | 'Alış' | 'Ödəniş';
+  sifaris_nomresi: string | null;
+  qeyd: string | null;
+  borc: number;
+  odenis: number;
+  qaliq: number;
+}
+
+export interface SupplierStatement {
+  tedarukcu: Supplier;
+  emeliyyatlar: SupplierStatementEntry[];
+  toplam_borc: number;
+  toplam_odenis: number;
+  qalan_borc: number;
+}
+
+// Shift Types
+export interface Shift {
+  id: number;
+  istifadeci_id: number;
+  istifadeci_adi: string | null;
+  acilis_meblegi: number;
+  gozlenilen_nagd: number | null;
+  sayilan_nagd: number | null;
+  status: 'Açıq' | 'Bağlı';
+  qeyd: string | null;
+  acilma_tarixi: string;
+  baglanma_tarixi: string | null;
+}
+
+export interface OpenShift {
+  acilis_meblegi: number;
+  qeyd?: string | null;
+}
+
+export interface CloseShift {
+  sayilan_nagd: number;
+  qeyd?: string | null;
+}
+
+export interface ShiftPaymentTotal {
+  odenis_usulu: string;
+  satis_sayi: number;
+  satis_meblegi: number;
+  borc_odenisi: number;
+  cem: number;
+}
+
+export interface ZReport {
+  novbe: Shift;
+  satis_sayi: number;
+  toplam_satis: number;
+  endirim: number;
+  iade_sayi: number;
+  toplam_iade: number;
+  nagd_qaytarma: number;
+  odenisler: ShiftPaymentTotal[];
+  gozlenilen_nagd: number;
+  sayilan_nagd: number | null;
+  ferq: number | null;
+}
+
+export interface AuditEntry {
+  id: number;
+  istifadeci_id: number;
+  istifadeci_adi: string;
+  emr: string;
+  obyekt: string;
+  obyekt_id: number | null;
+  evvel: Record<string, unknown> | null;
+  sonra: Record<string, unknown> | null;
+  created_at: string;
+}
+
+export interface AuditFilter {
+  istifadeci_id?: number;
+  emr?: string;
+  obyekt?: string;
+  obyekt_id?: number;
+  baslangic_tarix?: string;
+  bitis_tarix?: string;
+  limit?: number;
+}
+
+export interface AppliedMigration {
+  versiya: number;
+  ad: string;
+  tetbiq_tarixi: string;
+}
+
+export interface SchemaVersion {
+  cari_versiya: number;
+  son_versiya: number;
+  miqrasiyalar: AppliedMigration[];
+}
+
+export interface BackupFile {
+  ad: string;
+  novu: 'Əl ilə' | 'Avtomatik' | 'Miqrasiya öncəsi' | 'Bərpa öncəsi' | 'Sıfırlama öncəsi';
+  olcu: number;
+  yaradilma_tarixi: string;
+}
+
+export interface AutoBackupStatus {
+  son_yoxlama: string | null;
+  xeta: string | null;
+}
+
+export type ResetScope = 'emeliyyatlar' | 'kataloq' | 'musteriler';
+
+export interface ImportError {
+  setir: number;
+  sutun: string | null;
+  xeta: string;
+}
+
+export interface ImportFieldChange {
+  sahe: string;
+  evvel: string | null;
+  sonra: string | null;
+}
+
+export interface ImportStockChange {
+  olcu: string;
+  evvelki_miqdar: number;
+  elave_miqdar: number;
+  yeni_miqdar: number;
+}
+
+export interface ImportChange {
+  setir: number;
+  barkod: string;
+  ad: string;
+  emeliyyat: 'Yeni' | 'Yenilənir' | 'Dəyişiklik yoxdur';
+  sahe_deyisiklikleri: ImportFieldChange[];
+  stok: ImportStockChange[];
+}
+
+export interface ImportReport {
+  setir_sayi: number;
+  yeni_mehsul: number;
+  yenilenen_mehsul: number;
+  elave_edilen_stok: number;
+  deyisiklikler: ImportChange[];
+  xetalar: ImportError[];
+  tetbiq_edildi: boolean;
+}
+
+export type ExportFormat = 'csv' | 'xlsx' | 'pdf';
+
+// Same filters as the report's own command
+export type ExportRequest =
+  | { hesabat: 'gunluk_satis'; tarix?: string | null }
+  | { hesabat: 'ayliq_satis'; ay?: string | null }
+  | { hesabat: 'az_qalan_stok' }
+  | { hesabat: 'satis_siyahisi'; baslangic_tarix?: string | null; bitis_tarix?: string | null }
+  | { hesabat: 'qazanc'; baslangic_tarix?: string | null; bitis_tarix?: string | null; kateqoriya_id?: number | null }
+  | { hesabat: 'stok_deyeri'; kateqoriya_id?: number | null }
+  | {
+      hesabat: 'mehsul_statistikasi';
+      baslangic_tarix?: string | null;
+      bitis_tarix?: string | null;
+      kateqoriya_id?: number | null;
+    }
+  | { hesabat: 'marka'; baslangic_tarix?: string | null; bitis_tarix?: string | null }
+  | { hesabat: 'kampaniya'; baslangic_tarix?: string | null; bitis_tarix?: string | null }
+  | {
+      hesabat: 'mehsul_hereketleri';
+      mehsul_id: number;
+      baslangic_tarix?: string | null;
+      bitis_tarix?: string | null;
+    }
+  | { hesabat: 'z_hesabati'; novbe_id: number };
+
+export type BarcodeSymbology = 'EAN-13' | 'EAN-8' | 'UPC-A' | 'Code128';
+
+export type BarcodeImageFormat = 'svg' | 'png';
+
+export interface Variant {
+  id: number;
+  mehsul_id: number;
+  olcu_id: number;
+  olcu: string | null;
+  reng_id: number | null;
+  reng: string | null;
+  barkod: string;
+  miqdar: number;
+  created_at: string | null;
+}
+
+export interface CreateVariant {
+  mehsul_id: number;
+  olcu_id: number;
+  reng_id?: number | null;
+  // Left empty, an internal barcode is issued
+  barkod?: string | null;
+}
+
+// Set when the scanned code belongs to a variant rather than the product
+export interface ScannedProduct extends Product {
+  variant: Variant | null;
+}
+
+export interface ProductWithStock extends Product {
+  stok: StockInfo[];
+}
+
+export interface MatrixCell {
+  olcu_id: number;
+  reng_id?: number | null;
+  miqdar: number;
+  minimum_miqdar?: number | null;
+}
+
+// Every size x colour pair gets a stock row; `hucreler` overrides the defaults
+export interface CreateProductMatrix {
+  mehsul: CreateProduct;
+  olcu_idler: number[];
+  reng_idler?: number[];
+  miqdar?: number;
+  minimum_miqdar?: number | null;
+  hucreler?: MatrixCell[];
+}